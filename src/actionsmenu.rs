@@ -0,0 +1,47 @@
+use crate::keymap::{Command, Keymap};
+
+// What can be done to the selected item, from its provider's actions, as a menu over the list
+// so actions can be found without knowing their keys. Picking one runs its command like its
+// key would.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionsMenu {
+    pub commands: Vec<Command>,
+    // The action and the keys bound to it, a line for each command.
+    pub lines: Vec<String>,
+    pub selected: usize,
+}
+
+impl ActionsMenu {
+    // None if the provider has nothing to do to the item.
+    pub fn new(keymap: &Keymap, actions: Vec<(Command, &'static str)>) -> Option<ActionsMenu> {
+        if actions.len() == 0 {
+            return None;
+        }
+        let width = actions.iter().map(|(_, name)| name.chars().count()).max().unwrap_or(0);
+        let lines = actions.iter()
+            .map(|(command, name)| {
+                let chords = keymap.chords(*command).iter().map(|chord| chord.to_string()).collect::<Vec<String>>();
+                if chords.len() == 0 {
+                    return name.to_string();
+                }
+                return format!("{:width$}  {}", name, chords.join(", "), width = width);
+            })
+            .collect::<Vec<String>>();
+        return Some(ActionsMenu {
+            commands: actions.into_iter().map(|(command, _)| command).collect(),
+            lines,
+            selected: 0,
+        });
+    }
+
+    // Moves the selection, wrapping around the ends.
+    pub fn move_by(&mut self, lines: isize) {
+        let len = self.commands.len() as isize;
+        self.selected = (self.selected as isize + lines).rem_euclid(len) as usize;
+    }
+
+    pub fn command(&self) -> Command {
+        return self.commands[self.selected];
+    }
+}
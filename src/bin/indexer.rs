@@ -1,6 +1,3 @@
-use std::io::Write;
-
-use windows::Win32::System::Environment::*;
 use windows::Win32::System::WindowsProgramming::*;
 use windows::Win32::NetworkManagement::NetManagement::*;
 use windows::Win32::Security::*;
@@ -32,86 +29,55 @@ struct IndexRoot {
 
 const INDEX_DIRECTORIES: &'static [IndexRoot] = &[
     IndexRoot {
-        path: "%ProgramData%\\Microsoft\\Windows\\Start Menu\\\0",
+        path: "%ProgramData%\\Microsoft\\Windows\\Start Menu\\",
         // kind: AppKind::Exe,
         max_depth: 99,
     },
     IndexRoot {
-        path: "%USERPROFILE%\\.cargo\\bin\\\0",
+        path: "%USERPROFILE%\\.cargo\\bin\\",
         // kind: AppKind::Exe,
         max_depth: 99,
     },
     IndexRoot {
-        path: "%USERPROFILE%\\AppData\\Roaming\\Microsoft\\Windows\\Start Menu\\\0",
+        path: "%USERPROFILE%\\AppData\\Roaming\\Microsoft\\Windows\\Start Menu\\",
         // kind: AppKind::Exe,
         max_depth: 99,
     },
     IndexRoot {
-        path: "%USERPROFILE%\\AppData\\Local\\Microsoft\\WindowsApps\\\0",
+        path: "%USERPROFILE%\\AppData\\Local\\Microsoft\\WindowsApps\\",
         // kind: AppKind::Exe,
         max_depth: 0,
     },
     IndexRoot {
-        path: "%ProgramData%\\chocolatey\\bin\\\0",
+        path: "%ProgramData%\\chocolatey\\bin\\",
         // kind: AppKind::Exe,
         max_depth: 99,
     },
     IndexRoot {
-        path: "%SystemRoot%\\\0",
+        path: "%SystemRoot%\\",
         // path2: std::ffi::OsStr::new("%SystemRoot%\\system32\\"),
         // kind: AppKind::Exe,
         max_depth: 0,
     },
     IndexRoot {
-        path: "%SystemRoot%\\system32\\\0",
+        path: "%SystemRoot%\\system32\\",
         // path2: std::ffi::OsStr::new("%SystemRoot%\\system32\\"),
         // kind: AppKind::Exe,
         max_depth: 0,
     },
     // IndexRoot {
-    //     path: "%ProgramFiles%\\WindowsApps\\\0",
+    //     path: "%ProgramFiles%\\WindowsApps\\",
     //     kind: AppKind::Appx,
     //     max_depth: 0,
     // },
     // These are not apps users would want to run...
     // IndexRoot {
-    //     path: "%SystemRoot%\\SystemApps\\\0",
+    //     path: "%SystemRoot%\\SystemApps\\",
     //     kind: AppKind::Appx,
     //     max_depth: 0,
     // },
 ];
 
-fn visit_directories<IntoPath>(root: IntoPath, cb: &mut dyn FnMut(&std::fs::DirEntry), max_depth: i32) -> std::io::Result<()>
-where IntoPath: Into<std::path::PathBuf> {
-    if max_depth < 0 {
-        return Ok(());
-    }
-
-    let path = root.into();
-    // switch::trace!("indexer", log::Level::Info, "Visiting {:?} available depth {}", path, max_depth);
-    let dirs = std::fs::read_dir(&path)?;
-
-    for entry in dirs {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_dir() {
-            visit_directories(&path, cb, max_depth - 1)?;
-        } else {
-            cb(&entry);
-        }
-    }
-
-    return Ok(());
-}
-
-fn save_apps(apps: &Vec<AppEntry>) -> anyhow::Result<()> {
-    let path = switch::path::get_app_data_path("apps.json")?;
-    let mut file = std::fs::File::create(path)?;
-    file.write_all(serde_json::to_string(&apps)?.as_bytes())?;
-    file.sync_all()?;
-    return Ok(());
-}
-
 fn index_exes() -> anyhow::Result<Vec<AppEntry>> {
     let mut apps: Vec<AppEntry> = vec![];
 
@@ -206,17 +172,9 @@ fn index_exes() -> anyhow::Result<Vec<AppEntry>> {
     };
 
     for root in INDEX_DIRECTORIES {
-        let expanded_path: String;
-        
-        unsafe {
-            let mut expanded: [u8; 512] = [0; 512];
-            // PSTR(expanded.as_mut_ptr())
-            let len = ExpandEnvironmentStringsA(PCSTR(root.path.as_ptr()), &mut expanded) as usize;
-            // Exclude null terminator which is needed for ExpandEnvironmentStringsA but not for rust strings.
-            expanded_path = String::from_utf8_lossy(&expanded[..len-1]).into();
-        }
+        let expanded_path = switch::expand::expand(root.path);
         switch::trace!("indexer", log::Level::Info, "Indexing {:?}", expanded_path);
-        if let Err(err) = visit_directories(expanded_path, &mut gather_exes, root.max_depth) {
+        if let Err(err) = switch::index::visit_directories(expanded_path, &mut gather_exes, root.max_depth) {
             switch::trace!("indexer", log::Level::Error, "Error: {:?}", err);
         }
     }
@@ -224,6 +182,29 @@ fn index_exes() -> anyhow::Result<Vec<AppEntry>> {
     return Ok(apps);
 }
 
+// Names of files and directories under the file search roots from config.json.
+fn index_files() -> Vec<switch::filesearchprovider::FileEntry> {
+    let config = switch::config::Config::load();
+    let mut files = vec![];
+
+    for root in config.file_search_roots.iter() {
+        let expanded_path = switch::expand::expand(root);
+        switch::trace!("indexer", log::Level::Info, "Indexing files {:?}", expanded_path);
+        let mut gather_files = |path: &std::path::Path, is_dir: bool| {
+            files.push(switch::filesearchprovider::FileEntry {
+                path: path.to_string_lossy().into_owned(),
+                is_dir,
+            });
+        };
+
+        if let Err(err) = switch::index::walk_ignoring(expanded_path, &config.file_search_ignore, config.file_search_max_depth, &mut gather_files) {
+            switch::trace!("indexer", log::Level::Error, "Error: {:?}", err);
+        }
+    }
+
+    return files;
+}
+
 // Activating this factory requires high integrity level some reason.
 unsafe fn index_appx() -> anyhow::Result<Vec<AppEntry>> {
     let mut apps: Vec<AppEntry> = vec![];
@@ -347,6 +328,8 @@ fn main() -> anyhow::Result<()> {
 
     let mut apps = index_exes()?;
     apps.append(unsafe { &mut index_appx()? });
-    save_apps(&apps)?;
+    switch::index::save("apps.json", &apps)?;
+
+    switch::index::save(switch::filesearchprovider::FILE_INDEX, &index_files())?;
     return Ok(());
 }
\ No newline at end of file
@@ -398,10 +398,7 @@ unsafe extern "system" fn low_level_keyboard_proc(code: i32, wparam: WPARAM, lpa
             }).collect();
 
             if terminals.len() == 0 {
-                let wt_path = "%USERPROFILE%\\AppData\\Local\\Microsoft\\WindowsApps\\wt.exe\0";
-                let mut expanded: [u8; 512] = [0; 512];
-                let len = windows::Win32::System::Environment::ExpandEnvironmentStringsA(PCSTR(wt_path.as_ptr()), &mut expanded) as usize;
-                let cmdline = String::from_utf8_lossy(&expanded[..len-1]).into();
+                let cmdline = switch::expand::expand("%USERPROFILE%\\AppData\\Local\\Microsoft\\WindowsApps\\wt.exe");
                 let _  = switch::create_process::create_process(cmdline);
             } else {
                 let current = terminals.iter().position(|&t| t.windowh == GetForegroundWindow());
@@ -511,8 +508,40 @@ fn initialize_index() {
     unsafe { let _ = switch::console::clear_console(); }
 }
 
+// Message only window that gets WM_CLIPBOARDUPDATE, the message is retrieved in the
+// runner's message loop since the window belongs to this thread.
+unsafe fn create_clipboard_listener() -> HWND {
+    let instance = windows::Win32::System::LibraryLoader::GetModuleHandleA(None);
+    let window = CreateWindowExA(
+        WINDOW_EX_STYLE(0),
+        PCSTR(b"STATIC\0".as_ptr()),
+        PCSTR(std::ptr::null()),
+        WINDOW_STYLE(0),
+        0, 0, 0, 0,
+        HWND_MESSAGE, None, instance, std::ptr::null());
+
+    if window.0 == 0 || !windows::Win32::System::DataExchange::AddClipboardFormatListener(window).as_bool() {
+        switch::trace!("clipboard", log::Level::Error, "Failed to listen to clipboard: {}", GetLastError().0);
+        return HWND(0);
+    }
+    return window;
+}
+
+fn record_clipboard(max_entries: usize) {
+    if switch::clipboard::excluded_from_history() {
+        switch::trace!("clipboard", log::Level::Info, "Clipboard asks not to be recorded.");
+        return;
+    }
+    let text = switch::clipboard::get_text();
+    let recorded = switch::clipboardhistory::ClipboardHistory::path()
+        .and_then(|path| switch::clipboardhistory::ClipboardHistory::record_to(path, &text, max_entries));
+    if let Err(e) = recorded {
+        switch::trace!("clipboard", log::Level::Error, "Failed to record clipboard: {:?}", e);
+    }
+}
+
 fn quake_terminal_runner(command: &str) -> anyhow::Result<()> {
-    switch::log::initialize_log(log::Level::Debug, &["init", "runtime", "hotkey", "message_queue"], switch::path::get_app_data_path("quake_terminal_runner.log")?)?;
+    switch::log::initialize_log(log::Level::Debug, &["init", "runtime", "hotkey", "message_queue", "clipboard"], switch::path::get_app_data_path("quake_terminal_runner.log")?)?;
     // log::info!("quake_terminal_runner started.");
     switch::trace!("init", log::Level::Info, "quake_terminal_runner started.");
 
@@ -656,7 +685,7 @@ fn quake_terminal_runner(command: &str) -> anyhow::Result<()> {
             assert!(waits.lock().unwrap().add(context_unwrapped.start_switch_read_overlapped.hEvent));
         }
 
-        let terminal_pid = windowprovider::getppid(GetCurrentProcessId());
+        let terminal_pid = windowprovider::getppid(&switch::processes::snapshot().unwrap_or_default(), GetCurrentProcessId());
         context.write().unwrap().quake_window = get_process_window(terminal_pid)?;
         configure_quake_window(context.read().unwrap().quake_window)?;
 
@@ -672,6 +701,13 @@ fn quake_terminal_runner(command: &str) -> anyhow::Result<()> {
         // }
 
         HOOK_HANDLE = SetWindowsHookExW(WH_KEYBOARD_LL, Some(low_level_keyboard_proc), HINSTANCE(0), 0);
+
+        let config = switch::config::Config::load();
+        let clipboard_listener = if config.clipboard_history {
+            create_clipboard_listener()
+        } else {
+            HWND(0)
+        };
         // let mut threads = vec![];
 
         loop {
@@ -799,14 +835,8 @@ fn quake_terminal_runner(command: &str) -> anyhow::Result<()> {
                         } else if h == context_clone.read().unwrap().btm_event {
                             // Same as above but we want to run unelevated because the path for btm is medium integrity.
                             if context_clone.read().unwrap().current_running_process.is_invalid() {
-                                let cmdline: String;
                                 // cargo install bottom
-                                let btm_path = "%USERPROFILE%\\.cargo\\bin\\btm.exe -b\0";
-                                let mut expanded: [u8; 512] = [0; 512];
-                                // PSTR(expanded.as_mut_ptr())
-                                let len = windows::Win32::System::Environment::ExpandEnvironmentStringsA(PCSTR(btm_path.as_ptr()), &mut expanded) as usize;
-                                // Exclude null terminator which is needed for ExpandEnvironmentStringsA but not for rust strings.
-                                cmdline = String::from_utf8_lossy(&expanded[..len-1]).into();
+                                let cmdline = switch::expand::expand("%USERPROFILE%\\.cargo\\bin\\btm.exe -b");
 
                                 let pid = switch::create_process::create_process(cmdline.clone());
 
@@ -863,6 +893,9 @@ fn quake_terminal_runner(command: &str) -> anyhow::Result<()> {
                                 //     set_foreground_window_terminal(context.quake_window)?;
                                 // }
                             },
+                            WM_CLIPBOARDUPDATE => {
+                                record_clipboard(config.clipboard_history_size);
+                            },
                             // WM_START_SWITCH => {
                             //     panic!("LOL do I really run commands received from window messages");
                             // }
@@ -880,6 +913,10 @@ fn quake_terminal_runner(command: &str) -> anyhow::Result<()> {
         }
 
         UnhookWindowsHookEx(HOOK_HANDLE);
+        if clipboard_listener.0 != 0 {
+            windows::Win32::System::DataExchange::RemoveClipboardFormatListener(clipboard_listener);
+            DestroyWindow(clipboard_listener);
+        }
         UnregisterHotKey(HWND(0), QUAKE_HOT_KEY_ID);
         CloseHandle(context.read().unwrap().start_switch_read_overlapped.hEvent);
         CloseHandle(context.read().unwrap().should_exit_event);
@@ -896,6 +933,15 @@ fn quake_terminal_runner(command: &str) -> anyhow::Result<()> {
     }
 }
 
+// Started by switch which passes the quake terminal it ran in, that may already be hidden
+// by the time this runs.
+fn quake_window(matches: &clap::ArgMatches) -> anyhow::Result<HWND> {
+    return match matches.value_of("window") {
+        Some(window) => Ok(HWND(window.parse::<isize>()?)),
+        None => Ok(unsafe { GetForegroundWindow() }),
+    };
+}
+
 fn main() -> anyhow::Result<()> {
     let matches = Command::new("quakerun")
         .arg(Arg::new("runner")
@@ -913,6 +959,20 @@ fn main() -> anyhow::Result<()> {
             .short('s')
             .long("stop")
             .help("Stop quake runner"))
+        .arg(Arg::new("type")
+            .short('t')
+            .long("type")
+            .help("Type text into the window that comes to the foreground after the quake terminal hides")
+            .value_name("TEXT")
+            .takes_value(true))
+        .arg(Arg::new("paste")
+            .long("paste")
+            .help("Paste the clipboard into the window that comes to the foreground after the quake terminal hides"))
+        .arg(Arg::new("window")
+            .long("window")
+            .help("Quake terminal window for --type and --paste to wait on, the foreground window if not given")
+            .value_name("HWND")
+            .takes_value(true))
         .arg(Arg::new("command")
             .short('c')
             .long("command")
@@ -932,6 +992,16 @@ fn main() -> anyhow::Result<()> {
         println!("Stopping quakerun...");
         set_event_by_name(EXIT_QUAKE_EVENT_NAME);
         return Ok(());
+    } else if let Some(text) = matches.value_of("type") {
+        if switch::sendinput::wait_for_foreground_change(quake_window(&matches)?, std::time::Duration::from_secs(2)) {
+            return switch::sendinput::type_text(text);
+        }
+        return Ok(());
+    } else if matches.occurrences_of("paste") == 1 {
+        if switch::sendinput::wait_for_foreground_change(quake_window(&matches)?, std::time::Duration::from_secs(2)) {
+            return switch::sendinput::paste();
+        }
+        return Ok(());
     }
 
     // if matches.value_of("command").is_none() {
@@ -0,0 +1,172 @@
+use crate::listcontentprovider::{ListContentProvider, ListItem};
+use crate::keymap::Command;
+use crate::startappsprovider::{AppEntry, AppEntryKind};
+use crate::bookmarks::Bookmark;
+
+use crate::log::*;
+
+pub struct BookmarkEntry {
+    pub bookmark: Bookmark,
+}
+
+impl BookmarkEntry {
+    // Opened the same way as web searches, the url goes to the default browser.
+    pub fn app(&self) -> AppEntry {
+        return AppEntry {
+            name: self.bookmark.title.clone(),
+            kind: AppEntryKind::Command { command: self.bookmark.url.clone() },
+            ..Default::default()
+        };
+    }
+}
+
+impl ListItem for BookmarkEntry {
+    fn as_any(&self) -> &dyn std::any::Any {
+        return self;
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        return self;
+    }
+
+    fn as_string(&self) -> String {
+        return format!("{}  {}  {}", self.bookmark.title, self.bookmark.url, self.bookmark.folder);
+    }
+
+    fn as_matchable_string(&self) -> String {
+        return self.bookmark.title.clone();
+    }
+}
+
+// Browser bookmarks from the locations in bookmarks in config.json, enter opens the url and
+// alt+w copies it. Reading them can take a moment with a big places.sqlite to copy, so they
+// are loaded in the background and show up once they're read.
+pub struct BookmarkProvider {
+    bookmarks: Vec<Bookmark>,
+    loading: Option<std::sync::mpsc::Receiver<Vec<Bookmark>>>,
+    query: String,
+    filtered: Vec<BookmarkEntry>,
+}
+
+impl BookmarkProvider {
+    pub fn new(config: &crate::config::Config) -> Box<Self> {
+        let locations = config.bookmarks.clone();
+        let mut provider = Self::with_bookmarks(vec![]);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            // The receiver is gone if switch exited before the bookmarks were read.
+            let _ = sender.send(crate::bookmarks::load(&locations));
+        });
+        provider.loading = Some(receiver);
+        return provider;
+    }
+
+    pub fn with_bookmarks(bookmarks: Vec<Bookmark>) -> Box<Self> {
+        let mut provider = Box::new(BookmarkProvider {
+            bookmarks,
+            loading: None,
+            query: String::new(),
+            filtered: vec![],
+        });
+        provider.filter();
+        return provider;
+    }
+
+    fn receive_bookmarks(&mut self) {
+        let bookmarks = match self.loading.as_ref().map(|loading| loading.try_recv()) {
+            Some(Ok(bookmarks)) => bookmarks,
+            Some(Err(std::sync::mpsc::TryRecvError::Empty)) | None => return,
+            Some(Err(std::sync::mpsc::TryRecvError::Disconnected)) => vec![],
+        };
+        crate::trace!("bookmarks", log::Level::Info, "Loaded {} bookmarks", bookmarks.len());
+        self.loading = None;
+        self.bookmarks = bookmarks;
+        self.filter();
+    }
+
+    fn filter(&mut self) {
+        let query = self.query.trim();
+        let mut scored = self.bookmarks.iter()
+            .enumerate()
+            .filter_map(|(i, bookmark)| {
+                if query.len() == 0 {
+                    return Some((0, i, bookmark));
+                }
+                // Titles count most, then the url, then the folders with a/b style queries.
+                let title_score = crate::fuzzy::score(query, &bookmark.title).map(|score| score * 2);
+                return title_score
+                    .or_else(|| crate::fuzzy::score(query, &bookmark.url))
+                    .or_else(|| crate::fuzzy::score_path(query, &bookmark.path()))
+                    .map(|score| (score, i, bookmark));
+            })
+            .collect::<Vec<(i32, usize, &Bookmark)>>();
+        // Browser order for ties.
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        self.filtered = scored.into_iter()
+            .map(|(_, _, bookmark)| BookmarkEntry { bookmark: bookmark.clone() })
+            .collect();
+    }
+}
+
+impl ListContentProvider for BookmarkProvider {
+    fn name(&self) -> &str {
+        return "Bookmarks";
+    }
+
+    fn qualifiers(&self) -> Vec<(String, String)> {
+        return vec![("toolbar/rust".to_string(), "bookmarks in a folder".to_string())];
+    }
+
+    fn total(&self) -> Option<usize> {
+        return Some(self.bookmarks.len());
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "open"), (Command::Copy, "copy url")];
+    }
+
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        self.receive_bookmarks();
+        return self.filtered.iter_mut().map(|e| e as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        self.receive_bookmarks();
+        return self.filtered.iter().map(|e| e.as_string()).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.filter();
+    }
+
+    fn start(&mut self, filtered_index: usize, elevated: bool) {
+        let app = match self.filtered.get(filtered_index) {
+            Some(entry) => entry.app(),
+            None => return,
+        };
+
+        crate::trace!("start", log::Level::Info, "Bookmark elevated {:?}: {:?}", elevated, app);
+        if let Err(e) = app.launch(elevated) {
+            crate::trace!("start", log::Level::Error, "Bookmark error: {:?}", e);
+        }
+    }
+
+    // Bookmarks belong to the browser, they're removed there.
+    fn remove(&mut self, _filtered_index: usize) {
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.filtered.get(filtered_index).map(|e| e.bookmark.url.clone());
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        return Some("*");
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "open"), (Command::Copy, "copy url")];
+    }
+}
@@ -0,0 +1,283 @@
+use crate::log::*;
+
+// Bookmarks from Chromium family browsers and Firefox for the bookmark provider.
+// Chromium keeps them in a Bookmarks json file next to the profile, Firefox in the
+// moz_bookmarks table of places.sqlite. Firefox keeps the database locked and recent changes
+// in places.sqlite-wal while it runs, so the database is read from a copy of both files.
+
+pub const CHROMIUM_BOOKMARKS: &str = "Bookmarks";
+pub const FIREFOX_PLACES: &str = "places.sqlite";
+
+// Firefox root folders by guid, the titles in the database are internal names.
+const FIREFOX_ROOTS: [(&str, &str); 4] = [
+    ("menu________", "Bookmarks Menu"),
+    ("toolbar_____", "Bookmarks Toolbar"),
+    ("unfiled_____", "Other Bookmarks"),
+    ("mobile______", "Mobile Bookmarks"),
+];
+const FIREFOX_ROOT: &str = "root________";
+// Tags are folders of bookmarks under this root, the same pages are also bookmarked normally.
+const FIREFOX_TAGS: &str = "tags________";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bookmark {
+    pub title: String,
+    pub url: String,
+    // Folders from the root down separated by /, like "Bookmarks bar/Rust".
+    pub folder: String,
+}
+
+impl Bookmark {
+    // Folder path with the title, what path style queries match against.
+    pub fn path(&self) -> String {
+        if self.folder.len() == 0 {
+            return self.title.clone();
+        }
+        return format!("{}/{}", self.folder, self.title);
+    }
+}
+
+// Firefox queries and bookmarklets can't be opened outside the browser.
+pub fn openable(url: &str) -> bool {
+    return !url.starts_with("place:") && !url.starts_with("javascript:") && url.len() > 0;
+}
+
+fn folder_path(parent: &str, name: &str) -> String {
+    if parent.len() == 0 {
+        return name.to_owned();
+    }
+    return format!("{}/{}", parent, name);
+}
+
+fn visit_chromium_node(node: &serde_json::Value, folder: &str, bookmarks: &mut Vec<Bookmark>) {
+    let name = node["name"].as_str().unwrap_or("");
+    match node["type"].as_str() {
+        Some("url") => {
+            let url = node["url"].as_str().unwrap_or("");
+            if openable(url) {
+                bookmarks.push(Bookmark {
+                    title: if name.len() > 0 { name.to_owned() } else { url.to_owned() },
+                    url: url.to_owned(),
+                    folder: folder.to_owned(),
+                });
+            }
+        },
+        Some("folder") => {
+            let folder = folder_path(folder, name);
+            for child in node["children"].as_array().into_iter().flatten() {
+                visit_chromium_node(child, &folder, bookmarks);
+            }
+        },
+        _ => {},
+    }
+}
+
+// Bookmarks from a Chromium Bookmarks file, in the order of the bookmark manager.
+pub fn parse_chromium(json: &str) -> anyhow::Result<Vec<Bookmark>> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let roots = match value["roots"].as_object() {
+        Some(roots) => roots,
+        None => anyhow::bail!("No roots in bookmarks"),
+    };
+
+    let mut bookmarks = vec![];
+    // Object order isn't kept, the roots are listed in the order chromium shows them.
+    for root in ["bookmark_bar", "other", "synced"] {
+        if let Some(node) = roots.get(root) {
+            visit_chromium_node(node, "", &mut bookmarks);
+        }
+    }
+    return Ok(bookmarks);
+}
+
+pub fn load_chromium(path: &std::path::Path) -> anyhow::Result<Vec<Bookmark>> {
+    return parse_chromium(&std::fs::read_to_string(path)?);
+}
+
+struct FirefoxFolder {
+    parent: i64,
+    position: i64,
+    title: String,
+    guid: String,
+}
+
+// Folder path of a Firefox bookmark and the positions of its folders from the root, which
+// sort in the order of the library window. None if it's a tag or not under the root.
+fn firefox_folder(folders: &std::collections::HashMap<i64, FirefoxFolder>, parent: i64) -> Option<(String, Vec<i64>)> {
+    let mut names = vec![];
+    let mut positions = vec![];
+    let mut id = parent;
+    // Bounded in case the parents loop in a broken database.
+    for _ in 0..folders.len() + 1 {
+        let folder = folders.get(&id)?;
+        if folder.guid == FIREFOX_ROOT {
+            names.reverse();
+            positions.reverse();
+            return Some((names.join("/"), positions));
+        }
+        if folder.guid == FIREFOX_TAGS {
+            return None;
+        }
+
+        let title = FIREFOX_ROOTS.iter()
+            .find(|(guid, _)| *guid == folder.guid)
+            .map(|(_, title)| title.to_string())
+            .unwrap_or_else(|| folder.title.clone());
+        names.push(title);
+        positions.push(folder.position);
+        id = folder.parent;
+    }
+    return None;
+}
+
+// Bookmarks from an open places.sqlite, in the order of the library window.
+pub fn query_firefox(connection: &rusqlite::Connection) -> anyhow::Result<Vec<Bookmark>> {
+    const BOOKMARK: i64 = 1;
+    const FOLDER: i64 = 2;
+
+    let mut statement = connection.prepare(
+        "SELECT b.id, b.type, b.parent, b.position, b.title, b.guid, p.url, p.title
+         FROM moz_bookmarks b LEFT JOIN moz_places p ON b.fk = p.id")?;
+    let rows = statement.query_map([], |row| {
+        return Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, Option<String>>(4)?,
+            row.get::<_, String>(5)?,
+            row.get::<_, Option<String>>(6)?,
+            row.get::<_, Option<String>>(7)?,
+        ));
+    })?.collect::<Result<Vec<_>, _>>()?;
+
+    let folders = rows.iter()
+        .filter(|row| row.1 == FOLDER)
+        .map(|(id, _, parent, position, title, guid, _, _)| (*id, FirefoxFolder {
+            parent: *parent,
+            position: *position,
+            title: title.clone().unwrap_or_default(),
+            guid: guid.clone(),
+        }))
+        .collect::<std::collections::HashMap<i64, FirefoxFolder>>();
+
+    let mut sorted = vec![];
+    for (_, kind, parent, position, title, _, url, page_title) in rows.into_iter() {
+        let url = match url {
+            Some(url) if kind == BOOKMARK && openable(&url) => url,
+            _ => continue,
+        };
+        let (folder, mut order) = match firefox_folder(&folders, parent) {
+            Some(folder) => folder,
+            None => continue,
+        };
+        order.push(position);
+
+        let title = title.filter(|t| t.len() > 0)
+            .or(page_title.filter(|t| t.len() > 0))
+            .unwrap_or_else(|| url.clone());
+        sorted.push((order, Bookmark { title, url, folder }));
+    }
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    return Ok(sorted.into_iter().map(|(_, bookmark)| bookmark).collect());
+}
+
+// Copies places.sqlite and its write ahead log next to each other in the temp directory.
+fn copy_database(path: &std::path::Path) -> anyhow::Result<std::path::PathBuf> {
+    // Unique for each copy so profiles can be read at the same time.
+    static COPIES: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    let count = COPIES.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let copy = std::env::temp_dir().join(format!("switch-places-{}-{}.sqlite", std::process::id(), count));
+    std::fs::copy(path, &copy)?;
+
+    let wal = std::path::PathBuf::from(format!("{}-wal", path.to_string_lossy()));
+    let wal_copy = std::path::PathBuf::from(format!("{}-wal", copy.to_string_lossy()));
+    if wal.exists() {
+        std::fs::copy(&wal, &wal_copy)?;
+    } else {
+        let _ = std::fs::remove_file(&wal_copy);
+    }
+    return Ok(copy);
+}
+
+fn remove_database(copy: &std::path::Path) {
+    for suffix in ["", "-wal", "-shm", "-journal"] {
+        let _ = std::fs::remove_file(format!("{}{}", copy.to_string_lossy(), suffix));
+    }
+}
+
+pub fn load_firefox(path: &std::path::Path) -> anyhow::Result<Vec<Bookmark>> {
+    let copy = copy_database(path)?;
+    // Opened writable so sqlite can replay the copied log, the browser's files are only read.
+    let result = rusqlite::Connection::open(&copy)
+        .map_err(anyhow::Error::from)
+        .and_then(|connection| query_firefox(&connection));
+    remove_database(&copy);
+    return result;
+}
+
+pub fn load_file(path: &std::path::Path) -> anyhow::Result<Vec<Bookmark>> {
+    let is_places = path.file_name()
+        .map(|name| name.to_string_lossy().eq_ignore_ascii_case(FIREFOX_PLACES))
+        .unwrap_or(false);
+    if is_places {
+        return load_firefox(path);
+    }
+    return load_chromium(path);
+}
+
+// Bookmark files for a configured location. A file is used as is, a directory is a profile
+// or a directory of profiles like chromium's User Data or Firefox's Profiles.
+pub fn find_files(location: &std::path::Path) -> Vec<std::path::PathBuf> {
+    if location.is_file() {
+        return vec![location.to_owned()];
+    }
+
+    let in_profile = |dir: &std::path::Path| {
+        return [CHROMIUM_BOOKMARKS, FIREFOX_PLACES].iter()
+            .map(|name| dir.join(name))
+            .filter(|path| path.is_file())
+            .collect::<Vec<std::path::PathBuf>>();
+    };
+
+    let mut files = in_profile(location);
+    let mut profiles = match std::fs::read_dir(location) {
+        Ok(entries) => entries.filter_map(|e| e.ok())
+            .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+            .map(|e| e.path())
+            .collect::<Vec<std::path::PathBuf>>(),
+        Err(_) => vec![],
+    };
+    // Default before Profile 1 and so on, which sorts first anyway.
+    profiles.sort();
+    for profile in profiles.iter() {
+        files.extend(in_profile(profile));
+    }
+    return files;
+}
+
+// Concatenates bookmarks keeping only the first of each url.
+pub fn merge_bookmarks(lists: Vec<Vec<Bookmark>>) -> Vec<Bookmark> {
+    let mut seen = std::collections::HashSet::new();
+    return lists.into_iter()
+        .flatten()
+        .filter(|bookmark| seen.insert(bookmark.url.clone()))
+        .collect();
+}
+
+// Bookmarks from every configured location, a browser that can't be read is logged and skipped.
+pub fn load(locations: &[String]) -> Vec<Bookmark> {
+    let mut lists = vec![];
+    for location in locations.iter() {
+        let location = std::path::PathBuf::from(crate::expand::expand(location));
+        for file in find_files(&location) {
+            match load_file(&file) {
+                Ok(bookmarks) => lists.push(bookmarks),
+                Err(e) => {
+                    crate::trace!("bookmarks", log::Level::Error, "Failed to read {:?}: {:?}", file, e);
+                },
+            }
+        }
+    }
+    return merge_bookmarks(lists);
+}
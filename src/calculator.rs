@@ -0,0 +1,595 @@
+// Expression evaluator for the calculator provider.
+//
+// Grammar, lowest precedence first:
+// conversion := sum [("in" | "to" | "as") target]
+// sum        := product (("+" | "-") product)*
+// product    := unary (("*" | "/" | "%") unary)*
+// unary      := ("-" | "+") unary | power
+// power      := quantity [("^" | "**") unary]
+// quantity   := primary [unit]
+// primary    := number | constant | function "(" sum ("," sum)* ")" | "(" sum ")"
+//
+// Numbers can be decimal with exponent (1.5e3), hex (0xff), binary (0b101) or octal (0o17).
+// Target is a unit of the same dimension or one of hex, bin, oct.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dimension {
+    Data,
+    Time,
+    Length,
+    Mass,
+    Temperature,
+}
+
+pub struct Unit {
+    pub names: &'static [&'static str],
+    pub dimension: Dimension,
+    // value in base unit = value * scale + offset
+    pub scale: f64,
+    pub offset: f64,
+}
+
+const fn unit(names: &'static [&'static str], dimension: Dimension, scale: f64) -> Unit {
+    return Unit { names, dimension, scale, offset: 0.0 };
+}
+
+// Base units are byte, second, meter, kilogram and kelvin.
+// Byte units come before bit units so a case insensitive "mb" means megabytes.
+pub const UNITS: &[Unit] = &[
+    unit(&["B", "byte", "bytes"], Dimension::Data, 1.0),
+    unit(&["KB", "kB"], Dimension::Data, 1e3),
+    unit(&["MB"], Dimension::Data, 1e6),
+    unit(&["GB"], Dimension::Data, 1e9),
+    unit(&["TB"], Dimension::Data, 1e12),
+    unit(&["PB"], Dimension::Data, 1e15),
+    unit(&["KiB"], Dimension::Data, 1024.0),
+    unit(&["MiB"], Dimension::Data, 1048576.0),
+    unit(&["GiB"], Dimension::Data, 1073741824.0),
+    unit(&["TiB"], Dimension::Data, 1099511627776.0),
+    unit(&["PiB"], Dimension::Data, 1125899906842624.0),
+    unit(&["bit", "bits", "b"], Dimension::Data, 0.125),
+    unit(&["Kbit", "kbit", "Kb"], Dimension::Data, 125.0),
+    unit(&["Mbit", "Mb"], Dimension::Data, 125e3),
+    unit(&["Gbit", "Gb"], Dimension::Data, 125e6),
+
+    unit(&["ns", "nanosecond", "nanoseconds"], Dimension::Time, 1e-9),
+    unit(&["us", "µs", "microsecond", "microseconds"], Dimension::Time, 1e-6),
+    unit(&["ms", "millisecond", "milliseconds"], Dimension::Time, 1e-3),
+    unit(&["s", "sec", "secs", "second", "seconds"], Dimension::Time, 1.0),
+    unit(&["min", "mins", "minute", "minutes"], Dimension::Time, 60.0),
+    unit(&["h", "hr", "hrs", "hour", "hours"], Dimension::Time, 3600.0),
+    unit(&["d", "day", "days"], Dimension::Time, 86400.0),
+    unit(&["wk", "week", "weeks"], Dimension::Time, 604800.0),
+    // Julian year.
+    unit(&["yr", "year", "years"], Dimension::Time, 31557600.0),
+
+    unit(&["nm"], Dimension::Length, 1e-9),
+    unit(&["um", "µm"], Dimension::Length, 1e-6),
+    unit(&["mm"], Dimension::Length, 1e-3),
+    unit(&["cm"], Dimension::Length, 1e-2),
+    unit(&["m", "meter", "meters", "metre", "metres"], Dimension::Length, 1.0),
+    unit(&["km"], Dimension::Length, 1e3),
+    // "in" is the conversion keyword so inches have to be spelled out.
+    unit(&["inch", "inches"], Dimension::Length, 0.0254),
+    unit(&["ft", "foot", "feet"], Dimension::Length, 0.3048),
+    unit(&["yd", "yard", "yards"], Dimension::Length, 0.9144),
+    unit(&["mi", "mile", "miles"], Dimension::Length, 1609.344),
+
+    unit(&["mg"], Dimension::Mass, 1e-6),
+    unit(&["g", "gram", "grams"], Dimension::Mass, 1e-3),
+    unit(&["kg"], Dimension::Mass, 1.0),
+    unit(&["t", "tonne", "tonnes"], Dimension::Mass, 1e3),
+    unit(&["oz"], Dimension::Mass, 0.028349523125),
+    unit(&["lb", "lbs"], Dimension::Mass, 0.45359237),
+
+    Unit { names: &["K", "kelvin"], dimension: Dimension::Temperature, scale: 1.0, offset: 0.0 },
+    Unit { names: &["C", "°C", "celsius"], dimension: Dimension::Temperature, scale: 1.0, offset: 273.15 },
+    Unit { names: &["F", "°F", "fahrenheit"], dimension: Dimension::Temperature, scale: 5.0 / 9.0, offset: 273.15 - 32.0 * 5.0 / 9.0 },
+];
+
+pub fn find_unit(name: &str) -> Option<&'static Unit> {
+    return UNITS.iter().find(|u| u.names.contains(&name))
+        .or_else(|| UNITS.iter().find(|u| u.names.iter().any(|n| n.eq_ignore_ascii_case(name))));
+}
+
+impl Unit {
+    pub fn name(&self) -> &'static str {
+        return self.names[0];
+    }
+
+    fn to_base(&self, value: f64) -> f64 {
+        return value * self.scale + self.offset;
+    }
+
+    fn from_base(&self, value: f64) -> f64 {
+        return (value - self.offset) / self.scale;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Radix {
+    Decimal,
+    Hex,
+    Binary,
+    Octal,
+}
+
+// Result of evaluating an expression.
+#[derive(Clone, Copy)]
+pub struct Evaluation {
+    // In unit if there is one.
+    pub value: f64,
+    pub unit: Option<&'static Unit>,
+    pub radix: Radix,
+}
+
+impl std::fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let number = match self.radix {
+            Radix::Decimal => format_number(self.value),
+            radix => format_integer(self.value, radix).unwrap_or_else(|| format_number(self.value)),
+        };
+
+        return match self.unit {
+            Some(unit) => write!(f, "{} {}", number, unit.name()),
+            None => write!(f, "{}", number),
+        };
+    }
+}
+
+impl std::fmt::Debug for Evaluation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        return write!(f, "{}", self);
+    }
+}
+
+// Integers are shown without a fraction, everything else with up to 10 significant digits.
+pub fn format_number(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".into();
+    } else if value.is_infinite() {
+        return if value > 0.0 { "∞".into() } else { "-∞".into() };
+    }
+
+    if value == value.trunc() && value.abs() < 1e16 {
+        return format!("{}", value as i64);
+    }
+
+    let magnitude = value.abs().log10().floor() as i32;
+    if magnitude >= 16 || magnitude < -6 {
+        let formatted = format!("{:.9e}", value);
+        let (mantissa, exponent) = formatted.split_once('e').unwrap();
+        return format!("{}e{}", mantissa.trim_end_matches('0').trim_end_matches('.'), exponent);
+    }
+
+    let decimals = (9 - magnitude).max(0) as usize;
+    let formatted = format!("{:.*}", decimals, value);
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    return formatted.to_owned();
+}
+
+// Format value as an integer in radix, None if it isn't an integer.
+pub fn format_integer(value: f64, radix: Radix) -> Option<String> {
+    if value != value.trunc() || value.abs() >= 9.2e18 {
+        return None;
+    }
+
+    let integer = value as i64;
+    let (sign, magnitude) = if integer < 0 { ("-", integer.unsigned_abs()) } else { ("", integer as u64) };
+    return Some(match radix {
+        Radix::Decimal => format!("{}", integer),
+        Radix::Hex => format!("{}0x{:X}", sign, magnitude),
+        Radix::Binary => format!("{}0b{:b}", sign, magnitude),
+        Radix::Octal => format!("{}0o{:o}", sign, magnitude),
+    });
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(&'static str),
+}
+
+fn tokenize(input: &str) -> anyhow::Result<Vec<Token>> {
+    let chars = input.chars().collect::<Vec<char>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).map(|c| c.is_ascii_digit()).unwrap_or(false)) {
+            let (value, len) = lex_number(&chars[i..])?;
+            tokens.push(Token::Number(value));
+            i += len;
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '°' || c == 'µ' {
+            let len = chars[i..].iter()
+                .take_while(|&&c| c.is_alphanumeric() || c == '°' || c == 'µ' || c == '_')
+                .count();
+            tokens.push(Token::Ident(chars[i..i + len].iter().collect()));
+            i += len;
+            continue;
+        }
+
+        let op = match c {
+            '*' if chars.get(i + 1) == Some(&'*') => "**",
+            '+' => "+",
+            '-' | '−' => "-",
+            '*' | '×' => "*",
+            '/' | '÷' => "/",
+            '%' => "%",
+            '^' => "^",
+            '(' => "(",
+            ')' => ")",
+            ',' => ",",
+            _ => return Err(anyhow::Error::msg(format!("Unexpected '{}'", c))),
+        };
+        tokens.push(Token::Op(op));
+        // Ops are ascii and only ** takes more than one char.
+        i += op.len();
+    }
+
+    return Ok(tokens);
+}
+
+// Returns the number and how many chars it took.
+fn lex_number(chars: &[char]) -> anyhow::Result<(f64, usize)> {
+    let radix = match (chars.get(0), chars.get(1)) {
+        (Some('0'), Some('x')) | (Some('0'), Some('X')) => 16,
+        (Some('0'), Some('b')) | (Some('0'), Some('B')) => 2,
+        (Some('0'), Some('o')) | (Some('0'), Some('O')) => 8,
+        _ => 10,
+    };
+
+    if radix != 10 {
+        let digits = chars[2..].iter()
+            .take_while(|&&c| c.is_digit(radix) || c == '_')
+            .collect::<String>();
+        let value = u64::from_str_radix(&digits.replace('_', ""), radix)
+            .map_err(|_| anyhow::Error::msg(format!("Bad number '{}'", chars[..2 + digits.chars().count()].iter().collect::<String>())))?;
+        return Ok((value as f64, 2 + digits.chars().count()));
+    }
+
+    let mut len = chars.iter().take_while(|&&c| c.is_ascii_digit() || c == '_').count();
+    if chars.get(len) == Some(&'.') {
+        len += 1;
+        len += chars[len..].iter().take_while(|&&c| c.is_ascii_digit() || c == '_').count();
+    }
+
+    // Exponent only if digits follow, otherwise the e is an identifier like in "2 exp(1)".
+    if let Some('e') | Some('E') = chars.get(len) {
+        let sign = match chars.get(len + 1) {
+            Some('+') | Some('-') => 1,
+            _ => 0,
+        };
+        let digits = chars[(len + 1 + sign).min(chars.len())..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if digits > 0 {
+            len += 1 + sign + digits;
+        }
+    }
+
+    let text = chars[..len].iter().filter(|&&c| c != '_').collect::<String>();
+    let value = text.parse::<f64>().map_err(|_| anyhow::Error::msg(format!("Bad number '{}'", text)))?;
+    return Ok((value, len));
+}
+
+// Value in base units while evaluating.
+#[derive(Clone, Copy)]
+struct Quantity {
+    value: f64,
+    dimension: Option<Dimension>,
+    // Unit to display the result in if no conversion is asked for.
+    unit: Option<&'static Unit>,
+}
+
+impl Quantity {
+    fn scalar(value: f64) -> Quantity {
+        return Quantity { value, dimension: None, unit: None };
+    }
+
+    // Applies f to the value. Temperatures are kept in kelvin from absolute zero, so they're
+    // changed in the unit they were written in: -40 C is 40 below freezing and 2 * 30 C is 60 C.
+    fn map(self, f: impl Fn(f64) -> f64) -> Quantity {
+        return match (self.dimension, self.unit) {
+            (Some(Dimension::Temperature), Some(unit)) => Quantity { value: unit.to_base(f(unit.from_base(self.value))), ..self },
+            _ => Quantity { value: f(self.value), ..self },
+        };
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        return self.tokens.get(self.pos);
+    }
+
+    fn peek_op(&self, op: &str) -> bool {
+        return matches!(self.peek(), Some(Token::Op(o)) if *o == op);
+    }
+
+    fn expect_op(&mut self, op: &str) -> anyhow::Result<()> {
+        if !self.peek_op(op) {
+            return Err(anyhow::Error::msg(format!("Expected '{}'", op)));
+        }
+        self.pos += 1;
+        return Ok(());
+    }
+
+    fn sum(&mut self) -> anyhow::Result<Quantity> {
+        let mut left = self.product()?;
+        loop {
+            let subtract = if self.peek_op("+") {
+                false
+            } else if self.peek_op("-") {
+                true
+            } else {
+                return Ok(left);
+            };
+            self.pos += 1;
+
+            let right = self.product()?;
+            if left.dimension != right.dimension {
+                return Err(anyhow::Error::msg("Can't add quantities of different dimensions"));
+            }
+            if left.dimension == Some(Dimension::Temperature) {
+                return Err(anyhow::Error::msg("Can't add temperatures"));
+            }
+            left.value = if subtract { left.value - right.value } else { left.value + right.value };
+            left.unit = left.unit.or(right.unit);
+        }
+    }
+
+    fn product(&mut self) -> anyhow::Result<Quantity> {
+        let mut left = self.unary()?;
+        loop {
+            let op = if self.peek_op("*") {
+                "*"
+            } else if self.peek_op("/") {
+                "/"
+            } else if self.peek_op("%") {
+                "%"
+            } else {
+                return Ok(left);
+            };
+            self.pos += 1;
+
+            let right = self.unary()?;
+            left = match (op, left.dimension, right.dimension) {
+                ("*", _, None) => left.map(|value| value * right.value),
+                ("*", None, _) => right.map(|value| left.value * value),
+                ("/", _, None) => left.map(|value| value / right.value),
+                // Ratio of two quantities of the same kind, like 1 GiB / 1 MiB.
+                ("/", Some(l), Some(r)) if l == r && l != Dimension::Temperature => Quantity::scalar(left.value / right.value),
+                ("%", _, None) => left.map(|value| value % right.value),
+                ("%", Some(l), Some(r)) if l == r && l != Dimension::Temperature => Quantity { value: left.value % right.value, ..left },
+                _ => return Err(anyhow::Error::msg("Unsupported combination of units")),
+            };
+        }
+    }
+
+    fn unary(&mut self) -> anyhow::Result<Quantity> {
+        if self.peek_op("-") {
+            self.pos += 1;
+            return Ok(self.unary()?.map(|value| -value));
+        } else if self.peek_op("+") {
+            self.pos += 1;
+            return self.unary();
+        }
+        return self.power();
+    }
+
+    fn power(&mut self) -> anyhow::Result<Quantity> {
+        let base = self.quantity()?;
+        if self.peek_op("^") || self.peek_op("**") {
+            self.pos += 1;
+            // Right associative, and binds tighter than unary minus on the left: -2^2 = -4.
+            let exponent = self.unary()?;
+            if base.dimension.is_some() || exponent.dimension.is_some() {
+                return Err(anyhow::Error::msg("Can't raise units to a power"));
+            }
+            return Ok(Quantity::scalar(base.value.powf(exponent.value)));
+        }
+        return Ok(base);
+    }
+
+    fn quantity(&mut self) -> anyhow::Result<Quantity> {
+        let q = self.primary()?;
+        if let Some(Token::Ident(name)) = self.peek() {
+            if is_conversion_keyword(name) {
+                return Ok(q);
+            }
+            if let Some(unit) = find_unit(name) {
+                if q.dimension.is_some() {
+                    return Err(anyhow::Error::msg(format!("Unexpected unit '{}'", name)));
+                }
+                self.pos += 1;
+                return Ok(Quantity {
+                    value: unit.to_base(q.value),
+                    dimension: Some(unit.dimension),
+                    unit: Some(unit),
+                });
+            }
+        }
+        return Ok(q);
+    }
+
+    fn primary(&mut self) -> anyhow::Result<Quantity> {
+        let token = self.peek().cloned().ok_or(anyhow::Error::msg("Unexpected end of expression"))?;
+        self.pos += 1;
+
+        return match token {
+            Token::Number(value) => Ok(Quantity::scalar(value)),
+            Token::Op("(") => {
+                let q = self.sum()?;
+                self.expect_op(")")?;
+                Ok(q)
+            },
+            Token::Ident(name) => {
+                if self.peek_op("(") {
+                    self.pos += 1;
+                    let mut args = vec![self.sum()?];
+                    while self.peek_op(",") {
+                        self.pos += 1;
+                        args.push(self.sum()?);
+                    }
+                    self.expect_op(")")?;
+                    return call(&name, &args);
+                }
+
+                match constant(&name) {
+                    Some(value) => Ok(Quantity::scalar(value)),
+                    None => Err(anyhow::Error::msg(format!("Unknown name '{}'", name))),
+                }
+            },
+            Token::Op(op) => Err(anyhow::Error::msg(format!("Unexpected '{}'", op))),
+        };
+    }
+}
+
+fn is_conversion_keyword(name: &str) -> bool {
+    return name == "in" || name == "to" || name == "as";
+}
+
+fn constant(name: &str) -> Option<f64> {
+    return match name.to_lowercase().as_str() {
+        "pi" | "π" => Some(std::f64::consts::PI),
+        "tau" | "τ" => Some(std::f64::consts::TAU),
+        "e" => Some(std::f64::consts::E),
+        _ => None,
+    };
+}
+
+fn call(name: &str, args: &[Quantity]) -> anyhow::Result<Quantity> {
+    let scalar = |i: usize| -> anyhow::Result<f64> {
+        let arg = args.get(i).ok_or(anyhow::Error::msg(format!("Missing argument to {}", name)))?;
+        if arg.dimension.is_some() {
+            return Err(anyhow::Error::msg(format!("{} takes a number", name)));
+        }
+        return Ok(arg.value);
+    };
+
+    let arity = match name {
+        "min" | "max" => args.len().max(1),
+        "pow" | "atan2" | "log" if args.len() == 2 => 2,
+        _ => 1,
+    };
+    if args.len() != arity {
+        return Err(anyhow::Error::msg(format!("Wrong number of arguments to {}", name)));
+    }
+
+    // min, max and abs work on quantities too.
+    match name {
+        "abs" => return Ok(args[0].map(f64::abs)),
+        "min" | "max" => {
+            if args.iter().any(|a| a.dimension != args[0].dimension) {
+                return Err(anyhow::Error::msg(format!("Arguments to {} must have the same dimension", name)));
+            }
+            let pick = |a: f64, b: f64| if name == "min" { a.min(b) } else { a.max(b) };
+            let value = args.iter().skip(1).fold(args[0].value, |acc, a| pick(acc, a.value));
+            return Ok(Quantity { value, ..args[0] });
+        },
+        _ => {},
+    }
+
+    let x = scalar(0)?;
+    let value = match name {
+        "sqrt" => x.sqrt(),
+        "cbrt" => x.cbrt(),
+        "exp" => x.exp(),
+        "ln" => x.ln(),
+        "log" if args.len() == 2 => x.ln() / scalar(1)?.ln(),
+        "log" | "log10" => x.log10(),
+        "log2" => x.log2(),
+        "sin" => x.sin(),
+        "cos" => x.cos(),
+        "tan" => x.tan(),
+        "asin" => x.asin(),
+        "acos" => x.acos(),
+        "atan" => x.atan(),
+        "atan2" => x.atan2(scalar(1)?),
+        "sinh" => x.sinh(),
+        "cosh" => x.cosh(),
+        "tanh" => x.tanh(),
+        "floor" => x.floor(),
+        "ceil" => x.ceil(),
+        "round" => x.round(),
+        "trunc" => x.trunc(),
+        "pow" => x.powf(scalar(1)?),
+        "deg" => x.to_degrees(),
+        "rad" => x.to_radians(),
+        _ => return Err(anyhow::Error::msg(format!("Unknown function '{}'", name))),
+    };
+    return Ok(Quantity::scalar(value));
+}
+
+pub fn evaluate(input: &str) -> anyhow::Result<Evaluation> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+    };
+
+    if parser.tokens.len() == 0 {
+        return Err(anyhow::Error::msg("Empty expression"));
+    }
+
+    let q = parser.sum()?;
+    let mut result = Evaluation {
+        value: q.unit.map(|u| u.from_base(q.value)).unwrap_or(q.value),
+        unit: q.unit,
+        radix: Radix::Decimal,
+    };
+
+    if let Some(Token::Ident(keyword)) = parser.peek().cloned() {
+        if is_conversion_keyword(&keyword) {
+            parser.pos += 1;
+            let target = match parser.peek().cloned() {
+                Some(Token::Ident(target)) => target,
+                _ => return Err(anyhow::Error::msg(format!("Expected a unit after '{}'", keyword))),
+            };
+            parser.pos += 1;
+
+            let radix = match target.to_lowercase().as_str() {
+                "hex" => Some(Radix::Hex),
+                "bin" | "binary" => Some(Radix::Binary),
+                "oct" | "octal" => Some(Radix::Octal),
+                "dec" | "decimal" => Some(Radix::Decimal),
+                _ => None,
+            };
+
+            if let Some(radix) = radix {
+                result.radix = radix;
+            } else {
+                let unit = find_unit(&target).ok_or(anyhow::Error::msg(format!("Unknown unit '{}'", target)))?;
+                if q.dimension != Some(unit.dimension) {
+                    return Err(anyhow::Error::msg(format!("Can't convert to {}", unit.name())));
+                }
+                result.value = unit.from_base(q.value);
+                result.unit = Some(unit);
+            }
+        }
+    }
+
+    if let Some(token) = parser.peek() {
+        let token = match token {
+            Token::Number(value) => format_number(*value),
+            Token::Ident(name) => name.clone(),
+            Token::Op(op) => op.to_string(),
+        };
+        return Err(anyhow::Error::msg(format!("Unexpected '{}'", token)));
+    }
+
+    return Ok(result);
+}
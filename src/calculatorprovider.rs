@@ -0,0 +1,152 @@
+use crate::listcontentprovider::{ListContentProvider, ListItem};
+use crate::keymap::Command;
+use crate::calculator::{Evaluation, Radix};
+
+use crate::log::*;
+
+pub struct CalculatorResult {
+    // What gets copied to the clipboard, empty if the expression has an error.
+    pub value: String,
+    // What is shown in the list.
+    pub text: String,
+}
+
+impl ListItem for CalculatorResult {
+    fn as_any(&self) -> &dyn std::any::Any {
+        return self;
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        return self;
+    }
+
+    fn as_string(&self) -> String {
+        return self.text.clone();
+    }
+
+    // Tab puts the result back in the input so it can be used in the next calculation.
+    fn as_matchable_string(&self) -> String {
+        return self.value.clone();
+    }
+}
+
+// Evaluates the query as an arithmetic expression or unit conversion, see calculator.rs.
+// The result is the first item, integers are also shown in hex and binary.
+pub struct CalculatorProvider {
+    query: String,
+    results: Vec<CalculatorResult>,
+}
+
+fn result(value: String) -> CalculatorResult {
+    return CalculatorResult {
+        text: format!("= {}", value),
+        value,
+    };
+}
+
+pub fn calculator_results(query: &str) -> Vec<CalculatorResult> {
+    if query.trim().len() == 0 {
+        return vec![];
+    }
+
+    let evaluation = match crate::calculator::evaluate(query) {
+        Ok(evaluation) => evaluation,
+        Err(e) => {
+            return vec![CalculatorResult {
+                value: String::new(),
+                text: format!("{}", e),
+            }];
+        },
+    };
+
+    let mut results = vec![result(evaluation.to_string())];
+
+    if evaluation.unit.is_none() && crate::calculator::format_integer(evaluation.value, Radix::Hex).is_some() {
+        for radix in [Radix::Decimal, Radix::Hex, Radix::Binary] {
+            if radix == evaluation.radix {
+                continue;
+            }
+            results.push(result(Evaluation { radix, ..evaluation }.to_string()));
+        }
+    }
+
+    return results;
+}
+
+impl CalculatorProvider {
+    pub fn new() -> Box<Self> {
+        return Box::new(CalculatorProvider {
+            query: String::new(),
+            results: vec![],
+        });
+    }
+}
+
+impl ListContentProvider for CalculatorProvider {
+    fn name(&self) -> &str {
+        return "Calculator";
+    }
+
+    fn qualifiers(&self) -> Vec<(String, String)> {
+        return vec![
+            ("3.5 GiB in MB".to_string(), "convert units".to_string()),
+            ("0xff * 2 in bin".to_string(), "show in another base".to_string()),
+        ];
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "copy")];
+    }
+
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        return self.results.iter_mut().map(|r| r as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        return self.results.iter().map(|r| r.text.clone()).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.results = calculator_results(&self.query);
+    }
+
+    // Copy the result.
+    fn start(&mut self, filtered_index: usize, _elevated: bool) {
+        if filtered_index >= self.results.len() || self.results[filtered_index].value.len() == 0 {
+            return;
+        }
+
+        let value = &self.results[filtered_index].value;
+        crate::trace!("start", log::Level::Info, "Copy calculator result: {}", value);
+        if let Err(e) = crate::clipboard::set_text(value) {
+            crate::trace!("start", log::Level::Error, "Copy calculator result failed: {:?}", e);
+        }
+    }
+
+    fn remove(&mut self, _filtered_index: usize) {
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.results.get(filtered_index)
+            .filter(|r| r.value.len() > 0)
+            .map(|r| r.value.clone());
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        return Some("=");
+    }
+
+    // Expressions typed in any provider, but not plain numbers or words like pi that could be
+    // searched for.
+    fn claims_query(&self, query: &str) -> bool {
+        let query = query.trim();
+        return query.chars().any(|c| c.is_ascii_digit())
+            && query.parse::<f64>().is_err()
+            && crate::calculator::evaluate(query).is_ok();
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "copy")];
+    }
+}
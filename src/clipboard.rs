@@ -3,22 +3,174 @@ use windows::Win32::System::DataExchange::*;
 use windows::Win32::System::SystemServices::*;
 use windows::Win32::System::Memory::*;
 
+use crate::log::*;
+
+// Text clipboard, SystemClipboard is the real one and MemoryClipboard is for tests.
+pub trait Clipboard {
+    // Empty string if the clipboard has no text.
+    fn get_text(&mut self) -> anyhow::Result<String>;
+    fn set_text(&mut self, text: &str) -> anyhow::Result<()>;
+}
+
+#[derive(Default)]
+pub struct SystemClipboard;
+
+#[derive(Default, Debug, Clone)]
+pub struct MemoryClipboard {
+    pub text: Option<String>,
+}
+
+impl MemoryClipboard {
+    pub fn new() -> Box<Self> {
+        return Box::new(MemoryClipboard::default());
+    }
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get_text(&mut self) -> anyhow::Result<String> {
+        return Ok(self.text.clone().unwrap_or_default());
+    }
+
+    fn set_text(&mut self, text: &str) -> anyhow::Result<()> {
+        self.text = Some(text.to_owned());
+        return Ok(());
+    }
+}
+
+// Closes the clipboard when dropped so early returns don't leave it open for everyone else.
+struct OpenClipboardGuard;
+
+impl OpenClipboardGuard {
+    fn open() -> anyhow::Result<Self> {
+        unsafe {
+            if !OpenClipboard(HWND(0)).as_bool() {
+                return Err(anyhow::Error::from(windows::core::Error::from_win32()));
+            }
+        }
+        return Ok(OpenClipboardGuard);
+    }
+}
+
+impl Drop for OpenClipboardGuard {
+    fn drop(&mut self) {
+        unsafe {
+            CloseClipboard();
+        }
+    }
+}
+
+impl Clipboard for SystemClipboard {
+    fn get_text(&mut self) -> anyhow::Result<String> {
+        let _guard = OpenClipboardGuard::open()?;
+        unsafe {
+            // Windows synthesizes CF_UNICODETEXT from CF_TEXT and CF_OEMTEXT so this covers all text.
+            let data_handle = GetClipboardData(CF_UNICODETEXT.0);
+            if data_handle.is_invalid() {
+                return Ok(String::new());
+            }
+
+            let data = GlobalLock(data_handle.0) as *const u16;
+            if data.is_null() {
+                return Err(anyhow::Error::from(windows::core::Error::from_win32()));
+            }
+
+            // Don't trust the data to be null terminated, stop at the end of the allocation.
+            let max_len = GlobalSize(data_handle.0) / std::mem::size_of::<u16>();
+            let wide = std::slice::from_raw_parts(data, max_len);
+            let len = wide.iter().position(|&c| c == 0).unwrap_or(max_len);
+            let text = String::from_utf16_lossy(&wide[..len]);
+
+            GlobalUnlock(data_handle.0);
+            return Ok(text);
+        }
+    }
+
+    fn set_text(&mut self, text: &str) -> anyhow::Result<()> {
+        let text = (text.to_owned() + "\0").encode_utf16().collect::<Vec<u16>>();
+        let _guard = OpenClipboardGuard::open()?;
+        unsafe {
+            if !EmptyClipboard().as_bool() {
+                return Err(anyhow::Error::from(windows::core::Error::from_win32()));
+            }
+
+            // The clipboard owns the memory after SetClipboardData succeeds.
+            let memory = GlobalAlloc(GMEM_MOVEABLE, text.len() * std::mem::size_of::<u16>());
+            if memory == 0 {
+                return Err(anyhow::Error::from(windows::core::Error::from_win32()));
+            }
+
+            let data = GlobalLock(memory) as *mut u16;
+            if data.is_null() {
+                let error = windows::core::Error::from_win32();
+                GlobalFree(memory);
+                return Err(anyhow::Error::from(error));
+            }
+            std::ptr::copy_nonoverlapping(text.as_ptr(), data, text.len());
+            GlobalUnlock(memory);
+
+            if SetClipboardData(CF_UNICODETEXT.0, HANDLE(memory)).is_invalid() {
+                let error = windows::core::Error::from_win32();
+                GlobalFree(memory);
+                return Err(anyhow::Error::from(error));
+            }
+
+            return Ok(());
+        }
+    }
+}
+
+// Clipboard text or empty string, errors are logged.
 pub fn get_text() -> String {
-    unsafe {
-        OpenClipboard(HWND(0));
-        let data_handle = GetClipboardData(CF_TEXT.0);
-        if data_handle.is_invalid() {
-            return "".into();
+    return match SystemClipboard.get_text() {
+        Ok(text) => text,
+        Err(e) => {
+            crate::trace!("clipboard", log::Level::Error, "Failed to read clipboard: {:?}", e);
+            String::new()
         }
+    };
+}
 
-        let data = GlobalLock(data_handle.0);
-        // let text = String::from_utf8_lossy(std::mem::transmute(data)).into() as String;
-        let cstr = std::ffi::CStr::from_ptr(data as *const _);
-        let text = cstr.to_str().unwrap();
+pub fn set_text(text: &str) -> anyhow::Result<()> {
+    return SystemClipboard.set_text(text);
+}
 
-        GlobalUnlock(data_handle.0);
+fn register_format(name: &str) -> u32 {
+    let name = (name.to_owned() + "\0").encode_utf16().collect::<Vec<u16>>();
+    return unsafe { RegisterClipboardFormatW(windows::core::PCWSTR(name.as_ptr())) };
+}
 
-        CloseClipboard();
-        return text.into();
+// Password managers copy with one of these formats next to the text to keep it out of
+// clipboard history: ExcludeClipboardContentFromMonitorProcessing with any data, or
+// CanIncludeInClipboardHistory holding a DWORD 0. True when the text shouldn't be recorded,
+// or when the clipboard can't be read to tell.
+pub fn excluded_from_history() -> bool {
+    let exclude = register_format("ExcludeClipboardContentFromMonitorProcessing");
+    let include = register_format("CanIncludeInClipboardHistory");
+    unsafe {
+        if exclude != 0 && IsClipboardFormatAvailable(exclude).as_bool() {
+            return true;
+        }
+        if include == 0 || !IsClipboardFormatAvailable(include).as_bool() {
+            return false;
+        }
+
+        let _guard = match OpenClipboardGuard::open() {
+            Ok(guard) => guard,
+            Err(e) => {
+                crate::trace!("clipboard", log::Level::Error, "Failed to read clipboard: {:?}", e);
+                return true;
+            }
+        };
+        let data_handle = GetClipboardData(include);
+        if data_handle.is_invalid() || GlobalSize(data_handle.0) < std::mem::size_of::<u32>() {
+            return true;
+        }
+        let data = GlobalLock(data_handle.0) as *const u32;
+        if data.is_null() {
+            return true;
+        }
+        let allowed = std::ptr::read_unaligned(data);
+        GlobalUnlock(data_handle.0);
+        return allowed == 0;
     }
 }
\ No newline at end of file
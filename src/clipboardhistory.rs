@@ -0,0 +1,97 @@
+// Recent clipboard text, newest first, persisted as a json array of strings in
+// %APPDATA%\switch\clipboard_history.json.
+
+pub const DEFAULT_MAX_ENTRIES: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipboardHistory {
+    entries: Vec<String>,
+    max_entries: usize,
+}
+
+impl ClipboardHistory {
+    pub fn new(max_entries: usize) -> Self {
+        return ClipboardHistory {
+            entries: vec![],
+            max_entries,
+        };
+    }
+
+    pub fn with_entries(entries: Vec<String>, max_entries: usize) -> Self {
+        let mut history = Self::new(max_entries);
+        // Oldest first so the first entry ends up newest.
+        for entry in entries.into_iter().rev() {
+            history.record(&entry);
+        }
+        return history;
+    }
+
+    pub fn entries(&self) -> &[String] {
+        return &self.entries;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    pub fn max_entries(&self) -> usize {
+        return self.max_entries;
+    }
+
+    // Puts text at the front, removing an earlier copy of it.
+    // Returns false if text is blank and wasn't recorded.
+    pub fn record(&mut self, text: &str) -> bool {
+        if text.trim().len() == 0 || self.max_entries == 0 {
+            return false;
+        }
+
+        self.entries.retain(|e| e != text);
+        self.entries.insert(0, text.to_owned());
+        self.entries.truncate(self.max_entries);
+        return true;
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<String> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        return Some(self.entries.remove(index));
+    }
+
+    pub fn parse(json: &str, max_entries: usize) -> anyhow::Result<Self> {
+        let entries: Vec<String> = serde_json::from_str(json)?;
+        return Ok(Self::with_entries(entries, max_entries));
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        return Ok(serde_json::to_string(&self.entries)?);
+    }
+
+    pub fn load_from<IntoPath: Into<std::path::PathBuf>>(path: IntoPath, max_entries: usize) -> anyhow::Result<Self> {
+        let path = path.into();
+        if !path.exists() {
+            return Ok(Self::new(max_entries));
+        }
+
+        return Self::parse(&std::fs::read_to_string(&path)?, max_entries);
+    }
+
+    pub fn save_to<IntoPath: Into<std::path::PathBuf>>(&self, path: IntoPath) -> anyhow::Result<()> {
+        std::fs::write(path.into(), self.to_json()?)?;
+        return Ok(());
+    }
+
+    pub fn path() -> anyhow::Result<String> {
+        return crate::path::get_app_data_path("clipboard_history.json");
+    }
+
+    // Read the history, record text and write it back.
+    pub fn record_to<IntoPath: Into<std::path::PathBuf>>(path: IntoPath, text: &str, max_entries: usize) -> anyhow::Result<()> {
+        let path = path.into();
+        let mut history = Self::load_from(&path, max_entries)?;
+        if history.record(text) {
+            history.save_to(&path)?;
+        }
+        return Ok(());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::listcontentprovider::{ListContentProvider, ListItem};
+use crate::keymap::Command;
+use crate::clipboard::{Clipboard, SystemClipboard};
+use crate::clipboardhistory::ClipboardHistory;
+
+use crate::log::*;
+
+pub struct ClipboardEntry {
+    pub text: String,
+    // Position in the history.
+    index: usize,
+}
+
+impl ClipboardEntry {
+    // Entries can be multiline, show them on one line.
+    pub fn display_text(text: &str) -> String {
+        return text.trim().replace("\r\n", "⏎").replace(&['\r', '\n'][..], "⏎").replace('\t', " ");
+    }
+}
+
+impl ListItem for ClipboardEntry {
+    fn as_any(&self) -> &dyn std::any::Any {
+        return self;
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        return self;
+    }
+
+    fn as_string(&self) -> String {
+        return Self::display_text(&self.text);
+    }
+
+    fn as_matchable_string(&self) -> String {
+        return self.text.clone();
+    }
+}
+
+// Searches recent clipboard text, enter copies the entry back to the clipboard.
+// Entries are recorded by quakerun when the clipboard changes and by switch when it starts.
+pub struct ClipboardHistoryProvider {
+    clipboard: Box<dyn Clipboard>,
+    history: ClipboardHistory,
+    // Where the history is saved, None to keep it in memory.
+    path: Option<String>,
+    query: String,
+    filtered: Vec<ClipboardEntry>,
+}
+
+impl ClipboardHistoryProvider {
+    pub fn new(config: &crate::config::Config) -> Box<Self> {
+        let max_entries = config.clipboard_history_size;
+        let path = ClipboardHistory::path().ok();
+        let history = match path.as_ref().map(|path| ClipboardHistory::load_from(path, max_entries)) {
+            Some(Ok(history)) => history,
+            Some(Err(e)) => {
+                crate::trace!("clipboard", log::Level::Error, "Failed to load clipboard history: {:?}", e);
+                ClipboardHistory::new(max_entries)
+            },
+            None => ClipboardHistory::new(max_entries),
+        };
+
+        let mut provider = Self::with_clipboard(Box::new(SystemClipboard), history, path);
+        // Same as quakerun, text a password manager marked stays out of the history.
+        if !crate::clipboard::excluded_from_history() {
+            provider.record_clipboard();
+        }
+        return provider;
+    }
+
+    pub fn with_clipboard(clipboard: Box<dyn Clipboard>, history: ClipboardHistory, path: Option<String>) -> Box<Self> {
+        let mut provider = Box::new(ClipboardHistoryProvider {
+            clipboard,
+            history,
+            path,
+            query: String::new(),
+            filtered: vec![],
+        });
+        provider.filter();
+        return provider;
+    }
+
+    pub fn clipboard(&mut self) -> &mut dyn Clipboard {
+        return self.clipboard.as_mut();
+    }
+
+    pub fn history(&self) -> &ClipboardHistory {
+        return &self.history;
+    }
+
+    // Add what is on the clipboard now, in case it was copied while quakerun wasn't running.
+    pub fn record_clipboard(&mut self) {
+        match self.clipboard.get_text() {
+            Ok(text) => self.update(|history| history.record(&text)),
+            Err(e) => {
+                crate::trace!("clipboard", log::Level::Error, "Failed to read clipboard: {:?}", e);
+            }
+        }
+    }
+
+    // quakerun records to the same file while switch is open, so it's read again before a change
+    // and written back with the change, instead of overwriting what was recorded meanwhile.
+    fn update<F: FnOnce(&mut ClipboardHistory) -> bool>(&mut self, change: F) {
+        if let Some(path) = &self.path {
+            match ClipboardHistory::load_from(path, self.history.max_entries()) {
+                Ok(history) => self.history = history,
+                Err(e) => {
+                    crate::trace!("clipboard", log::Level::Error, "Failed to load clipboard history: {:?}", e);
+                }
+            }
+        }
+        if change(&mut self.history) {
+            self.save();
+        }
+        self.filter();
+    }
+
+    fn save(&self) {
+        if let Some(path) = &self.path {
+            if let Err(e) = self.history.save_to(path) {
+                crate::trace!("clipboard", log::Level::Error, "Failed to save clipboard history: {:?}", e);
+            }
+        }
+    }
+
+    fn filter(&mut self) {
+        let query = self.query.to_lowercase();
+        self.filtered = self.history.entries().iter().enumerate()
+            .filter(|(_, text)| text.to_lowercase().contains(&query))
+            .map(|(index, text)| ClipboardEntry { text: text.clone(), index })
+            .collect();
+    }
+}
+
+impl ListContentProvider for ClipboardHistoryProvider {
+    fn name(&self) -> &str {
+        return "Clipboard";
+    }
+
+    fn total(&self) -> Option<usize> {
+        return Some(self.history.len());
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "copy"), (Command::Remove, "delete")];
+    }
+
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        return self.filtered.iter_mut().map(|e| e as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        return self.filtered.iter().map(|e| e.as_string()).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.filter();
+    }
+
+    // Copy the entry so it can be pasted, it moves to the top of the history.
+    fn start(&mut self, filtered_index: usize, _elevated: bool) {
+        if filtered_index >= self.filtered.len() {
+            return;
+        }
+
+        let text = self.filtered[filtered_index].text.clone();
+        crate::trace!("start", log::Level::Info, "Copy clipboard history entry {}", self.filtered[filtered_index].index);
+        if let Err(e) = self.clipboard.set_text(&text) {
+            crate::trace!("start", log::Level::Error, "Copy clipboard history entry failed: {:?}", e);
+            return;
+        }
+
+        self.update(|history| history.record(&text));
+    }
+
+    fn remove(&mut self, filtered_index: usize) {
+        if filtered_index >= self.filtered.len() {
+            return;
+        }
+
+        // By text, the entry may have moved since the list was filtered.
+        let text = self.filtered[filtered_index].text.clone();
+        self.update(|history| match history.entries().iter().position(|e| *e == text) {
+            Some(index) => history.remove(index).is_some(),
+            None => false,
+        });
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.filtered.get(filtered_index).map(|e| e.text.clone());
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "copy"), (Command::Remove, "delete")];
+    }
+}
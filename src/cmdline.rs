@@ -0,0 +1,342 @@
+// Command line tokenizer for turning a typed command into program + params.
+//
+// Windows rules follow CommandLineToArgvW, see
+// https://docs.microsoft.com/en-us/cpp/c-language/parsing-c-command-line-arguments
+// and wine's dlls/shell32/shell32_main.c which documents the undocumented parts
+// (consecutive quotes, the special casing of the first argument).
+//
+// Posix rules are the quoting subset of sh, no expansion is done here.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Syntax {
+    Windows,
+    Posix,
+}
+
+impl Default for Syntax {
+    fn default() -> Self {
+        if cfg!(windows) {
+            return Syntax::Windows;
+        }
+        return Syntax::Posix;
+    }
+}
+
+fn is_windows_whitespace(c: char) -> bool {
+    return c == ' ' || c == '\t';
+}
+
+fn is_posix_whitespace(c: char) -> bool {
+    return c == ' ' || c == '\t' || c == '\n';
+}
+
+// Returns the first argument and the byte offset where it ends.
+// The program name is never subject to backslash escaping, a quote simply
+// starts and ends it. Otherwise it ends at the first space or tab.
+fn windows_program(cmdline: &str) -> (String, usize) {
+    let mut program = String::new();
+    let mut chars = cmdline.char_indices();
+
+    if cmdline.starts_with('"') {
+        chars.next();
+        for (i, c) in chars {
+            if c == '"' {
+                return (program, i + 1);
+            }
+            program.push(c);
+        }
+        return (program, cmdline.len());
+    }
+
+    for (i, c) in chars {
+        if is_windows_whitespace(c) {
+            return (program, i);
+        }
+        program.push(c);
+    }
+    return (program, cmdline.len());
+}
+
+// Arguments after the program name.
+fn windows_arguments(params: &str) -> Vec<String> {
+    let mut args: Vec<String> = vec![];
+    let mut current = String::new();
+    // Whether current has been started, so that "" produces an empty argument.
+    let mut in_arg = false;
+    let mut backslashes = 0usize;
+    // Number of quotes seen, odd means we are inside a quoted region.
+    let mut quotes = 0usize;
+    let chars = params.chars().collect::<Vec<char>>();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if is_windows_whitespace(c) && quotes == 0 {
+            current.extend(std::iter::repeat('\\').take(backslashes));
+            backslashes = 0;
+            if in_arg {
+                args.push(std::mem::take(&mut current));
+                in_arg = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        in_arg = true;
+
+        if c == '\\' {
+            backslashes += 1;
+            i += 1;
+            continue;
+        }
+
+        if c == '"' {
+            // 2n backslashes followed by a quote produce n backslashes and the quote toggles
+            // quoting. 2n + 1 backslashes produce n backslashes and a literal quote.
+            current.extend(std::iter::repeat('\\').take(backslashes / 2));
+            if backslashes % 2 == 0 {
+                quotes += 1;
+            } else {
+                current.push('"');
+            }
+            backslashes = 0;
+            i += 1;
+
+            // Consecutive quotes, every third one is a literal quote.
+            while i < chars.len() && chars[i] == '"' {
+                quotes += 1;
+                if quotes == 3 {
+                    current.push('"');
+                    quotes = 0;
+                }
+                i += 1;
+            }
+            if quotes == 2 {
+                quotes = 0;
+            }
+            continue;
+        }
+
+        current.extend(std::iter::repeat('\\').take(backslashes));
+        backslashes = 0;
+        current.push(c);
+        i += 1;
+    }
+
+    current.extend(std::iter::repeat('\\').take(backslashes));
+    if in_arg {
+        args.push(current);
+    }
+
+    return args;
+}
+
+// Split like CommandLineToArgvW, except that an empty command line gives no arguments
+// instead of the path of the current executable.
+pub fn split_windows(cmdline: &str) -> Vec<String> {
+    let cmdline = cmdline.trim_start_matches(is_windows_whitespace);
+    if cmdline.len() == 0 {
+        return vec![];
+    }
+
+    let (program, end) = windows_program(cmdline);
+    let mut args = vec![program];
+    args.extend(windows_arguments(&cmdline[end..]));
+    return args;
+}
+
+// Split like sh without doing any expansion. Unterminated quotes are closed
+// at the end of the string rather than being an error, since we tokenize while
+// the user is still typing.
+pub fn split_posix(cmdline: &str) -> Vec<String> {
+    let mut args: Vec<String> = vec![];
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = cmdline.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if is_posix_whitespace(c) => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            },
+            '\'' => {
+                in_arg = true;
+                while let Some(c) = chars.next() {
+                    if c == '\'' {
+                        break;
+                    }
+                    current.push(c);
+                }
+            },
+            '"' => {
+                in_arg = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        // Inside double quotes backslash only escapes these.
+                        '\\' => match chars.peek() {
+                            Some('$') | Some('`') | Some('"') | Some('\\') => {
+                                current.push(chars.next().unwrap());
+                            },
+                            Some('\n') => {
+                                chars.next();
+                            },
+                            _ => current.push('\\'),
+                        },
+                        _ => current.push(c),
+                    }
+                }
+            },
+            '\\' => {
+                match chars.next() {
+                    // Line continuation.
+                    Some('\n') => {},
+                    Some(c) => {
+                        in_arg = true;
+                        current.push(c);
+                    },
+                    None => {
+                        in_arg = true;
+                        current.push('\\');
+                    },
+                }
+            },
+            _ => {
+                in_arg = true;
+                current.push(c);
+            },
+        }
+    }
+
+    if in_arg {
+        args.push(current);
+    }
+
+    return args;
+}
+
+pub fn split_with(cmdline: &str, syntax: Syntax) -> Vec<String> {
+    return match syntax {
+        Syntax::Windows => split_windows(cmdline),
+        Syntax::Posix => split_posix(cmdline),
+    };
+}
+
+// Split with the rules of the platform we're running on.
+pub fn split(cmdline: &str) -> Vec<String> {
+    return split_with(cmdline, Syntax::default());
+}
+
+// Split a command into the program, unquoted, and the rest of the command line untouched
+// so the program can parse its own parameters, which is what ShellExecute wants.
+// Returns None if there is no program.
+pub fn split_program_with(cmdline: &str, syntax: Syntax) -> Option<(String, String)> {
+    let cmdline = cmdline.trim();
+    if cmdline.len() == 0 {
+        return None;
+    }
+
+    return match syntax {
+        Syntax::Windows => {
+            let (program, end) = windows_program(cmdline);
+            Some((program, cmdline[end..].trim_start_matches(is_windows_whitespace).to_owned()))
+        },
+        Syntax::Posix => {
+            let end = posix_first_word_end(cmdline);
+            let program = split_posix(&cmdline[..end]).into_iter().next().unwrap_or_default();
+            Some((program, cmdline[end..].trim_start_matches(is_posix_whitespace).to_owned()))
+        },
+    };
+}
+
+pub fn split_program(cmdline: &str) -> Option<(String, String)> {
+    return split_program_with(cmdline, Syntax::default());
+}
+
+// Byte offset of the end of the first word, respecting quotes and escapes.
+fn posix_first_word_end(cmdline: &str) -> usize {
+    let mut chars = cmdline.char_indices();
+    let mut quote: Option<char> = None;
+
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some('\'') => {
+                if c == '\'' {
+                    quote = None;
+                }
+            },
+            Some(_) => {
+                if c == '\\' {
+                    chars.next();
+                } else if c == '"' {
+                    quote = None;
+                }
+            },
+            None => {
+                if is_posix_whitespace(c) {
+                    return i;
+                } else if c == '\\' {
+                    chars.next();
+                } else if c == '\'' || c == '"' {
+                    quote = Some(c);
+                }
+            },
+        }
+    }
+
+    return cmdline.len();
+}
+
+// Quote arg so that split_windows gives it back as a single argument.
+// Only for arguments after the program name, which doesn't support escaping.
+pub fn quote_windows(arg: &str) -> String {
+    if arg.len() > 0 && !arg.contains(|c: char| is_windows_whitespace(c) || c == '"') {
+        return arg.to_owned();
+    }
+
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0usize;
+    for c in arg.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+
+        if c == '"' {
+            // Escape the backslashes and the quote.
+            quoted.extend(std::iter::repeat('\\').take(backslashes * 2 + 1));
+        } else {
+            quoted.extend(std::iter::repeat('\\').take(backslashes));
+        }
+        quoted.push(c);
+        backslashes = 0;
+    }
+    // Backslashes before the closing quote need escaping too.
+    quoted.extend(std::iter::repeat('\\').take(backslashes * 2));
+    quoted.push('"');
+    return quoted;
+}
+
+// Quote arg so that split_posix gives it back as a single argument.
+pub fn quote_posix(arg: &str) -> String {
+    if arg.len() > 0 && arg.chars().all(|c| c.is_alphanumeric() || "-_./:=@%+,".contains(c)) {
+        return arg.to_owned();
+    }
+
+    return format!("'{}'", arg.replace('\'', r#"'\''"#));
+}
+
+pub fn quote_with(arg: &str, syntax: Syntax) -> String {
+    return match syntax {
+        Syntax::Windows => quote_windows(arg),
+        Syntax::Posix => quote_posix(arg),
+    };
+}
+
+pub fn quote(arg: &str) -> String {
+    return quote_with(arg, Syntax::default());
+}
@@ -0,0 +1,111 @@
+use serde::{Serialize, Deserialize};
+
+use crate::log::*;
+
+// User settings read from %APPDATA%\switch\config.json.
+// Every field has a default so the file only needs what the user wants to change.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    // Launch templates shown in start apps when their keyword is typed.
+    pub templates: Vec<crate::template::LaunchTemplate>,
+    // Url templates for the web search provider, {query} is the url encoded search.
+    pub search_engines: Vec<crate::template::LaunchTemplate>,
+    // Record copied text so it can be searched in the clipboard history provider.
+    // Off by default since everything copied, passwords included, is saved to disk.
+    pub clipboard_history: bool,
+    pub clipboard_history_size: usize,
+    // Directories the indexer walks for the file search provider.
+    pub file_search_roots: Vec<String>,
+    // Ignored in addition to what .gitignore and .ignore files say, in .gitignore syntax.
+    pub file_search_ignore: Vec<String>,
+    pub file_search_max_depth: i32,
+    // Directories of snippet files and TOML snippet files, see snippets.rs.
+    pub snippets: Vec<String>,
+    // Runs console programs like ssh, the program and its arguments are appended.
+    // Empty runs them in a console window of their own.
+    pub terminal: String,
+    // Opens git repositories and other directories, the path is appended.
+    pub editor: String,
+    // Directories searched for git repositories.
+    pub git_roots: Vec<String>,
+    pub git_max_depth: i32,
+    // Browser bookmark files, or profile directories and directories of profiles to find
+    // Bookmarks and places.sqlite in.
+    pub bookmarks: Vec<String>,
+    // Key preset, default, emacs or vim, see keymap.rs.
+    pub keymap: String,
+    // Key chord to command name over the preset, like "ctrl+n": "list-next" or "ctrl+k": "none".
+    pub keys: std::collections::BTreeMap<String, String>,
+    // Colors, prompt and layout over a built in theme, see theme.rs.
+    pub theme: crate::theme::ThemeConfig,
+    // Queries kept for each provider, 0 to keep none.
+    pub query_history_size: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        return Config {
+            templates: vec![],
+            search_engines: crate::websearchprovider::default_search_engines(),
+            clipboard_history: false,
+            clipboard_history_size: crate::clipboardhistory::DEFAULT_MAX_ENTRIES,
+            file_search_roots: vec![
+                "%USERPROFILE%\\Documents".into(),
+                "%USERPROFILE%\\Desktop".into(),
+                "%USERPROFILE%\\source".into(),
+            ],
+            file_search_ignore: vec![".git/".into(), "node_modules/".into()],
+            file_search_max_depth: 12,
+            snippets: vec![
+                "%APPDATA%\\switch\\snippets".into(),
+                "%APPDATA%\\switch\\snippets.toml".into(),
+            ],
+            terminal: "wt.exe".into(),
+            editor: "code".into(),
+            git_roots: vec![
+                "%USERPROFILE%\\source".into(),
+                "%USERPROFILE%\\code".into(),
+            ],
+            git_max_depth: 4,
+            bookmarks: vec![
+                "%LOCALAPPDATA%\\Google\\Chrome\\User Data".into(),
+                "%LOCALAPPDATA%\\Microsoft\\Edge\\User Data".into(),
+                "%LOCALAPPDATA%\\BraveSoftware\\Brave-Browser\\User Data".into(),
+                "%APPDATA%\\Mozilla\\Firefox\\Profiles".into(),
+            ],
+            keymap: "default".into(),
+            keys: std::collections::BTreeMap::new(),
+            theme: crate::theme::ThemeConfig::default(),
+            query_history_size: crate::queryhistory::DEFAULT_MAX_ENTRIES,
+        };
+    }
+}
+
+impl Config {
+    pub fn parse(json: &str) -> anyhow::Result<Config> {
+        return Ok(serde_json::from_str(json)?);
+    }
+
+    pub fn load_from<IntoPath: Into<std::path::PathBuf>>(path: IntoPath) -> anyhow::Result<Config> {
+        let path = path.into();
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        return Self::parse(&std::fs::read_to_string(&path)?);
+    }
+
+    // A broken config file shouldn't stop switch from starting, log it and use defaults.
+    pub fn load() -> Config {
+        let loaded = crate::path::get_app_data_path("config.json").and_then(|path| Self::load_from(path));
+
+        return match loaded {
+            Ok(config) => config,
+            Err(e) => {
+                crate::trace!("config", log::Level::Error, "Failed to load config: {:?}", e);
+                Config::default()
+            }
+        };
+    }
+}
@@ -0,0 +1,224 @@
+// Shell like navigation of directories for the directory mode of start apps.
+// A query like C:\code\sw is split into the directory C:\code\ and the filter sw,
+// the directory is read once and filtered as the user types.
+
+pub const RECENT_DIRECTORIES: &str = "recent_directories.json";
+pub const MAX_RECENT_DIRECTORIES: usize = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectoryEntry {
+    pub name: String,
+    pub path: std::path::PathBuf,
+    pub is_dir: bool,
+    // Hidden or system attribute on windows, dot files elsewhere.
+    pub hidden: bool,
+}
+
+impl DirectoryEntry {
+    // Directories end with a separator so tab completing them descends.
+    pub fn display_path(&self) -> String {
+        let path = self.path.to_string_lossy().into_owned();
+        if self.is_dir && !path.ends_with(is_separator) {
+            return path + std::path::MAIN_SEPARATOR.to_string().as_str();
+        }
+        return path;
+    }
+}
+
+pub fn is_separator(c: char) -> bool {
+    return c == '/' || c == '\\';
+}
+
+// Splits the query into the directory to list and the filter for its entries.
+// A query ending with a separator lists that directory unfiltered.
+pub fn split_query(query: &str) -> (std::path::PathBuf, String) {
+    return match query.rfind(is_separator) {
+        Some(i) => (std::path::PathBuf::from(&query[..i + 1]), query[i + 1..].to_owned()),
+        None => (std::path::PathBuf::new(), query.to_owned()),
+    };
+}
+
+// Starts with a drive like C:\ or a UNC share like \\server\. Spaces in such a query are
+// part of the path, as in C:\Program Files, not arguments to a command.
+pub fn is_rooted(query: &str) -> bool {
+    let chars = query.chars().take(3).collect::<Vec<char>>();
+    if chars.len() == 3 && chars[0].is_ascii_alphabetic() && chars[1] == ':' && is_separator(chars[2]) {
+        return true;
+    }
+    return query.starts_with("\\\\") && query.len() > 2;
+}
+
+// Backspace at the end of a query that ends with a separator removes the last
+// directory instead of just the separator. None if query doesn't end with a separator
+// or is a root like C:\.
+pub fn ascend(query: &str) -> Option<String> {
+    if !query.ends_with(is_separator) {
+        return None;
+    }
+
+    let trimmed = query.trim_end_matches(is_separator);
+    let i = trimmed.rfind(is_separator)?;
+    return Some(trimmed[..i + 1].to_owned());
+}
+
+#[cfg(windows)]
+fn is_hidden(_name: &str, metadata: &std::fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+    return metadata.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0;
+}
+
+#[cfg(not(windows))]
+fn is_hidden(name: &str, _metadata: &std::fs::Metadata) -> bool {
+    return name.starts_with('.');
+}
+
+// Every entry of dir, including hidden ones.
+pub fn read_directory<IntoPath: Into<std::path::PathBuf>>(dir: IntoPath) -> std::io::Result<Vec<DirectoryEntry>> {
+    let dir = dir.into();
+    let mut entries = vec![];
+
+    for entry in std::fs::read_dir(&dir)?.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        // Entry metadata comes with the directory listing on windows, it doesn't touch the file.
+        let (is_dir, hidden) = match entry.metadata() {
+            Ok(metadata) => (metadata.is_dir(), is_hidden(&name, &metadata)),
+            Err(_) => (false, false),
+        };
+
+        entries.push(DirectoryEntry {
+            path: entry.path(),
+            name,
+            is_dir,
+            hidden,
+        });
+    }
+
+    return Ok(entries);
+}
+
+// Entries matching filter, directories first, then by how well they match, then by name.
+pub fn filter_entries<'a>(entries: &'a [DirectoryEntry], filter: &str, show_hidden: bool) -> Vec<&'a DirectoryEntry> {
+    let mut scored = entries.iter()
+        .filter(|e| show_hidden || !e.hidden)
+        .filter_map(|e| crate::fuzzy::score(filter, &e.name).map(|score| (score, e)))
+        .collect::<Vec<(i32, &DirectoryEntry)>>();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b.is_dir.cmp(&a.is_dir)
+            .then(b_score.cmp(a_score))
+            .then(a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+
+    return scored.into_iter().map(|(_, e)| e).collect();
+}
+
+// Longest case insensitive common prefix of names, with the case of the first name.
+pub fn common_prefix(names: &[&str]) -> String {
+    let first = match names.first() {
+        Some(first) => *first,
+        None => return String::new(),
+    };
+
+    let mut end = first.len();
+    for name in &names[1..] {
+        let common = first.char_indices()
+            .zip(name.chars())
+            .take_while(|((_, a), b)| a.to_lowercase().eq(b.to_lowercase()))
+            .last()
+            .map(|((i, a), _)| i + a.len_utf8())
+            .unwrap_or(0);
+        end = end.min(common);
+    }
+
+    return first[..end].to_owned();
+}
+
+// Tab completion of the last path component of query like a shell: extend it to the
+// common prefix of the entries starting with it, and when only one entry matches
+// complete it fully, adding a separator for directories.
+// None if there is nothing to add.
+pub fn complete(query: &str, entries: &[DirectoryEntry], show_hidden: bool) -> Option<String> {
+    let (_, filter) = split_query(query);
+    let filter_lower = filter.to_lowercase();
+    let candidates = entries.iter()
+        .filter(|e| show_hidden || !e.hidden)
+        .filter(|e| e.name.to_lowercase().starts_with(&filter_lower))
+        .collect::<Vec<&DirectoryEntry>>();
+
+    let base = &query[..query.len() - filter.len()];
+    let completed = if candidates.len() == 1 {
+        let mut name = candidates[0].name.clone();
+        if candidates[0].is_dir {
+            name.push(std::path::MAIN_SEPARATOR);
+        }
+        name
+    } else {
+        common_prefix(&candidates.iter().map(|e| e.name.as_str()).collect::<Vec<&str>>())
+    };
+
+    if completed.chars().count() <= filter.chars().count() {
+        return None;
+    }
+    return Some(base.to_owned() + &completed);
+}
+
+// Most recent first, a directory visited again moves to the front.
+pub fn push_recent(recent: &mut Vec<String>, dir: &str, max: usize) {
+    let mut dir = dir.to_owned();
+    if !dir.ends_with(is_separator) {
+        dir.push(std::path::MAIN_SEPARATOR);
+    }
+    recent.retain(|d| !d.eq_ignore_ascii_case(&dir));
+    recent.insert(0, dir);
+    recent.truncate(max);
+}
+
+pub fn load_recent() -> Vec<String> {
+    return crate::index::load(RECENT_DIRECTORIES).unwrap_or_default();
+}
+
+pub fn record_recent(dir: &str) -> anyhow::Result<()> {
+    let mut recent = load_recent();
+    push_recent(&mut recent, dir, MAX_RECENT_DIRECTORIES);
+    return crate::index::save(RECENT_DIRECTORIES, &recent);
+}
+
+// Recent directories for query, best match first.
+pub fn suggest_recent<'a>(recent: &'a [String], query: &str, max: usize) -> Vec<&'a String> {
+    if query.trim().len() == 0 {
+        return vec![];
+    }
+
+    let mut scored = recent.iter()
+        .enumerate()
+        .filter_map(|(i, d)| crate::fuzzy::score_path(query, d).map(|score| (score, i, d)))
+        .collect::<Vec<(i32, usize, &String)>>();
+    // More recent wins ties.
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    return scored.into_iter().take(max).map(|(_, _, d)| d).collect();
+}
+
+// Cached listing of one directory so typing the filter doesn't read it again.
+#[derive(Debug, Default)]
+pub struct DirectoryCache {
+    dir: Option<std::path::PathBuf>,
+    entries: Vec<DirectoryEntry>,
+}
+
+impl DirectoryCache {
+    // Entries of dir, read if dir isn't the cached one.
+    pub fn entries(&mut self, dir: &std::path::Path) -> &[DirectoryEntry] {
+        if self.dir.as_deref() != Some(dir) {
+            self.entries = read_directory(dir).unwrap_or_default();
+            self.dir = Some(dir.to_owned());
+        }
+        return &self.entries;
+    }
+
+    pub fn clear(&mut self) {
+        self.dir = None;
+        self.entries.clear();
+    }
+}
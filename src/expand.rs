@@ -0,0 +1,149 @@
+// Environment variable expansion for typed commands and index roots.
+//
+// Supports %VAR% like ExpandEnvironmentStrings, $VAR and ${VAR} like sh, and ~ or ~user
+// at the start of a word. Variables that can't be resolved are left as typed, so
+// things like \\server\c$ or a literal 100% survive expansion.
+
+pub trait VariableSource {
+    fn var(&self, name: &str) -> Option<String>;
+    // Home directory of user, or of the current user if user is None.
+    fn home_dir(&self, user: Option<&str>) -> Option<String>;
+}
+
+// Reads variables from the process environment.
+pub struct Environment;
+
+impl VariableSource for Environment {
+    fn var(&self, name: &str) -> Option<String> {
+        // Names are case insensitive on windows, std::env handles that for us.
+        // Use var_os so a variable that isn't valid unicode still expands to something.
+        return std::env::var_os(name).map(|v| v.to_string_lossy().into_owned());
+    }
+
+    fn home_dir(&self, user: Option<&str>) -> Option<String> {
+        let home = if cfg!(windows) {
+            self.var("USERPROFILE")
+        } else {
+            self.var("HOME")
+        }?;
+
+        let user = match user {
+            Some(user) => user,
+            None => return Some(home),
+        };
+
+        if !cfg!(windows) {
+            if let Some(home) = passwd_home_dir(user) {
+                return Some(home);
+            }
+        }
+
+        // Other users' profiles are usually next to ours.
+        let sibling = std::path::Path::new(&home).parent()?.join(user);
+        if !sibling.exists() {
+            return None;
+        }
+        return Some(sibling.to_string_lossy().into_owned());
+    }
+}
+
+fn passwd_home_dir(user: &str) -> Option<String> {
+    let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+    // name:password:uid:gid:gecos:home:shell
+    return passwd.lines()
+        .map(|l| l.split(':').collect::<Vec<&str>>())
+        .find(|fields| fields.len() >= 6 && fields[0] == user)
+        .map(|fields| fields[5].to_owned());
+}
+
+fn is_name_start(c: char) -> bool {
+    return c == '_' || c.is_ascii_alphabetic();
+}
+
+fn is_name_char(c: char) -> bool {
+    return c == '_' || c.is_ascii_alphanumeric();
+}
+
+fn is_path_separator(c: char) -> bool {
+    return c == '/' || c == '\\';
+}
+
+// Expand %VAR%, $VAR, ${VAR}, ~ and ~user using source.
+pub fn expand_with(input: &str, source: &dyn VariableSource) -> String {
+    let chars = input.chars().collect::<Vec<char>>();
+    let mut result = String::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let word_start = i == 0 || chars[i - 1].is_whitespace();
+
+        if c == '%' {
+            // %VAR%, anything between the percents except another percent is a name.
+            if let Some(len) = chars[i + 1..].iter().position(|&c| c == '%') {
+                let name = chars[i + 1..i + 1 + len].iter().collect::<String>();
+                if let Some(value) = (len > 0).then(|| source.var(&name)).flatten() {
+                    result.push_str(&value);
+                    i += len + 2;
+                    continue;
+                }
+            }
+        } else if c == '$' && chars.get(i + 1) == Some(&'{') {
+            if let Some(len) = chars[i + 2..].iter().position(|&c| c == '}') {
+                let name = chars[i + 2..i + 2 + len].iter().collect::<String>();
+                if let Some(value) = (len > 0).then(|| source.var(&name)).flatten() {
+                    result.push_str(&value);
+                    i += len + 3;
+                    continue;
+                }
+            }
+        } else if c == '$' && chars.get(i + 1).map(|&c| is_name_start(c)).unwrap_or(false) {
+            let len = chars[i + 1..].iter().take_while(|&&c| is_name_char(c)).count();
+            let name = chars[i + 1..i + 1 + len].iter().collect::<String>();
+            if let Some(value) = source.var(&name) {
+                result.push_str(&value);
+                i += len + 1;
+                continue;
+            }
+        } else if c == '~' && word_start {
+            let len = chars[i + 1..].iter()
+                .take_while(|&&c| !is_path_separator(c) && !c.is_whitespace())
+                .count();
+            let user = chars[i + 1..i + 1 + len].iter().collect::<String>();
+            let home = if user.len() == 0 {
+                source.home_dir(None)
+            } else {
+                source.home_dir(Some(&user))
+            };
+            if let Some(home) = home {
+                result.push_str(&home);
+                i += len + 1;
+                continue;
+            }
+        }
+
+        result.push(c);
+        i += 1;
+    }
+
+    return result;
+}
+
+pub fn expand(input: &str) -> String {
+    return expand_with(input, &Environment);
+}
+
+// Only expand a leading ~ or ~user, used for the query where we don't want
+// variables expanded while the user is typing them.
+pub fn expand_tilde_with(input: &str, source: &dyn VariableSource) -> String {
+    if !input.starts_with('~') {
+        return input.to_owned();
+    }
+
+    let end = input.find(|c: char| is_path_separator(c) || c.is_whitespace()).unwrap_or(input.len());
+    return expand_with(&input[..end], source) + &input[end..];
+}
+
+pub fn expand_tilde(input: &str) -> String {
+    return expand_tilde_with(input, &Environment);
+}
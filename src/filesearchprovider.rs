@@ -0,0 +1,189 @@
+use serde::{Serialize, Deserialize};
+
+use crate::listcontentprovider::{Action, ListContentProvider, ListItem};
+use crate::keymap::Command;
+
+use crate::log::*;
+
+// Written by the indexer, see index_files in bin/indexer.rs.
+pub const FILE_INDEX: &str = "files.json";
+
+// Only the best matches are listed, there can be a lot of files.
+const MAX_RESULTS: usize = 200;
+// Scoring is slow next to checking for a match, so a query that most of the index matches,
+// like a single letter, only ranks this many of the matches. Typing more narrows them down.
+pub const MAX_SCORED: usize = 10000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileEntry {
+    pub path: String,
+    #[serde(default)]
+    pub is_dir: bool,
+}
+
+impl ListItem for FileEntry {
+    fn as_any(&self) -> &dyn std::any::Any {
+        return self;
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        return self;
+    }
+
+    fn as_string(&self) -> String {
+        if self.is_dir {
+            return self.path.clone() + "\\";
+        }
+        return self.path.clone();
+    }
+
+    fn as_matchable_string(&self) -> String {
+        return self.path.clone();
+    }
+}
+
+// Fuzzy search over the names of files under the file_search_roots in config.json.
+pub struct FileSearchProvider {
+    files: Vec<FileEntry>,
+    query: String,
+    // Indices of the files matching query, all of them, not just the ones scored.
+    matches: Vec<usize>,
+    results: Vec<FileEntry>,
+}
+
+// Indices of the files among candidates that match query.
+pub fn filter(files: &[FileEntry], candidates: impl Iterator<Item = usize>, query: &str) -> Vec<usize> {
+    if query.trim().len() == 0 {
+        return vec![];
+    }
+    return candidates.filter(|&i| crate::fuzzy::matches_path(query, &files[i].path)).collect();
+}
+
+// Best matches first, ties go to the shorter path. Only the first MAX_SCORED matches in index
+// order are scored, so a better match after them isn't listed.
+pub fn rank(files: &[FileEntry], matches: &[usize], query: &str, max_results: usize) -> Vec<FileEntry> {
+    let mut scored = matches.iter()
+        .take(MAX_SCORED)
+        .map(|&i| &files[i])
+        .filter_map(|f| crate::fuzzy::score_path(query, &f.path).map(|score| (score, f)))
+        .collect::<Vec<(i32, &FileEntry)>>();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score.cmp(a_score)
+            .then(a.path.len().cmp(&b.path.len()))
+            .then(a.path.cmp(&b.path))
+    });
+
+    return scored.into_iter().take(max_results).map(|(_, f)| f.clone()).collect();
+}
+
+pub fn search(files: &[FileEntry], query: &str, max_results: usize) -> Vec<FileEntry> {
+    return rank(files, &filter(files, 0..files.len(), query), query, max_results);
+}
+
+impl FileSearchProvider {
+    pub fn new() -> Box<Self> {
+        let files = match crate::index::load::<Vec<FileEntry>>(FILE_INDEX) {
+            Ok(files) => files,
+            Err(e) => {
+                crate::trace!("query", log::Level::Error, "Failed to load {}: {:?}", FILE_INDEX, e);
+                vec![]
+            }
+        };
+        return Self::with_files(files);
+    }
+
+    pub fn with_files(files: Vec<FileEntry>) -> Box<Self> {
+        return Box::new(FileSearchProvider {
+            files,
+            query: String::new(),
+            matches: vec![],
+            results: vec![],
+        });
+    }
+}
+
+impl ListContentProvider for FileSearchProvider {
+    fn name(&self) -> &str {
+        return "Files";
+    }
+
+    fn qualifiers(&self) -> Vec<(String, String)> {
+        return vec![
+            ("src/main".to_string(), "match parts of the path".to_string()),
+            ("s".to_string(), format!("only the first {} matches in index order are ranked, type more to narrow them", MAX_SCORED)),
+        ];
+    }
+
+    fn total(&self) -> Option<usize> {
+        return Some(self.files.len());
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "open"), (Command::OpenFolder, "folder"), (Command::OpenTerminal, "terminal"), (Command::Copy, "copy path")];
+    }
+
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        return self.results.iter_mut().map(|f| f as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        return self.results.iter().map(|f| f.as_string()).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        // Typing on only takes away matches, so just the previous ones need checking.
+        self.matches = if self.query.trim().len() > 0 && query.starts_with(&self.query) {
+            filter(&self.files, self.matches.iter().copied(), &query)
+        } else {
+            filter(&self.files, 0..self.files.len(), &query)
+        };
+        self.query = query;
+        self.results = rank(&self.files, &self.matches, &self.query, MAX_RESULTS);
+    }
+
+    fn start(&mut self, filtered_index: usize, elevated: bool) {
+        if filtered_index >= self.results.len() {
+            return;
+        }
+
+        let file = &self.results[filtered_index];
+        crate::trace!("start", log::Level::Info, "Open file elevated {:?}: {:?}", elevated, file);
+        if let Err(e) = crate::open::open(&file.path, elevated) {
+            crate::trace!("start", log::Level::Error, "Open file error: {:?}", e);
+        }
+    }
+
+    // Files come from the index, nothing to remove.
+    fn remove(&mut self, _filtered_index: usize) {
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.results.get(filtered_index).map(|f| f.path.clone());
+    }
+
+    fn run_action(&mut self, filtered_index: usize, action: Action, elevated: bool) -> bool {
+        let file = match self.results.get(filtered_index) {
+            Some(file) => file,
+            None => return false,
+        };
+
+        let result = match action {
+            Action::OpenFolder => crate::open::open_folder(&file.path, elevated),
+            Action::OpenTerminal => crate::open::open_terminal(&file.path, elevated),
+            _ => return false,
+        };
+        if let Err(e) = result {
+            crate::trace!("start", log::Level::Error, "{:?} error: {:?}", action, e);
+        }
+        return true;
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        return Some("/");
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "open"), (Command::AcceptElevated, "open as admin"), (Command::OpenFolder, "open folder"), (Command::OpenTerminal, "open terminal"), (Command::Copy, "copy path")];
+    }
+}
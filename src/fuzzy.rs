@@ -0,0 +1,176 @@
+// Fuzzy matching for file paths. Every character of the pattern has to appear in
+// order, and matches score higher at the start of a word or path segment and when
+// they are consecutive, similar to fzf.
+
+const SCORE_MATCH: i32 = 16;
+const BONUS_FIRST_CHAR: i32 = 10;
+// After / \ or the start of the text.
+const BONUS_SEGMENT_START: i32 = 10;
+// After _ - . or space, or a lower to upper case change.
+const BONUS_WORD_START: i32 = 8;
+const BONUS_CONSECUTIVE: i32 = 10;
+const PENALTY_GAP: i32 = 1;
+// Matching the file name is better than matching some directory above it.
+const BONUS_FILE_NAME: i32 = 24;
+
+fn is_separator(c: char) -> bool {
+    return c == '/' || c == '\\';
+}
+
+fn bonus(previous: Option<char>, c: char) -> i32 {
+    return match previous {
+        None => BONUS_SEGMENT_START,
+        Some(p) if is_separator(p) => BONUS_SEGMENT_START,
+        Some(p) if p == '_' || p == '-' || p == '.' || p == ' ' => BONUS_WORD_START,
+        Some(p) if p.is_lowercase() && c.is_uppercase() => BONUS_WORD_START,
+        Some(p) if !p.is_alphanumeric() && c.is_alphanumeric() => BONUS_WORD_START,
+        _ => 0,
+    };
+}
+
+fn chars_equal(pattern: char, text: char) -> bool {
+    if is_separator(pattern) {
+        return is_separator(text);
+    }
+    return pattern == text || text.to_lowercase().eq(pattern.to_lowercase());
+}
+
+// Best score of pattern as a subsequence of text, None if it isn't one.
+// Case insensitive, / and \ match each other.
+pub fn score(pattern: &str, text: &str) -> Option<i32> {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let text = text.chars().collect::<Vec<char>>();
+
+    if pattern.len() == 0 {
+        return Some(0);
+    }
+    if pattern.len() > text.len() {
+        return None;
+    }
+
+    // row[j] is the best score with the current pattern char matched at text[j].
+    let mut previous_row: Vec<Option<i32>> = vec![None; text.len()];
+    let mut row: Vec<Option<i32>> = vec![None; text.len()];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        // Best of previous_row[k] + k for k < j - 1, so the gap penalty for matching at j
+        // is (j - k - 1) without looking at every k.
+        let mut best_earlier: Option<i32> = None;
+
+        for j in 0..text.len() {
+            row[j] = None;
+
+            if j >= 2 && i > 0 {
+                if let Some(s) = previous_row[j - 2] {
+                    let candidate = s + (j as i32 - 2) * PENALTY_GAP;
+                    best_earlier = Some(best_earlier.map_or(candidate, |b| b.max(candidate)));
+                }
+            }
+
+            if !chars_equal(p, text[j]) {
+                continue;
+            }
+
+            let previous_char = if j > 0 { Some(text[j - 1]) } else { None };
+            let here = SCORE_MATCH + bonus(previous_char, text[j]);
+
+            if i == 0 {
+                let first = if j == 0 { BONUS_FIRST_CHAR } else { 0 };
+                // Small penalty for where the match starts, capped so long prefixes don't dominate.
+                row[j] = Some(here + first - (j as i32).min(5) * PENALTY_GAP);
+                continue;
+            }
+
+            let consecutive = if j >= 1 {
+                previous_row[j - 1].map(|s| s + BONUS_CONSECUTIVE)
+            } else {
+                None
+            };
+            let gapped = best_earlier.map(|b| b - (j as i32 - 1) * PENALTY_GAP);
+
+            row[j] = match (consecutive, gapped) {
+                (Some(a), Some(b)) => Some(a.max(b) + here),
+                (Some(a), None) => Some(a + here),
+                (None, Some(b)) => Some(b + here),
+                (None, None) => None,
+            };
+        }
+
+        std::mem::swap(&mut previous_row, &mut row);
+    }
+
+    return previous_row.into_iter().flatten().max();
+}
+
+// Score a path against a query of whitespace separated terms, all of which have to match.
+// A term with a separator in it, like src/main, is matched against the whole path so its
+// parts line up with path segments. Other terms prefer matching within the file name.
+pub fn score_path(query: &str, path: &str) -> Option<i32> {
+    let path = path.trim_end_matches(is_separator);
+    let name_start = path.rfind(is_separator).map(|i| i + 1).unwrap_or(0);
+    let name = &path[name_start..];
+
+    let mut total = 0;
+    for term in query.split_whitespace() {
+        let term_score = if term.contains(is_separator) {
+            score(term, path)?
+        } else if let Some(s) = score(term, name) {
+            s + BONUS_FILE_NAME
+        } else {
+            score(term, path)?
+        };
+        total += term_score;
+    }
+
+    // Prefer shallower paths when everything else is equal.
+    let depth = path.chars().filter(|&c| is_separator(c)).count() as i32;
+    return Some(total - depth);
+}
+
+// Whether score_path would match, every term's characters appear in path in order.
+// Much cheaper than scoring, for narrowing down what gets scored.
+pub fn matches_path(query: &str, path: &str) -> bool {
+    let path = path.trim_end_matches(is_separator);
+    return query.split_whitespace().all(|term| {
+        let mut chars = path.chars();
+        return term.chars().all(|p| chars.any(|c| chars_equal(p, c)));
+    });
+}
+
+// Char indices of text to highlight for a query of whitespace separated terms: where a
+// term appears as is, else the first place its characters appear in order. Only for
+// showing matches, providers rank with score.
+pub fn match_positions(query: &str, text: &str) -> Vec<usize> {
+    let text = text.chars().collect::<Vec<char>>();
+    let mut positions = vec![];
+
+    for term in query.split_whitespace() {
+        let term = term.chars().collect::<Vec<char>>();
+        let substring = (0..(text.len() + 1).saturating_sub(term.len()))
+            .find(|&start| term.iter().enumerate().all(|(i, &c)| chars_equal(c, text[start + i])));
+        if let Some(start) = substring {
+            positions.extend(start..start + term.len());
+            continue;
+        }
+
+        let mut matched = vec![];
+        let mut j = 0;
+        for &c in &term {
+            while j < text.len() && !chars_equal(c, text[j]) {
+                j += 1;
+            }
+            if j == text.len() {
+                break;
+            }
+            matched.push(j);
+            j += 1;
+        }
+        if matched.len() == term.len() {
+            positions.extend(matched);
+        }
+    }
+
+    positions.sort();
+    positions.dedup();
+    return positions;
+}
@@ -0,0 +1,209 @@
+use crate::listcontentprovider::{Action, ListContentProvider, ListItem};
+use crate::keymap::Command;
+use crate::startappsprovider::{AppEntry, AppEntryKind};
+use crate::gitrepos::{GitRepo, RepoUpdate};
+
+use crate::log::*;
+
+pub struct GitRepoEntry {
+    pub repo: GitRepo,
+}
+
+impl ListItem for GitRepoEntry {
+    fn as_any(&self) -> &dyn std::any::Any {
+        return self;
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        return self;
+    }
+
+    fn as_string(&self) -> String {
+        return format!("{}  {}", self.repo.display_name(), self.repo.path);
+    }
+
+    fn as_matchable_string(&self) -> String {
+        return self.repo.name();
+    }
+}
+
+// Git repositories under git_roots in config.json. Enter opens the repository in the
+// editor, alt+t opens a terminal in it, alt+o opens it in explorer and alt+w copies the path.
+// The cached list is shown right away and updated as the background refresh reads the
+// repositories.
+pub struct GitRepoProvider {
+    repos: Vec<GitRepo>,
+    editor: String,
+    updates: Option<std::sync::mpsc::Receiver<RepoUpdate>>,
+    query: String,
+    filtered: Vec<GitRepoEntry>,
+}
+
+impl GitRepoProvider {
+    pub fn new(config: &crate::config::Config) -> Box<Self> {
+        let mut provider = Self::with_repos(crate::gitrepos::load_cache(), config.editor.clone());
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let cached = provider.repos.clone();
+        let (roots, max_depth) = (config.git_roots.clone(), config.git_max_depth);
+        std::thread::spawn(move || {
+            crate::gitrepos::refresh(&roots, max_depth, &cached, &mut |update| {
+                // The receiver is gone if switch exited before the refresh finished.
+                let _ = sender.send(update);
+            });
+        });
+        provider.updates = Some(receiver);
+        return provider;
+    }
+
+    pub fn with_repos(mut repos: Vec<GitRepo>, editor: String) -> Box<Self> {
+        crate::gitrepos::sort_repos(&mut repos);
+        let mut provider = Box::new(GitRepoProvider {
+            repos,
+            editor,
+            updates: None,
+            query: String::new(),
+            filtered: vec![],
+        });
+        provider.filter();
+        return provider;
+    }
+
+    pub fn apply_update(&mut self, update: RepoUpdate) {
+        match update {
+            RepoUpdate::Repo(repo) => crate::gitrepos::update_repo(&mut self.repos, repo),
+            RepoUpdate::Done(found) => {
+                self.repos.retain(|repo| found.contains(&repo.path));
+                if let Err(e) = crate::index::save(crate::gitrepos::REPO_INDEX, &self.repos) {
+                    crate::trace!("git", log::Level::Error, "Failed to save repositories: {:?}", e);
+                }
+            },
+        }
+    }
+
+    fn receive_updates(&mut self) {
+        let updates = match &self.updates {
+            Some(updates) => updates.try_iter().collect::<Vec<RepoUpdate>>(),
+            None => return,
+        };
+        if updates.len() == 0 {
+            return;
+        }
+
+        for update in updates {
+            if let RepoUpdate::Done(_) = update {
+                self.updates = None;
+            }
+            self.apply_update(update);
+        }
+        self.filter();
+    }
+
+    fn filter(&mut self) {
+        let query = self.query.trim();
+        let mut scored = self.repos.iter()
+            .enumerate()
+            .filter_map(|(i, repo)| {
+                if query.len() == 0 {
+                    return Some((0, i, repo));
+                }
+                // Prefer matching the name, but a/b matches along the path.
+                let name_score = crate::fuzzy::score(query, &repo.name()).map(|score| score * 2);
+                return name_score.or_else(|| crate::fuzzy::score_path(query, &repo.path))
+                    .map(|score| (score, i, repo));
+            })
+            .collect::<Vec<(i32, usize, &GitRepo)>>();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        self.filtered = scored.into_iter()
+            .map(|(_, _, repo)| GitRepoEntry { repo: repo.clone() })
+            .collect();
+    }
+
+    pub fn editor_app(&self, repo: &GitRepo) -> AppEntry {
+        return AppEntry {
+            name: repo.name(),
+            kind: AppEntryKind::Command {
+                command: format!("{} {}", self.editor, crate::cmdline::quote_windows(&repo.path)),
+            },
+            ..Default::default()
+        };
+    }
+}
+
+impl ListContentProvider for GitRepoProvider {
+    fn name(&self) -> &str {
+        return "Git";
+    }
+
+    fn qualifiers(&self) -> Vec<(String, String)> {
+        return vec![("work/".to_string(), "repositories in a folder".to_string())];
+    }
+
+    fn total(&self) -> Option<usize> {
+        return Some(self.repos.len());
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "edit"), (Command::OpenFolder, "open"), (Command::OpenTerminal, "terminal"), (Command::Copy, "copy path")];
+    }
+
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        self.receive_updates();
+        return self.filtered.iter_mut().map(|e| e as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        self.receive_updates();
+        return self.filtered.iter().map(|e| e.as_string()).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.filter();
+    }
+
+    fn start(&mut self, filtered_index: usize, elevated: bool) {
+        let app = match self.filtered.get(filtered_index) {
+            Some(entry) => self.editor_app(&entry.repo),
+            None => return,
+        };
+
+        crate::trace!("start", log::Level::Info, "Open repository elevated {:?}: {:?}", elevated, app);
+        if let Err(e) = app.launch(elevated) {
+            crate::trace!("start", log::Level::Error, "Open repository error: {:?}", e);
+        }
+    }
+
+    fn remove(&mut self, _filtered_index: usize) {
+    }
+
+    fn run_action(&mut self, filtered_index: usize, action: Action, elevated: bool) -> bool {
+        let repo = match self.filtered.get(filtered_index) {
+            Some(entry) => &entry.repo,
+            None => return false,
+        };
+
+        let result = match action {
+            Action::OpenFolder => crate::open::open(&repo.path, elevated),
+            Action::OpenTerminal => crate::open::open_terminal(&repo.path, elevated),
+            _ => return false,
+        };
+        if let Err(e) = result {
+            crate::trace!("start", log::Level::Error, "{:?} error: {:?}", action, e);
+        }
+        return true;
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.filtered.get(filtered_index).map(|e| e.repo.path.clone());
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        return Some("#");
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "edit"), (Command::AcceptElevated, "edit as admin"), (Command::OpenFolder, "open folder"), (Command::OpenTerminal, "open terminal"), (Command::Copy, "copy path")];
+    }
+}
@@ -0,0 +1,218 @@
+use serde::{Serialize, Deserialize};
+
+// Git repositories under the code roots in config.json for the git repository provider.
+// Discovery stops descending at a directory with .git in it, so repositories inside
+// repositories like submodules aren't listed. The list is cached in REPO_INDEX and
+// refreshed in the background when the provider starts, reading the branch again only for
+// repositories whose HEAD or index changed since. git status is run for all of them, editing
+// a file writes neither.
+
+pub const REPO_INDEX: &str = "git_repos.json";
+
+// Not worth looking for repositories in.
+const SKIPPED_DIRECTORIES: [&str; 3] = ["node_modules", "target", "$RECYCLE.BIN"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GitRepo {
+    pub path: String,
+    // Current branch, or the abbreviated commit if HEAD is detached.
+    #[serde(default)]
+    pub branch: Option<String>,
+    // Uncommitted changes, None if git status couldn't tell.
+    #[serde(default)]
+    pub dirty: Option<bool>,
+    // When HEAD and the index were last written, in nanoseconds since the epoch, to tell if
+    // the branch and status need reading again.
+    #[serde(default)]
+    pub head_modified: Option<u64>,
+    #[serde(default)]
+    pub index_modified: Option<u64>,
+}
+
+impl GitRepo {
+    pub fn name(&self) -> String {
+        let separator = crate::directorylisting::is_separator;
+        return self.path.trim_end_matches(separator).rsplit(separator).next().unwrap_or(&self.path).to_owned();
+    }
+
+    // name (branch*)
+    pub fn display_name(&self) -> String {
+        let mut text = self.name();
+        if let Some(branch) = &self.branch {
+            text += &format!(" ({}{})", branch, if self.dirty == Some(true) { "*" } else { "" });
+        }
+        return text;
+    }
+}
+
+// The git directory of a repository, .git or where a .git file points for worktrees and
+// submodules.
+pub fn git_dir(repo: &std::path::Path) -> Option<std::path::PathBuf> {
+    let dot_git = repo.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+
+    let text = std::fs::read_to_string(&dot_git).ok()?;
+    let dir = text.lines().next()?.strip_prefix("gitdir:")?.trim();
+    return Some(repo.join(dir));
+}
+
+// The branch HEAD refers to, or the first 7 characters of the commit if it's detached.
+pub fn parse_head(head: &str) -> Option<String> {
+    let head = head.trim();
+    if let Some(reference) = head.strip_prefix("ref:") {
+        let reference = reference.trim();
+        return Some(reference.strip_prefix("refs/heads/").unwrap_or(reference).to_owned());
+    }
+
+    if head.len() >= 7 && head.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Some(head[..7].to_owned());
+    }
+    return None;
+}
+
+pub fn read_branch(repo: &std::path::Path) -> Option<String> {
+    return parse_head(&std::fs::read_to_string(git_dir(repo)?.join("HEAD")).ok()?);
+}
+
+// Whether git status lists any changes, None if git couldn't be run.
+pub fn read_dirty(repo: &std::path::Path) -> Option<bool> {
+    let mut command = std::process::Command::new("git");
+    command.arg("-C").arg(repo).args(["status", "--porcelain", "--ignore-submodules"]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    return Some(output.stdout.iter().any(|b| !b.is_ascii_whitespace()));
+}
+
+fn modified(path: &std::path::Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    return Some(modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_nanos() as u64);
+}
+
+// When HEAD and the index were last written. Checking out or committing writes HEAD and
+// staging writes the index, as does git status when it sees files have changed.
+pub fn modified_times(repo: &std::path::Path) -> (Option<u64>, Option<u64>) {
+    let dir = match git_dir(repo) {
+        Some(dir) => dir,
+        None => return (None, None),
+    };
+    return (modified(&dir.join("HEAD")), modified(&dir.join("index")));
+}
+
+pub fn read_repo(repo: &std::path::Path) -> GitRepo {
+    let (head_modified, index_modified) = modified_times(repo);
+    return GitRepo {
+        path: repo.to_string_lossy().into_owned(),
+        branch: read_branch(repo),
+        dirty: read_dirty(repo),
+        head_modified,
+        index_modified,
+    };
+}
+
+// The cached repository's branch is up to date if HEAD and the index haven't been written
+// since it was read. Its dirty flag may not be.
+pub fn is_current(cached: &GitRepo, repo: &std::path::Path) -> bool {
+    let (head_modified, index_modified) = modified_times(repo);
+    return head_modified.is_some() && head_modified == cached.head_modified && index_modified == cached.index_modified;
+}
+
+// Calls cb with every repository under root, not descending into them.
+pub fn discover(root: &std::path::Path, max_depth: i32, cb: &mut dyn FnMut(&std::path::Path)) {
+    if max_depth < 0 {
+        return;
+    }
+
+    if root.join(".git").exists() {
+        cb(root);
+        return;
+    }
+
+    let entries = match std::fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        // Don't follow symlinks and junctions, they can loop.
+        if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') || SKIPPED_DIRECTORIES.iter().any(|skipped| name.eq_ignore_ascii_case(skipped)) {
+            continue;
+        }
+        discover(&entry.path(), max_depth - 1, cb);
+    }
+}
+
+// Repositories sorted by name, then path.
+pub fn sort_repos(repos: &mut [GitRepo]) {
+    repos.sort_by(|a, b| {
+        a.name().to_lowercase().cmp(&b.name().to_lowercase()).then(a.path.cmp(&b.path))
+    });
+}
+
+// Replaces the cached repository at the same path with repo, or adds it.
+pub fn update_repo(repos: &mut Vec<GitRepo>, repo: GitRepo) {
+    match repos.iter_mut().find(|r| r.path == repo.path) {
+        Some(cached) => *cached = repo,
+        None => {
+            repos.push(repo);
+            sort_repos(repos);
+        },
+    }
+}
+
+pub enum RepoUpdate {
+    Repo(GitRepo),
+    // Discovery finished, these are all the repositories that still exist.
+    Done(Vec<String>),
+}
+
+// Discovers the repositories under roots and reads new ones and ones that changed since
+// they were cached, sending each as it's read so the cached list can be updated while the
+// rest are still being looked at. The others are only sent if git status changed. Repositories under a root that can't
+// be read, like an offline drive, are kept as they were.
+pub fn refresh(roots: &[String], max_depth: i32, cached: &[GitRepo], send: &mut dyn FnMut(RepoUpdate)) {
+    let mut found = vec![];
+    for root in roots.iter() {
+        let root = std::path::PathBuf::from(crate::expand::expand(root));
+        if std::fs::read_dir(&root).is_err() {
+            found.extend(cached.iter()
+                .filter(|repo| std::path::Path::new(&repo.path).starts_with(&root))
+                .map(|repo| repo.path.clone()));
+            continue;
+        }
+
+        discover(&root, max_depth, &mut |repo| {
+            let path = repo.to_string_lossy().into_owned();
+            found.push(path.clone());
+            match cached.iter().find(|cached| cached.path == path && is_current(cached, repo)) {
+                Some(cached) => {
+                    let dirty = read_dirty(repo);
+                    if dirty != cached.dirty {
+                        send(RepoUpdate::Repo(GitRepo { dirty, ..cached.clone() }));
+                    }
+                },
+                None => send(RepoUpdate::Repo(read_repo(repo))),
+            }
+        });
+    }
+    send(RepoUpdate::Done(found));
+}
+
+pub fn load_cache() -> Vec<GitRepo> {
+    return crate::index::load(REPO_INDEX).unwrap_or_default();
+}
@@ -0,0 +1,68 @@
+use crate::keymap::{Command, Keymap, COMMANDS};
+use crate::listcontentprovider::ListContentProvider;
+use crate::theme::align_columns;
+
+// The help F1 shows over the list: every bound key, the providers with their prefixes and the
+// query syntax they understand. It's made from the keymap and providers the UI is using, so
+// rebound keys and configured search engines show up as they are.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Help {
+    pub lines: Vec<String>,
+    // First line on screen.
+    pub scroll: usize,
+}
+
+impl Help {
+    pub fn new(keymap: &Keymap, providers: &[Box<dyn ListContentProvider>]) -> Help {
+        return Help { lines: help_lines(keymap, providers), scroll: 0 };
+    }
+
+    // Moves by lines, keeping a screen of height full if there's that much.
+    pub fn scroll_by(&mut self, lines: isize, height: usize) {
+        let max = self.lines.len().saturating_sub(height);
+        self.scroll = std::cmp::min((self.scroll as isize + lines).max(0) as usize, max);
+    }
+}
+
+fn chord_names(keymap: &Keymap, command: Command) -> String {
+    return keymap.chords(command).iter().map(|chord| chord.to_string()).collect::<Vec<String>>().join(", ");
+}
+
+pub fn help_lines(keymap: &Keymap, providers: &[Box<dyn ListContentProvider>]) -> Vec<String> {
+    let mut lines = vec!["Keys".to_string()];
+    let keys = COMMANDS.iter()
+        .map(|(name, command)| (name, chord_names(keymap, *command)))
+        .filter(|(_, chords)| chords.len() > 0)
+        .map(|(name, chords)| format!("  {}  {}", chords, name))
+        .collect::<Vec<String>>();
+    lines.extend(align_columns(&keys));
+
+    lines.push(String::new());
+    lines.push("Lists".to_string());
+    let lists = providers.iter().enumerate()
+        .map(|(i, provider)| {
+            let jump = if i < 9 { chord_names(keymap, Command::SelectProvider(i)) } else { String::new() };
+            let jump = if jump.len() > 0 { jump } else { "-".to_string() };
+            return match provider.prefix() {
+                Some(prefix) => format!("  {}  {}  {}", jump, provider.name(), prefix),
+                None => format!("  {}  {}", jump, provider.name()),
+            };
+        })
+        .collect::<Vec<String>>();
+    lines.extend(align_columns(&lists));
+
+    let queries = providers.iter()
+        .flat_map(|provider| {
+            let prefix = provider.prefix().unwrap_or("").to_string();
+            return provider.qualifiers().into_iter()
+                .map(move |(example, description)| format!("  {}{}  {}", prefix, example, description));
+        })
+        .collect::<Vec<String>>();
+    if queries.len() > 0 {
+        lines.push(String::new());
+        lines.push("Queries".to_string());
+        lines.extend(align_columns(&queries));
+    }
+    return lines;
+}
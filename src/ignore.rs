@@ -0,0 +1,202 @@
+// The .gitignore pattern format, used to skip files when indexing.
+// https://git-scm.com/docs/gitignore
+//
+// Supported: comments, blank lines, ! negation, trailing / for directories only,
+// patterns containing / are relative to the directory of the ignore file,
+// * ? [a-z] [!a-z] wildcards and ** for any number of directories.
+// Matching is ASCII case insensitive on Windows, like its file names, and case sensitive elsewhere.
+
+// Files read in every directory while walking, later ones take precedence.
+pub const IGNORE_FILE_NAMES: &[&str] = &[".gitignore", ".ignore"];
+
+#[derive(Debug, Clone, PartialEq)]
+struct Pattern {
+    // Path segments, ** is kept as its own segment.
+    segments: Vec<String>,
+    negated: bool,
+    dir_only: bool,
+    // Only match relative to the ignore file, otherwise the pattern matches the name at any depth.
+    anchored: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IgnoreFile {
+    patterns: Vec<Pattern>,
+}
+
+impl IgnoreFile {
+    pub fn parse(text: &str) -> Self {
+        return IgnoreFile {
+            patterns: text.lines().filter_map(parse_pattern).collect(),
+        };
+    }
+
+    pub fn from_patterns(patterns: &[String]) -> Self {
+        return IgnoreFile {
+            patterns: patterns.iter().filter_map(|p| parse_pattern(p)).collect(),
+        };
+    }
+
+    pub fn load<IntoPath: Into<std::path::PathBuf>>(path: IntoPath) -> anyhow::Result<Self> {
+        return Ok(Self::parse(&std::fs::read_to_string(path.into())?));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.patterns.len() == 0;
+    }
+
+    // Some(true) if the last pattern matching path ignores it, Some(false) if it is
+    // re-included with !, None if no pattern matches.
+    // path is relative to the directory of the ignore file, separated by / or \.
+    pub fn matched(&self, path: &str, is_dir: bool) -> Option<bool> {
+        let segments = path.split(|c| c == '/' || c == '\\')
+            .filter(|s| s.len() > 0)
+            .collect::<Vec<&str>>();
+
+        for pattern in self.patterns.iter().rev() {
+            if pattern.dir_only && !is_dir {
+                continue;
+            }
+
+            let matched = if pattern.anchored {
+                match_segments(&pattern.segments, &segments)
+            } else {
+                segments.last().map(|name| match_segment(&pattern.segments[0], name)).unwrap_or(false)
+            };
+
+            if matched {
+                return Some(!pattern.negated);
+            }
+        }
+
+        return None;
+    }
+
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        return self.matched(path, is_dir).unwrap_or(false);
+    }
+}
+
+fn parse_pattern(line: &str) -> Option<Pattern> {
+    // Trailing spaces are ignored unless escaped.
+    let mut line = line.trim_end_matches(|c| c == '\r' || c == '\n');
+    while line.ends_with(' ') && !line.ends_with("\\ ") {
+        line = &line[..line.len() - 1];
+    }
+
+    if line.len() == 0 || line.starts_with('#') {
+        return None;
+    }
+
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').filter(|rest| rest.starts_with('#') || rest.starts_with('!')).unwrap_or(line)),
+    };
+
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+
+    // A slash at the start or middle makes the pattern relative to the ignore file.
+    let anchored = line.contains('/');
+    let segments = line.split('/')
+        .filter(|s| s.len() > 0)
+        .map(String::from)
+        .collect::<Vec<String>>();
+
+    if segments.len() == 0 {
+        return None;
+    }
+
+    // **/name is the same as name.
+    if segments.len() == 2 && segments[0] == "**" && !line.starts_with('/') {
+        return Some(Pattern { segments: segments[1..].to_vec(), negated, dir_only, anchored: false });
+    }
+
+    return Some(Pattern { segments, negated, dir_only, anchored });
+}
+
+fn match_segments(pattern: &[String], path: &[&str]) -> bool {
+    if pattern.len() == 0 {
+        return path.len() == 0;
+    }
+
+    if pattern[0] == "**" {
+        // Trailing ** matches everything inside, but not the directory itself.
+        if pattern.len() == 1 {
+            return path.len() > 0;
+        }
+        return (0..=path.len()).any(|skip| match_segments(&pattern[1..], &path[skip..]));
+    }
+
+    return path.len() > 0 && match_segment(&pattern[0], path[0]) && match_segments(&pattern[1..], &path[1..]);
+}
+
+// Wildcard match of a single path segment.
+pub fn match_segment(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let name = name.chars().collect::<Vec<char>>();
+    return match_chars(&pattern, &name);
+}
+
+// c as it is, and on Windows in both ASCII cases.
+#[cfg(windows)]
+fn case_variants(c: char) -> [char; 2] {
+    return [c.to_ascii_lowercase(), c.to_ascii_uppercase()];
+}
+
+#[cfg(not(windows))]
+fn case_variants(c: char) -> [char; 2] {
+    return [c, c];
+}
+
+fn chars_equal(pattern: char, c: char) -> bool {
+    return case_variants(c).contains(&pattern);
+}
+
+fn match_chars(pattern: &[char], name: &[char]) -> bool {
+    if pattern.len() == 0 {
+        return name.len() == 0;
+    }
+
+    return match pattern[0] {
+        '*' => (0..=name.len()).any(|skip| match_chars(&pattern[1..], &name[skip..])),
+        '?' => name.len() > 0 && match_chars(&pattern[1..], &name[1..]),
+        '[' => match match_class(&pattern[1..], name.first().copied()) {
+            Some((matched, class_len)) => matched && match_chars(&pattern[1 + class_len..], &name[1..]),
+            // No closing ], treat [ literally.
+            None => name.first() == Some(&'[') && match_chars(&pattern[1..], &name[1..]),
+        },
+        '\\' if pattern.len() > 1 => name.first().map_or(false, |&n| chars_equal(pattern[1], n)) && match_chars(&pattern[2..], &name[1..]),
+        c => name.first().map_or(false, |&n| chars_equal(c, n)) && match_chars(&pattern[1..], &name[1..]),
+    };
+}
+
+// Matches c against the class after [, returns whether it matched and the length of the class including ].
+fn match_class(class: &[char], c: Option<char>) -> Option<(bool, usize)> {
+    let negated = class.first() == Some(&'!') || class.first() == Some(&'^');
+    let start = if negated { 1 } else { 0 };
+    // ] right after [ or [! is a literal.
+    let end = class.iter().skip(start + 1).position(|&x| x == ']')? + start + 1;
+
+    let c = match c {
+        Some(c) => c,
+        None => return Some((false, end + 1)),
+    };
+
+    let items = &class[start..end];
+    let mut matched = false;
+    let mut i = 0;
+    while i < items.len() {
+        if i + 2 < items.len() && items[i + 1] == '-' {
+            matched |= case_variants(c).iter().any(|&c| items[i] <= c && c <= items[i + 2]);
+            i += 3;
+        } else {
+            matched |= chars_equal(items[i], c);
+            i += 1;
+        }
+    }
+
+    return Some((matched != negated, end + 1));
+}
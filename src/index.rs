@@ -0,0 +1,146 @@
+// Directory walking and the json files in %APPDATA%\switch\ that the indexer writes
+// for the providers to read.
+
+use crate::ignore::{IgnoreFile, IGNORE_FILE_NAMES};
+
+use crate::log::*;
+
+// Calls cb for every file under root, directories themselves aren't passed to cb.
+pub fn visit_directories<IntoPath>(root: IntoPath, cb: &mut dyn FnMut(&std::fs::DirEntry), max_depth: i32) -> std::io::Result<()>
+where IntoPath: Into<std::path::PathBuf> {
+    let options = WalkOptions {
+        ignore_file_names: &[],
+        follow_links: true,
+        skip_errors: false,
+    };
+    return walk(root.into(), &options, vec![], max_depth, &mut |entry, is_dir| {
+        if !is_dir {
+            cb(entry);
+        }
+    });
+}
+
+struct WalkOptions<'a> {
+    // Read in every directory, none for a plain walk.
+    ignore_file_names: &'a [&'a str],
+    // Descend into symlinks and junctions to directories.
+    follow_links: bool,
+    // Skip unreadable directories below root instead of returning the error.
+    skip_errors: bool,
+}
+
+// An ignore file and the directory it applies to.
+struct IgnoreScope {
+    dir: std::path::PathBuf,
+    rules: IgnoreFile,
+}
+
+impl IgnoreScope {
+    fn matched(&self, path: &std::path::Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.dir).ok()?;
+        return self.rules.matched(&relative.to_string_lossy(), is_dir);
+    }
+}
+
+// Like visit_directories but also calls cb for directories, and skips whatever the
+// .gitignore and .ignore files along the way, or the extra patterns, exclude.
+// Ignored directories aren't descended into, and neither are links since they can loop.
+// Unreadable directories below root are skipped.
+pub fn walk_ignoring<IntoPath>(root: IntoPath, extra_ignores: &[String], max_depth: i32, cb: &mut dyn FnMut(&std::path::Path, bool)) -> std::io::Result<()>
+where IntoPath: Into<std::path::PathBuf> {
+    let root = root.into();
+    let scopes = vec![IgnoreScope {
+        dir: root.clone(),
+        rules: IgnoreFile::from_patterns(extra_ignores),
+    }];
+    let options = WalkOptions {
+        ignore_file_names: IGNORE_FILE_NAMES,
+        follow_links: false,
+        skip_errors: true,
+    };
+    return walk(root, &options, scopes, max_depth, &mut |entry, is_dir| cb(&entry.path(), is_dir));
+}
+
+// The walk behind visit_directories and walk_ignoring, visit_directories follows links and
+// stops at the first error like it always has.
+fn walk(root: std::path::PathBuf, options: &WalkOptions, mut scopes: Vec<IgnoreScope>, max_depth: i32, cb: &mut dyn FnMut(&std::fs::DirEntry, bool)) -> std::io::Result<()> {
+    // Read the root here so a missing root is an error instead of an empty result.
+    std::fs::read_dir(&root)?;
+    return walk_impl(&root, options, &mut scopes, max_depth, cb);
+}
+
+fn walk_impl(dir: &std::path::Path, options: &WalkOptions, scopes: &mut Vec<IgnoreScope>, max_depth: i32, cb: &mut dyn FnMut(&std::fs::DirEntry, bool)) -> std::io::Result<()> {
+    if max_depth < 0 {
+        return Ok(());
+    }
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if options.skip_errors => {
+            crate::trace!("indexer", log::Level::Info, "Skipping {:?}: {:?}", dir, e);
+            return Ok(());
+        },
+        Err(e) => return Err(e),
+    };
+
+    let scopes_len = scopes.len();
+    for name in options.ignore_file_names {
+        let path = dir.join(name);
+        if let Ok(rules) = IgnoreFile::load(&path) {
+            if !rules.is_empty() {
+                scopes.push(IgnoreScope { dir: dir.to_owned(), rules });
+            }
+        }
+    }
+
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) if options.skip_errors => continue,
+            Err(e) => return Err(e),
+        };
+        let path = entry.path();
+        let is_dir = if options.follow_links {
+            path.is_dir()
+        } else {
+            entry.file_type().map(|t| t.is_dir()).unwrap_or(false)
+        };
+
+        // The innermost ignore file that has an opinion wins.
+        let ignored = scopes.iter().rev()
+            .find_map(|scope| scope.matched(&path, is_dir))
+            .unwrap_or(false);
+        if ignored {
+            continue;
+        }
+
+        cb(&entry, is_dir);
+        if is_dir {
+            walk_impl(&path, options, scopes, max_depth - 1, cb)?;
+        }
+    }
+
+    scopes.truncate(scopes_len);
+    return Ok(());
+}
+
+pub fn save<T: serde::Serialize + ?Sized>(file: &str, value: &T) -> anyhow::Result<()> {
+    return save_to(crate::path::get_app_data_path(file)?, value);
+}
+
+pub fn save_to<IntoPath: Into<std::path::PathBuf>, T: serde::Serialize + ?Sized>(path: IntoPath, value: &T) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let mut file = std::fs::File::create(path.into())?;
+    file.write_all(serde_json::to_string(value)?.as_bytes())?;
+    file.sync_all()?;
+    return Ok(());
+}
+
+pub fn load<T: serde::de::DeserializeOwned>(file: &str) -> anyhow::Result<T> {
+    return load_from(crate::path::get_app_data_path(file)?);
+}
+
+pub fn load_from<IntoPath: Into<std::path::PathBuf>, T: serde::de::DeserializeOwned>(path: IntoPath) -> anyhow::Result<T> {
+    return Ok(serde_json::from_str(&std::fs::read_to_string(path.into())?)?);
+}
@@ -0,0 +1,441 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// The editable query line of the switch UI. The cursor moves and deletes by grapheme, so an
+// accented letter made of two chars or an emoji with a skin tone is one step, and the line
+// is drawn by display width, so CJK characters take two columns.
+//
+// Editing works like emacs: killed text goes to a kill ring to yank back, consecutive kills
+// join into one entry, and every edit can be undone, a run of typed characters at once.
+
+pub const WORD_BOUNDARIES: &str = " ;:\\/-=";
+
+pub const KILL_RING_SIZE: usize = 16;
+pub const UNDO_SIZE: usize = 100;
+
+// What the last command was, for joining kills, grouping typing in undo and yank-pop.
+#[derive(Clone, Copy, PartialEq)]
+enum LastEdit {
+    Other,
+    Insert,
+    Kill,
+    Yank,
+}
+
+pub struct InputLine {
+    buffer: String,
+    // Byte offset of the cursor, always at the start or end of a grapheme.
+    pos: usize,
+    // Columns scrolled off the left when the line is wider than where it's drawn.
+    scroll: usize,
+    // Newest last.
+    kill_ring: Vec<String>,
+    // Byte offset where the last yank starts, it ends at the cursor, and its kill ring entry.
+    yanked: Option<(usize, usize)>,
+    // Buffer and cursor before each edit, and the ones undone.
+    undo: Vec<(String, usize)>,
+    redo: Vec<(String, usize)>,
+    last: LastEdit,
+}
+
+impl Default for InputLine {
+    fn default() -> Self {
+        return InputLine {
+            buffer: String::new(),
+            pos: 0,
+            scroll: 0,
+            kill_ring: vec![],
+            yanked: None,
+            undo: vec![],
+            redo: vec![],
+            last: LastEdit::Other,
+        };
+    }
+}
+
+impl From<&InputLine> for String {
+    fn from(il: &InputLine) -> Self {
+        return il.buffer.clone();
+    }
+}
+
+fn is_word_boundary(grapheme: &str) -> bool {
+    let mut chars = grapheme.chars();
+    return match (chars.next(), chars.next()) {
+        (Some(c), None) => WORD_BOUNDARIES.contains(c),
+        _ => false,
+    };
+}
+
+impl InputLine {
+    fn graphemes(&self) -> Vec<&str> {
+        return self.buffer.graphemes(true).collect();
+    }
+
+    // Byte offset of the grapheme at index, the end of the buffer past the last one.
+    fn byte_offset(&self, index: usize) -> usize {
+        return self.buffer.grapheme_indices(true).nth(index).map(|(i, _)| i).unwrap_or(self.buffer.len());
+    }
+
+    // Replaces graphemes from..to with text, the cursor goes after it.
+    fn splice(&mut self, from: usize, to: usize, text: &str) {
+        let from = self.byte_offset(from);
+        let to = self.byte_offset(to);
+        self.buffer.replace_range(from..to, text);
+        self.pos = from + text.len();
+        self.snap_cursor();
+    }
+
+    // Saves the line for undo before an edit, unless it's more typing.
+    fn begin(&mut self, edit: LastEdit) {
+        let state = (self.buffer.clone(), self.pos);
+        if !(edit == LastEdit::Insert && self.last == LastEdit::Insert) && self.undo.last() != Some(&state) {
+            self.undo.push(state);
+            if self.undo.len() > UNDO_SIZE {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        if edit != LastEdit::Yank {
+            self.yanked = None;
+        }
+        self.last = edit;
+    }
+
+    // Moving the cursor ends a run of typing or kills.
+    fn moved(&mut self) {
+        self.last = LastEdit::Other;
+        self.yanked = None;
+    }
+
+    // Removes graphemes from..to into the kill ring. Right after another kill it's added to
+    // that kill, in front of it when killing backwards.
+    fn kill(&mut self, from: usize, to: usize) {
+        if from == to {
+            return;
+        }
+
+        let killed = self.buffer[self.byte_offset(from)..self.byte_offset(to)].to_owned();
+        let joined = self.last == LastEdit::Kill && self.kill_ring.len() > 0;
+        let backward = to == self.cursor_pos();
+        self.begin(LastEdit::Kill);
+        if joined {
+            let last = self.kill_ring.last_mut().unwrap();
+            if backward {
+                last.insert_str(0, &killed);
+            } else {
+                last.push_str(&killed);
+            }
+        } else {
+            self.kill_ring.push(killed);
+            if self.kill_ring.len() > KILL_RING_SIZE {
+                self.kill_ring.remove(0);
+            }
+        }
+        self.splice(from, to, "");
+    }
+
+    // Text inserted in front of a combining mark joins its grapheme, keep the cursor out of it.
+    fn snap_cursor(&mut self) {
+        self.pos = self.buffer.grapheme_indices(true)
+            .map(|(i, _)| i)
+            .chain(std::iter::once(self.buffer.len()))
+            .find(|&i| i >= self.pos)
+            .unwrap();
+    }
+
+    pub fn clear(&mut self) {
+        self.begin(LastEdit::Other);
+        self.buffer.clear();
+        self.pos = 0;
+        self.scroll = 0;
+    }
+
+    pub fn push(&mut self, ch: char) {
+        self.buffer.push(ch);
+    }
+
+    pub fn insert(&mut self, ch: char) {
+        self.begin(LastEdit::Insert);
+        self.buffer.insert(self.pos, ch);
+        self.pos += ch.len_utf8();
+        self.snap_cursor();
+    }
+
+    pub fn backward_remove_cursor(&mut self) {
+        let pos = self.cursor_pos();
+        if pos > 0 {
+            self.begin(LastEdit::Other);
+            self.splice(pos - 1, pos, "");
+        }
+    }
+
+    pub fn forward_remove_cursor(&mut self) {
+        let pos = self.cursor_pos();
+        if pos < self.len() {
+            self.begin(LastEdit::Other);
+            self.splice(pos, pos + 1, "");
+        }
+    }
+
+    pub fn backward_find_word(&self) -> usize {
+        let graphemes = self.graphemes();
+        let mut past_boundary = 0;
+
+        for i in (0 .. self.cursor_pos()).rev() {
+            if !is_word_boundary(graphemes[i]) {
+                past_boundary = i;
+                break;
+            }
+        }
+
+        for i in (0 .. past_boundary).rev() {
+            if is_word_boundary(graphemes[i]) {
+                return i + 1;
+            }
+        }
+
+        return 0usize;
+    }
+
+    pub fn backward_kill_word(&mut self) {
+        let i = self.backward_find_word();
+        self.kill(i, self.cursor_pos());
+    }
+
+    pub fn forward_find_word(&self) -> usize {
+        let graphemes = self.graphemes();
+        let mut past_boundary = graphemes.len();
+
+        for i in self.cursor_pos() + 1 .. graphemes.len() {
+            if !is_word_boundary(graphemes[i]) {
+                past_boundary = i;
+                break;
+            }
+        }
+
+        for i in past_boundary .. graphemes.len() {
+            if is_word_boundary(graphemes[i]) {
+                return i;
+            }
+        }
+
+        return graphemes.len();
+    }
+
+    pub fn forward_kill_word(&mut self) {
+        let i = self.forward_find_word();
+        self.kill(self.cursor_pos(), i);
+    }
+
+    pub fn backward_kill_line(&mut self) {
+        self.kill(0, self.cursor_pos());
+    }
+
+    pub fn forward_kill_line(&mut self) {
+        self.kill(self.cursor_pos(), self.len());
+    }
+
+    pub fn backward_word(&mut self) {
+        self.set_cursor_pos(self.backward_find_word());
+    }
+
+    pub fn forward_word(&mut self) {
+        self.set_cursor_pos(self.forward_find_word());
+    }
+
+    pub fn cursor_end(&mut self) {
+        self.moved();
+        self.pos = self.buffer.len();
+    }
+
+    pub fn cursor_begin(&mut self) {
+        self.moved();
+        self.pos = 0;
+    }
+
+    pub fn cursor_move(&mut self, delta: isize) {
+        let result = self.cursor_pos() as isize + delta;
+        if result >= 0 && result <= self.len() as isize {
+            self.set_cursor_pos(result as usize);
+        }
+    }
+
+    // In graphemes.
+    pub fn cursor_pos(&self) -> usize {
+        return self.buffer[..self.pos].graphemes(true).count();
+    }
+
+    // Clamped to the end of the buffer.
+    pub fn set_cursor_pos(&mut self, pos: usize) {
+        self.moved();
+        self.pos = self.byte_offset(pos);
+    }
+
+    // In graphemes.
+    pub fn len(&self) -> usize {
+        return self.buffer.graphemes(true).count();
+    }
+
+    // Columns the text before the cursor takes on screen.
+    pub fn cursor_column(&self) -> usize {
+        return self.buffer[..self.pos].width();
+    }
+
+    pub fn reset_buffer<IntoString: Into<String>>(&mut self, s: IntoString) {
+        self.begin(LastEdit::Other);
+        self.buffer = s.into();
+        self.pos = self.buffer.len();
+    }
+
+    pub fn insert_string<IntoString: Into<String>>(&mut self, s: IntoString) {
+        let s = s.into() as String;
+        self.begin(LastEdit::Other);
+        self.buffer.insert_str(self.pos, &s);
+        self.pos += s.len();
+        self.snap_cursor();
+    }
+
+    // Inserts the last kill.
+    pub fn yank(&mut self) {
+        let text = match self.kill_ring.last() {
+            Some(text) => text.clone(),
+            None => return,
+        };
+        self.begin(LastEdit::Yank);
+        let start = self.pos;
+        self.buffer.insert_str(self.pos, &text);
+        self.pos += text.len();
+        self.snap_cursor();
+        self.yanked = Some((start, self.kill_ring.len() - 1));
+    }
+
+    // Right after a yank, replaces what was yanked with the kill before it, going round the
+    // ring. Returns false if the last command wasn't a yank.
+    pub fn yank_pop(&mut self) -> bool {
+        let (start, index) = match self.yanked {
+            Some(yanked) if self.last == LastEdit::Yank => yanked,
+            _ => return false,
+        };
+        let index = (index + self.kill_ring.len() - 1) % self.kill_ring.len();
+        let text = self.kill_ring[index].clone();
+
+        self.begin(LastEdit::Yank);
+        self.buffer.replace_range(start..self.pos, &text);
+        self.pos = start + text.len();
+        self.snap_cursor();
+        self.yanked = Some((start, index));
+        return true;
+    }
+
+    pub fn kill_ring(&self) -> &[String] {
+        return &self.kill_ring;
+    }
+
+    // Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let (buffer, pos) = match self.undo.pop() {
+            Some(state) => state,
+            None => return false,
+        };
+        self.redo.push((std::mem::replace(&mut self.buffer, buffer), self.pos));
+        self.pos = pos;
+        self.moved();
+        return true;
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let (buffer, pos) = match self.redo.pop() {
+            Some(state) => state,
+            None => return false,
+        };
+        self.undo.push((std::mem::replace(&mut self.buffer, buffer), self.pos));
+        self.pos = pos;
+        self.moved();
+        return true;
+    }
+
+    // Swaps the graphemes around the cursor and moves past them, at the end of the line the
+    // last two.
+    pub fn transpose_chars(&mut self) {
+        let len = self.len();
+        let pos = std::cmp::min(self.cursor_pos(), len.saturating_sub(1));
+        if pos == 0 {
+            return;
+        }
+
+        let graphemes = self.graphemes();
+        let swapped = format!("{}{}", graphemes[pos], graphemes[pos - 1]);
+        self.begin(LastEdit::Other);
+        self.splice(pos - 1, pos + 1, &swapped);
+    }
+
+    // From the cursor to the end of the word, the cursor goes after it.
+    fn change_word_case(&mut self, change: fn(&str) -> String) {
+        let end = self.forward_find_word();
+        let word = self.buffer[self.pos..self.byte_offset(end)].to_owned();
+        self.begin(LastEdit::Other);
+        self.splice(self.cursor_pos(), end, &change(&word));
+    }
+
+    pub fn upcase_word(&mut self) {
+        self.change_word_case(|word| word.to_uppercase());
+    }
+
+    pub fn downcase_word(&mut self) {
+        self.change_word_case(|word| word.to_lowercase());
+    }
+
+    // The first letter in upper case and the rest in lower case.
+    pub fn capitalize_word(&mut self) {
+        self.change_word_case(|word| {
+            let mut capitalized = String::new();
+            let mut first = true;
+            for c in word.chars() {
+                if first && c.is_alphanumeric() {
+                    capitalized.extend(c.to_uppercase());
+                    first = false;
+                } else {
+                    capitalized.extend(c.to_lowercase());
+                }
+            }
+            return capitalized;
+        });
+    }
+
+    // The part of the line that fits in width columns and the cursor's column in it. Scrolls
+    // as little as possible to keep the cursor in view, with a column for it past the end.
+    pub fn view(&mut self, width: usize) -> (String, usize) {
+        if width == 0 {
+            return (String::new(), 0);
+        }
+
+        let cursor = self.cursor_column();
+        let total = self.buffer.width();
+        self.scroll = std::cmp::min(self.scroll, (total + 1).saturating_sub(width));
+        if cursor < self.scroll {
+            self.scroll = cursor;
+        } else if cursor - self.scroll >= width {
+            self.scroll = cursor + 1 - width;
+        }
+
+        // A wide grapheme cut by either edge is left out, with spaces in its place on the left.
+        let mut text = String::new();
+        let mut column = 0;
+        for grapheme in self.buffer.graphemes(true) {
+            let start = column;
+            column += grapheme.width();
+            if start < self.scroll {
+                if column > self.scroll {
+                    text += &" ".repeat(column - self.scroll);
+                }
+                continue;
+            }
+            if column > self.scroll + width {
+                break;
+            }
+            text += grapheme;
+        }
+        return (text, cursor - self.scroll);
+    }
+}
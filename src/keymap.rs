@@ -0,0 +1,406 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::log::*;
+
+// Key chords of the switch UI and the named commands they run. The default keymap is what
+// switch always did, a preset adds emacs or vim style list keys on top and config.json can
+// bind or unbind any chord, like "ctrl+n": "list-next" or "ctrl+k": "none".
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Exit,
+    Accept,
+    AcceptElevated,
+    ListNext,
+    ListPrevious,
+    PageDown,
+    PageUp,
+    BeginningOfLine,
+    EndOfLine,
+    BackwardChar,
+    ForwardChar,
+    BackwardWord,
+    ForwardWord,
+    BackwardDeleteChar,
+    DeleteChar,
+    BackwardKillWord,
+    KillWord,
+    BackwardKillLine,
+    KillLine,
+    Yank,
+    YankPop,
+    Undo,
+    Redo,
+    TransposeChars,
+    CapitalizeWord,
+    UpcaseWord,
+    DowncaseWord,
+    HistorySearch,
+    ToggleMark,
+    Help,
+    // Lists what can be done to the selected item, from its provider's actions.
+    ActionsMenu,
+    Complete,
+    NextProvider,
+    // Jumps to a provider by its number in the status bar, 0 for the first.
+    SelectProvider(usize),
+    Remove,
+    KillTree,
+    OpenFolder,
+    OpenTerminal,
+    ToggleHidden,
+    Copy,
+    RaisePriority,
+    LowerPriority,
+}
+
+// Names used in config.json, in the order help lists them.
+pub const COMMANDS: &[(&str, Command)] = &[
+    ("accept", Command::Accept),
+    ("accept-elevated", Command::AcceptElevated),
+    ("exit", Command::Exit),
+    ("list-next", Command::ListNext),
+    ("list-previous", Command::ListPrevious),
+    ("page-down", Command::PageDown),
+    ("page-up", Command::PageUp),
+    ("complete", Command::Complete),
+    ("next-provider", Command::NextProvider),
+    ("provider-1", Command::SelectProvider(0)),
+    ("provider-2", Command::SelectProvider(1)),
+    ("provider-3", Command::SelectProvider(2)),
+    ("provider-4", Command::SelectProvider(3)),
+    ("provider-5", Command::SelectProvider(4)),
+    ("provider-6", Command::SelectProvider(5)),
+    ("provider-7", Command::SelectProvider(6)),
+    ("provider-8", Command::SelectProvider(7)),
+    ("provider-9", Command::SelectProvider(8)),
+    ("beginning-of-line", Command::BeginningOfLine),
+    ("end-of-line", Command::EndOfLine),
+    ("backward-char", Command::BackwardChar),
+    ("forward-char", Command::ForwardChar),
+    ("backward-word", Command::BackwardWord),
+    ("forward-word", Command::ForwardWord),
+    ("backward-delete-char", Command::BackwardDeleteChar),
+    ("delete-char", Command::DeleteChar),
+    ("backward-kill-word", Command::BackwardKillWord),
+    ("kill-word", Command::KillWord),
+    ("backward-kill-line", Command::BackwardKillLine),
+    ("kill-line", Command::KillLine),
+    ("yank", Command::Yank),
+    ("yank-pop", Command::YankPop),
+    ("undo", Command::Undo),
+    ("redo", Command::Redo),
+    ("transpose-chars", Command::TransposeChars),
+    ("capitalize-word", Command::CapitalizeWord),
+    ("upcase-word", Command::UpcaseWord),
+    ("downcase-word", Command::DowncaseWord),
+    ("history-search", Command::HistorySearch),
+    ("toggle-mark", Command::ToggleMark),
+    ("help", Command::Help),
+    ("actions-menu", Command::ActionsMenu),
+    ("remove", Command::Remove),
+    ("kill-tree", Command::KillTree),
+    ("open-folder", Command::OpenFolder),
+    ("open-terminal", Command::OpenTerminal),
+    ("toggle-hidden", Command::ToggleHidden),
+    ("copy", Command::Copy),
+    ("raise-priority", Command::RaisePriority),
+    ("lower-priority", Command::LowerPriority),
+];
+
+// Removes a binding in config.json.
+pub const UNBIND: &str = "none";
+
+impl Command {
+    pub fn parse(name: &str) -> Option<Command> {
+        return COMMANDS.iter().find(|(n, _)| *n == name).map(|(_, command)| *command);
+    }
+
+    pub fn name(&self) -> &'static str {
+        return COMMANDS.iter().find(|(_, command)| command == self).map(|(name, _)| *name).unwrap();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("space", KeyCode::Char(' ')),
+];
+
+const KEY_ALIASES: &[(&str, &str)] = &[
+    ("escape", "esc"),
+    ("del", "delete"),
+    ("return", "enter"),
+    ("pgup", "pageup"),
+    ("pgdn", "pagedown"),
+];
+
+impl KeyChord {
+    pub fn new(modifiers: KeyModifiers, code: KeyCode) -> KeyChord {
+        return KeyChord { code, modifiers }.normalized();
+    }
+
+    // Modifiers then the key joined by +, like ctrl+shift+delete, alt+o, f1 or ?.
+    pub fn parse(text: &str) -> anyhow::Result<KeyChord> {
+        let text = text.trim().to_lowercase();
+        // The key itself can be +.
+        let (modifiers, key) = if text == "+" {
+            ("", "+")
+        } else if let Some(modifiers) = text.strip_suffix("++") {
+            (modifiers, "+")
+        } else {
+            text.rsplit_once('+').unwrap_or(("", &text))
+        };
+
+        let mut chord_modifiers = KeyModifiers::NONE;
+        for modifier in modifiers.split('+').filter(|m| m.len() > 0) {
+            chord_modifiers |= match modifier {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => anyhow::bail!("Unknown modifier {} in {}", modifier, text),
+            };
+        }
+
+        let key = KEY_ALIASES.iter().find(|(alias, _)| *alias == key).map(|(_, name)| *name).unwrap_or(key);
+        let code = if let Some((_, code)) = KEY_NAMES.iter().find(|(name, _)| *name == key) {
+            *code
+        } else if let Some(n) = key.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()).filter(|n| *n >= 1 && *n <= 24) {
+            KeyCode::F(n)
+        } else {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => anyhow::bail!("Unknown key {} in {}", key, text),
+            }
+        };
+
+        return Ok(KeyChord::new(chord_modifiers, code));
+    }
+
+    // Letters are compared lowercase since shift or caps lock changes what ctrl+n arrives as.
+    fn normalized(mut self) -> KeyChord {
+        if let KeyCode::Char(c) = self.code {
+            self.code = KeyCode::Char(c.to_ascii_lowercase());
+        }
+        return self;
+    }
+
+    fn from_event(key: &KeyEvent) -> KeyChord {
+        let modifiers = key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        return KeyChord::new(modifiers, key.code);
+    }
+}
+
+impl std::fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (modifier, name) in [(KeyModifiers::CONTROL, "ctrl+"), (KeyModifiers::ALT, "alt+"), (KeyModifiers::SHIFT, "shift+")] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        if let Some((name, _)) = KEY_NAMES.iter().find(|(_, code)| *code == self.code) {
+            return f.write_str(name);
+        }
+        return match self.code {
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            code => write!(f, "{:?}", code),
+        };
+    }
+}
+
+// Today's keys.
+const DEFAULT_BINDINGS: &[(&str, Command)] = &[
+    ("enter", Command::Accept),
+    ("ctrl+enter", Command::AcceptElevated),
+    ("esc", Command::Exit),
+    ("ctrl+c", Command::Exit),
+    ("down", Command::ListNext),
+    ("up", Command::ListPrevious),
+    ("pagedown", Command::PageDown),
+    ("pageup", Command::PageUp),
+    ("tab", Command::Complete),
+    ("ctrl+tab", Command::NextProvider),
+    ("f2", Command::NextProvider),
+    ("alt+1", Command::SelectProvider(0)),
+    ("alt+2", Command::SelectProvider(1)),
+    ("alt+3", Command::SelectProvider(2)),
+    ("alt+4", Command::SelectProvider(3)),
+    ("alt+5", Command::SelectProvider(4)),
+    ("alt+6", Command::SelectProvider(5)),
+    ("alt+7", Command::SelectProvider(6)),
+    ("alt+8", Command::SelectProvider(7)),
+    ("alt+9", Command::SelectProvider(8)),
+    ("home", Command::BeginningOfLine),
+    ("end", Command::EndOfLine),
+    ("left", Command::BackwardChar),
+    ("right", Command::ForwardChar),
+    ("ctrl+left", Command::BackwardWord),
+    ("ctrl+right", Command::ForwardWord),
+    ("backspace", Command::BackwardDeleteChar),
+    ("ctrl+backspace", Command::BackwardKillWord),
+    ("ctrl+u", Command::BackwardKillLine),
+    ("ctrl+k", Command::KillLine),
+    ("ctrl+y", Command::Yank),
+    ("alt+y", Command::YankPop),
+    ("ctrl+z", Command::Undo),
+    ("ctrl+shift+z", Command::Redo),
+    ("ctrl+t", Command::TransposeChars),
+    ("alt+c", Command::CapitalizeWord),
+    ("alt+u", Command::UpcaseWord),
+    ("alt+l", Command::DowncaseWord),
+    ("ctrl+r", Command::HistorySearch),
+    ("space", Command::ToggleMark),
+    ("f1", Command::Help),
+    ("?", Command::Help),
+    ("shift+f10", Command::ActionsMenu),
+    ("delete", Command::Remove),
+    ("shift+delete", Command::KillTree),
+    ("alt+o", Command::OpenFolder),
+    ("alt+t", Command::OpenTerminal),
+    ("alt+h", Command::ToggleHidden),
+    ("alt+w", Command::Copy),
+    ("alt+up", Command::RaisePriority),
+    ("alt+down", Command::LowerPriority),
+];
+
+const EMACS_BINDINGS: &[(&str, Command)] = &[
+    ("ctrl+n", Command::ListNext),
+    ("ctrl+p", Command::ListPrevious),
+    ("ctrl+v", Command::PageDown),
+    ("alt+v", Command::PageUp),
+    ("ctrl+a", Command::BeginningOfLine),
+    ("ctrl+e", Command::EndOfLine),
+    ("ctrl+b", Command::BackwardChar),
+    ("ctrl+f", Command::ForwardChar),
+    ("alt+b", Command::BackwardWord),
+    ("alt+f", Command::ForwardWord),
+    ("ctrl+d", Command::DeleteChar),
+    ("alt+d", Command::KillWord),
+    ("alt+backspace", Command::BackwardKillWord),
+    ("ctrl+/", Command::Undo),
+    ("ctrl+g", Command::Exit),
+];
+
+// ctrl+k moves up instead of killing the rest of the line.
+const VIM_BINDINGS: &[(&str, Command)] = &[
+    ("ctrl+j", Command::ListNext),
+    ("ctrl+k", Command::ListPrevious),
+    ("ctrl+f", Command::PageDown),
+    ("ctrl+b", Command::PageUp),
+    ("ctrl+w", Command::BackwardKillWord),
+];
+
+pub const PRESETS: &[&str] = &["default", "emacs", "vim"];
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeyChord, Command)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        return Keymap::preset("default").unwrap();
+    }
+}
+
+impl Keymap {
+    pub fn empty() -> Keymap {
+        return Keymap { bindings: vec![] };
+    }
+
+    // The default keymap, with emacs or vim keys added.
+    pub fn preset(name: &str) -> anyhow::Result<Keymap> {
+        let extra = match name {
+            "default" | "" => &[],
+            "emacs" => EMACS_BINDINGS,
+            "vim" => VIM_BINDINGS,
+            _ => anyhow::bail!("Unknown keymap {}, expected one of {}", name, PRESETS.join(", ")),
+        };
+
+        let mut keymap = Keymap::empty();
+        for (chord, command) in DEFAULT_BINDINGS.iter().chain(extra.iter()) {
+            keymap.set(KeyChord::parse(chord).unwrap(), Some(*command));
+        }
+        return Ok(keymap);
+    }
+
+    // The preset in config.json with its keys applied. Bad entries are logged and skipped
+    // so a typo doesn't leave switch without keys.
+    pub fn from_config(config: &crate::config::Config) -> Keymap {
+        let mut keymap = Keymap::preset(&config.keymap).unwrap_or_else(|e| {
+            crate::trace!("config", log::Level::Error, "{:?}", e);
+            Keymap::default()
+        });
+        for (chord, command) in &config.keys {
+            if let Err(e) = keymap.bind(chord, command) {
+                crate::trace!("config", log::Level::Error, "Key {}: {:?}", chord, e);
+            }
+        }
+        return keymap;
+    }
+
+    // Binds the chord to the command name, or unbinds it for "none".
+    pub fn bind(&mut self, chord: &str, command: &str) -> anyhow::Result<()> {
+        let chord = KeyChord::parse(chord)?;
+        if command == UNBIND {
+            self.set(chord, None);
+            return Ok(());
+        }
+        match Command::parse(command) {
+            Some(command) => self.set(chord, Some(command)),
+            None => anyhow::bail!("Unknown command {}", command),
+        }
+        return Ok(());
+    }
+
+    fn set(&mut self, chord: KeyChord, command: Option<Command>) {
+        let chord = chord.normalized();
+        self.bindings.retain(|(bound, _)| *bound != chord);
+        if let Some(command) = command {
+            self.bindings.push((chord, command));
+        }
+    }
+
+    // Shift falls back to the chord without it, so shift+left still moves and capital
+    // letters with ctrl still match.
+    pub fn lookup(&self, key: &KeyEvent) -> Option<Command> {
+        let chord = KeyChord::from_event(key);
+        let find = |chord: KeyChord| self.bindings.iter().find(|(bound, _)| *bound == chord).map(|(_, command)| *command);
+        return find(chord).or_else(|| {
+            if !chord.modifiers.contains(KeyModifiers::SHIFT) {
+                return None;
+            }
+            return find(KeyChord::new(chord.modifiers - KeyModifiers::SHIFT, chord.code));
+        });
+    }
+
+    // Chords bound to the command, in the order they were bound.
+    pub fn chords(&self, command: Command) -> Vec<KeyChord> {
+        return self.bindings.iter().filter(|(_, bound)| *bound == command).map(|(chord, _)| *chord).collect();
+    }
+
+    pub fn bindings(&self) -> &[(KeyChord, Command)] {
+        return &self.bindings;
+    }
+}
@@ -5,10 +5,25 @@ pub use listcontentprovider::ListContentProvider;
 pub use listcontentprovider::ListItem;
 pub use windowprovider::WindowProvider;
 pub use startappsprovider::StartAppsProvider;
+pub use websearchprovider::WebSearchProvider;
+pub use calculatorprovider::CalculatorProvider;
+pub use clipboardhistoryprovider::ClipboardHistoryProvider;
+pub use filesearchprovider::FileSearchProvider;
+pub use symbolprovider::SymbolProvider;
+pub use snippetprovider::SnippetProvider;
+pub use terminalprofileprovider::TerminalProfileProvider;
+pub use sshhostprovider::SshHostProvider;
+pub use gitrepoprovider::GitRepoProvider;
+pub use bookmarkprovider::BookmarkProvider;
+pub use processprovider::ProcessProvider;
 
 pub mod setforegroundwindow;
 pub mod startappsprovider;
 pub mod windowprovider;
+pub mod websearchprovider;
+pub mod calculatorprovider;
+pub mod clipboardhistoryprovider;
+pub mod filesearchprovider;
 pub mod listcontentprovider;
 pub mod waitlist;
 pub mod console;
@@ -17,4 +32,37 @@ pub mod log;
 pub mod com;
 pub mod create_process;
 pub mod path;
-pub mod clipboard;
\ No newline at end of file
+pub mod clipboard;
+pub mod clipboardhistory;
+pub mod cmdline;
+pub mod expand;
+pub mod config;
+pub mod template;
+pub mod calculator;
+pub mod ignore;
+pub mod index;
+pub mod fuzzy;
+pub mod open;
+pub mod directorylisting;
+pub mod symbols;
+pub mod symbolprovider;
+pub mod sendinput;
+pub mod snippets;
+pub mod snippetprovider;
+pub mod terminalprofiles;
+pub mod terminalprofileprovider;
+pub mod sshhosts;
+pub mod sshhostprovider;
+pub mod gitrepos;
+pub mod gitrepoprovider;
+pub mod bookmarks;
+pub mod bookmarkprovider;
+pub mod processes;
+pub mod processprovider;
+pub mod inputline;
+pub mod searchablelistapp;
+pub mod keymap;
+pub mod actionsmenu;
+pub mod theme;
+pub mod queryhistory;
+pub mod help;
\ No newline at end of file
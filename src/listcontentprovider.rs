@@ -1,3 +1,18 @@
+use crate::keymap::Command;
+
+// Things besides start, remove and copy that can be done to the selected item.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    // Show the item's file in explorer.
+    OpenFolder,
+    // Open a terminal in the item's directory.
+    OpenTerminal,
+    // Kill the item's process and everything it started.
+    KillTree,
+    RaisePriority,
+    LowerPriority,
+}
+
 pub trait ListContentProvider {
     // If I add a type here it would become a type parameter, then how do I put them in an vector, if they all have the same type???
     // Vec<Box<dyn ListContentProvider>>
@@ -8,6 +23,113 @@ pub trait ListContentProvider {
     fn set_query(&mut self, filter: String);
     fn start(&mut self, filtered_index: usize, elevated: bool);
     fn remove(&mut self, filtered_index: usize);
+
+    // Starts the marked items, one after the other.
+    fn start_all(&mut self, filtered_indices: &[usize], elevated: bool) {
+        for &filtered_index in filtered_indices {
+            self.start(filtered_index, elevated);
+        }
+    }
+
+    // Removes the marked items, from the last so the indices of the rest still hold. Providers
+    // that reload their list after each remove should override this.
+    fn remove_all(&mut self, filtered_indices: &[usize]) {
+        let mut filtered_indices = filtered_indices.to_vec();
+        filtered_indices.sort_unstable();
+        for &filtered_index in filtered_indices.iter().rev() {
+            self.remove(filtered_index);
+        }
+    }
+
+    // Shown in the status bar, and keeps the query history of each provider apart.
+    fn name(&self) -> &str;
+
+    // Typing this at the start of the query in another provider switches to this one.
+    fn prefix(&self) -> Option<&str> {
+        return None;
+    }
+
+    // Typing a query this returns true for in another provider also switches to this one, without
+    // a prefix, and back again once it returns false. The calculator takes 2 * 3 this way.
+    fn claims_query(&self, _query: &str) -> bool {
+        return false;
+    }
+
+    // Text for the copy action, like the path of a file or a calculator result.
+    fn copy_text(&mut self, _filtered_index: usize) -> Option<String> {
+        return None;
+    }
+
+    // Returns false if the provider doesn't support the action for this item.
+    fn run_action(&mut self, _filtered_index: usize, _action: Action, _elevated: bool) -> bool {
+        return false;
+    }
+
+    // Runs the action on each marked item, true if it was run on any. Providers whose actions
+    // reload the list should override this, the indices don't hold after a reload.
+    fn run_action_all(&mut self, filtered_indices: &[usize], action: Action) -> bool {
+        let mut ran = false;
+        for &filtered_index in filtered_indices {
+            ran |= self.run_action(filtered_index, action, false);
+        }
+        return ran;
+    }
+
+    // Tab completion of the query, None to complete to the selected item's matchable string.
+    fn complete(&mut self, _filtered_index: usize) -> Option<String> {
+        return None;
+    }
+
+    // If this returns a query, enter replaces the query with it instead of starting the item,
+    // like descending into a directory.
+    fn navigate(&mut self, _filtered_index: usize) -> Option<String> {
+        return None;
+    }
+
+    // Backspace at the end of the input replaces the query with this, like going up a directory.
+    // Also asked on backspace in an empty input before leaving a provider switched to by prefix.
+    fn backspace_query(&mut self) -> Option<String> {
+        return None;
+    }
+
+    // Called when another provider is selected, to drop state like a half answered prompt.
+    fn leave(&mut self) {
+    }
+
+    // The row highlighted just before the query changes, for providers that use it in the next
+    // query like start apps does for {selected_dir}.
+    fn highlight(&mut self, _filtered_index: Option<usize>) {
+    }
+
+    fn toggle_hidden(&mut self) {
+    }
+
+    // How many items there are before the query filters them, for the status bar's
+    // matched/total. None when the results are made from the query, like the calculator's.
+    fn total(&self) -> Option<usize> {
+        return None;
+    }
+
+    // What the query is being read as, like apps, directory or url.
+    fn mode(&self) -> Option<&str> {
+        return None;
+    }
+
+    // Query syntax past plain filtering, like a search engine's keyword, as an example and what it
+    // does, for the help.
+    fn qualifiers(&self) -> Vec<(String, String)> {
+        return vec![];
+    }
+
+    // The few most used actions, the status bar shows them with the keys they're bound to.
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "start")];
+    }
+
+    // Everything that can be done to the item, by the command that does it, for the actions menu.
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "start")];
+    }
 }
 
 pub trait ListItem /*where Self: Into<String>*/ /*where Self: std::fmt::Display*/  {
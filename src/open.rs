@@ -0,0 +1,42 @@
+// Opening files, folders and terminals through the shell, shared by providers that list paths.
+
+use crate::startappsprovider::{AppEntry, AppEntryKind};
+
+fn shell_execute(path: &str, params: String, elevated: bool) -> anyhow::Result<()> {
+    let entry = AppEntry {
+        name: path.to_owned(),
+        kind: AppEntryKind::Exe {
+            path: path.to_owned(),
+            params,
+        },
+        ..Default::default()
+    };
+    return entry.launch(elevated);
+}
+
+// Open with the default program, or run it if it's executable.
+pub fn open(path: &str, elevated: bool) -> anyhow::Result<()> {
+    return shell_execute(path, String::new(), elevated);
+}
+
+// Explorer window of the parent directory with path selected.
+pub fn open_folder(path: &str, elevated: bool) -> anyhow::Result<()> {
+    // explorer parses its own command line, the quotes just keep commas in the path together.
+    return shell_execute("explorer.exe", format!("/select,\"{}\"", path.trim_end_matches('\\')), elevated);
+}
+
+// Windows terminal in dir, or in the parent directory if dir is a file.
+pub fn open_terminal(dir: &str, elevated: bool) -> anyhow::Result<()> {
+    let path = std::path::Path::new(dir);
+    let dir = if path.is_file() {
+        path.parent().unwrap_or(path)
+    } else {
+        path
+    };
+    return windows_terminal(format!("-d {}", crate::cmdline::quote_windows(&dir.to_string_lossy())), elevated);
+}
+
+// wt.exe with args, see https://docs.microsoft.com/windows/terminal/command-line-arguments
+pub fn windows_terminal(args: String, elevated: bool) -> anyhow::Result<()> {
+    return shell_execute("wt.exe", args, elevated);
+}
@@ -0,0 +1,437 @@
+// Running processes for the process provider, behind ProcessSource so the provider and the
+// tree can be tested with the /proc backend on Linux or a /proc like directory of fixtures.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Idle,
+    BelowNormal,
+    Normal,
+    AboveNormal,
+    High,
+    Realtime,
+}
+
+const PRIORITIES: [Priority; 6] = [
+    Priority::Idle,
+    Priority::BelowNormal,
+    Priority::Normal,
+    Priority::AboveNormal,
+    Priority::High,
+    Priority::Realtime,
+];
+
+impl Priority {
+    // The next priority up or down, None past the ends.
+    pub fn step(self, up: bool) -> Option<Priority> {
+        let i = PRIORITIES.iter().position(|p| *p == self)?;
+        if up {
+            return PRIORITIES.get(i + 1).copied();
+        }
+        return i.checked_sub(1).map(|i| PRIORITIES[i]);
+    }
+
+    // Nice value the priority is set to on Linux.
+    pub fn nice(self) -> i32 {
+        return match self {
+            Priority::Idle => 19,
+            Priority::BelowNormal => 10,
+            Priority::Normal => 0,
+            Priority::AboveNormal => -5,
+            Priority::High => -10,
+            Priority::Realtime => -20,
+        };
+    }
+
+    pub fn from_nice(nice: i32) -> Priority {
+        return match nice {
+            i32::MIN..=-20 => Priority::Realtime,
+            -19..=-10 => Priority::High,
+            -9..=-5 => Priority::AboveNormal,
+            -4..=4 => Priority::Normal,
+            5..=14 => Priority::BelowNormal,
+            _ => Priority::Idle,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub parent: u32,
+    pub name: String,
+    // Executable path, None if the process can't be opened.
+    pub path: Option<String>,
+    // Resident or working set bytes.
+    pub memory: u64,
+    // User and kernel time used since the process started.
+    pub cpu_time: std::time::Duration,
+    pub priority: Option<Priority>,
+    // When the process started, as a FILETIME on Windows and clock ticks after boot on Linux.
+    // Only compared between processes of one source, None if it can't be read.
+    pub creation_time: Option<u64>,
+}
+
+pub trait ProcessSource {
+    fn processes(&self) -> anyhow::Result<Vec<ProcessInfo>>;
+    fn kill(&self, pid: u32) -> anyhow::Result<()>;
+    fn set_priority(&self, pid: u32, priority: Priority) -> anyhow::Result<()>;
+}
+
+// 12.3 MB
+pub fn format_memory(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        return format!("{} B", bytes);
+    }
+    return format!("{:.1} {}", value, UNITS[unit]);
+}
+
+// 1:02:03 for hours, 2:03 for minutes and 3.45s under a minute.
+pub fn format_cpu_time(time: std::time::Duration) -> String {
+    let seconds = time.as_secs();
+    if seconds >= 3600 {
+        return format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60);
+    }
+    if seconds >= 60 {
+        return format!("{}:{:02}", seconds / 60, seconds % 60);
+    }
+    return format!("{:.2}s", time.as_secs_f64());
+}
+
+// Processes depth first from the roots, each with its depth. A process is a root if its
+// parent is pid 0, isn't running or is a later process with the parent's pid, pid 0 is the
+// idle process on Windows and the kernel's on Linux. Processes with parents that loop from
+// reused pids on Windows are made roots too.
+pub fn tree(processes: &[ProcessInfo]) -> Vec<(usize, &ProcessInfo)> {
+    let by_pid = processes.iter().map(|p| (p.pid, p)).collect::<std::collections::HashMap<u32, &ProcessInfo>>();
+    let is_root = |p: &ProcessInfo| {
+        return p.parent == 0 || p.parent == p.pid || !by_pid.get(&p.parent).map(|&parent| started_under(p, Some(parent))).unwrap_or(false);
+    };
+
+    let mut children: std::collections::HashMap<u32, Vec<&ProcessInfo>> = std::collections::HashMap::new();
+    for process in processes.iter().filter(|p| !is_root(p)) {
+        children.entry(process.parent).or_default().push(process);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|p| p.pid);
+    }
+
+    let mut roots = processes.iter()
+        .filter(|p| is_root(p))
+        .collect::<Vec<&ProcessInfo>>();
+    roots.sort_by_key(|p| p.pid);
+
+    let mut visited = std::collections::HashSet::new();
+    let mut ordered = vec![];
+    let mut stack = roots.into_iter().rev().map(|p| (0, p)).collect::<Vec<(usize, &ProcessInfo)>>();
+    loop {
+        while let Some((depth, process)) = stack.pop() {
+            if !visited.insert(process.pid) {
+                continue;
+            }
+            ordered.push((depth, process));
+            if process.pid == 0 {
+                continue;
+            }
+            for child in children.get(&process.pid).into_iter().flatten().rev() {
+                stack.push((depth + 1, child));
+            }
+        }
+
+        // Processes whose parents form a loop are never reached from a root.
+        match processes.iter().filter(|p| !visited.contains(&p.pid)).min_by_key(|p| p.pid) {
+            Some(process) => stack.push((0, process)),
+            None => break,
+        }
+    }
+    return ordered;
+}
+
+// Windows keeps the parent pid of a process after the parent exits, and the pid can be given
+// to a new process. A child that started before its parent was started by an earlier process
+// with the same pid. Processes started in the same clock tick as their parent are children.
+fn started_under(child: &ProcessInfo, parent: Option<&ProcessInfo>) -> bool {
+    return match (child.creation_time, parent.and_then(|p| p.creation_time)) {
+        (Some(child), Some(parent)) => child >= parent,
+        _ => true,
+    };
+}
+
+// pid and everything started under it, parents before children so nothing is restarted
+// by a parent while its children are killed. Like in the tree nothing is under pid 0.
+pub fn descendants(processes: &[ProcessInfo], pid: u32) -> Vec<u32> {
+    let mut pids = vec![pid];
+    let mut i = 0;
+    while i < pids.len() {
+        let parent = pids[i];
+        let parent_info = processes.iter().find(|p| p.pid == parent);
+        let mut children = processes.iter()
+            .filter(|p| parent != 0 && p.parent == parent && !pids.contains(&p.pid))
+            .filter(|p| started_under(p, parent_info))
+            .map(|p| p.pid)
+            .collect::<Vec<u32>>();
+        children.sort();
+        pids.extend(children);
+        i += 1;
+    }
+    return pids;
+}
+
+// Kills pid and its descendants, continuing past processes that can't be killed.
+pub fn kill_tree(source: &dyn ProcessSource, pid: u32) -> anyhow::Result<()> {
+    let processes = source.processes()?;
+    let mut result = Ok(());
+    for pid in descendants(&processes, pid) {
+        if let Err(e) = source.kill(pid) {
+            result = Err(e);
+        }
+    }
+    return result;
+}
+
+// Reads processes from a /proc directory, kill and renice run the commands of the same name.
+pub struct ProcFs {
+    pub root: std::path::PathBuf,
+}
+
+// USER_HZ, the unit of the times in /proc/<pid>/stat, which is 100 on every architecture Linux
+// runs on.
+const CLOCK_TICKS: u64 = 100;
+
+impl ProcFs {
+    pub fn new() -> Self {
+        return ProcFs { root: "/proc".into() };
+    }
+
+    // Fields of /proc/<pid>/stat, the name is in parentheses and can have spaces and
+    // parentheses of its own so the fields after it are found from the last ')'.
+    pub fn parse_stat(pid: u32, stat: &str) -> Option<ProcessInfo> {
+        let open = stat.find('(')?;
+        let close = stat.rfind(')')?;
+        let name = stat.get(open + 1..close)?.to_owned();
+        let fields = stat[close + 1..].split_whitespace().collect::<Vec<&str>>();
+
+        // Numbered from state, the first field after the name.
+        let field = |i: usize| fields.get(i).and_then(|f| f.parse::<i64>().ok());
+        let ticks = (field(11)? + field(12)?) as u64;
+        return Some(ProcessInfo {
+            pid,
+            parent: field(1)? as u32,
+            name,
+            path: None,
+            memory: 0,
+            cpu_time: std::time::Duration::from_millis(ticks * 1000 / CLOCK_TICKS),
+            priority: field(16).map(|nice| Priority::from_nice(nice as i32)),
+            creation_time: field(19).map(|ticks| ticks as u64),
+        });
+    }
+
+    // VmRSS from /proc/<pid>/status, kernel threads don't have it.
+    pub fn parse_rss(status: &str) -> Option<u64> {
+        let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+        let mut words = line["VmRSS:".len()..].split_whitespace();
+        let kb = words.next()?.parse::<u64>().ok()?;
+        return Some(kb * 1024);
+    }
+
+    fn read_process(&self, pid: u32) -> Option<ProcessInfo> {
+        let dir = self.root.join(pid.to_string());
+        let mut process = Self::parse_stat(pid, &std::fs::read_to_string(dir.join("stat")).ok()?)?;
+        process.memory = std::fs::read_to_string(dir.join("status")).ok()
+            .and_then(|status| Self::parse_rss(&status))
+            .unwrap_or(0);
+        process.path = std::fs::read_link(dir.join("exe")).ok().map(|path| path.to_string_lossy().into_owned());
+        return Some(process);
+    }
+
+    fn run(command: &mut std::process::Command) -> anyhow::Result<()> {
+        let output = command.output()?;
+        if !output.status.success() {
+            anyhow::bail!("{:?} failed: {}", command, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        return Ok(());
+    }
+}
+
+impl ProcessSource for ProcFs {
+    fn processes(&self) -> anyhow::Result<Vec<ProcessInfo>> {
+        let mut processes = std::fs::read_dir(&self.root)?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.file_name().to_str().and_then(|name| name.parse::<u32>().ok()))
+            // Gone between listing the directory and reading it.
+            .filter_map(|pid| self.read_process(pid))
+            .collect::<Vec<ProcessInfo>>();
+        processes.sort_by_key(|p| p.pid);
+        return Ok(processes);
+    }
+
+    fn kill(&self, pid: u32) -> anyhow::Result<()> {
+        return Self::run(std::process::Command::new("kill").arg("-KILL").arg(pid.to_string()));
+    }
+
+    fn set_priority(&self, pid: u32, priority: Priority) -> anyhow::Result<()> {
+        return Self::run(std::process::Command::new("renice")
+            .arg("-n").arg(priority.nice().to_string())
+            .arg("-p").arg(pid.to_string()));
+    }
+}
+
+#[cfg(windows)]
+pub use self::toolhelp::*;
+
+#[cfg(windows)]
+mod toolhelp {
+    use windows::{
+        core::PWSTR,
+        Win32::Foundation::*,
+        Win32::System::Diagnostics::ToolHelp::{
+            CreateToolhelp32Snapshot,
+            Process32FirstW,
+            Process32NextW,
+            TH32CS_SNAPPROCESS,
+            PROCESSENTRY32W,
+        },
+        Win32::System::ProcessStatus::{K32GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS},
+        Win32::System::Threading::*,
+    };
+
+    use super::{Priority, ProcessInfo, ProcessSource};
+
+    const PRIORITY_CLASSES: [(Priority, PROCESS_CREATION_FLAGS); 6] = [
+        (Priority::Idle, IDLE_PRIORITY_CLASS),
+        (Priority::BelowNormal, BELOW_NORMAL_PRIORITY_CLASS),
+        (Priority::Normal, NORMAL_PRIORITY_CLASS),
+        (Priority::AboveNormal, ABOVE_NORMAL_PRIORITY_CLASS),
+        (Priority::High, HIGH_PRIORITY_CLASS),
+        (Priority::Realtime, REALTIME_PRIORITY_CLASS),
+    ];
+
+    // Processes from a Toolhelp snapshot.
+    pub struct Toolhelp;
+
+    // pid, parent and name of every process from one snapshot.
+    pub fn snapshot() -> anyhow::Result<Vec<ProcessInfo>> {
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                return Err(windows::core::Error::from_win32().into());
+            }
+
+            let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+            entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+            let mut processes = vec![];
+            let mut more = Process32FirstW(snapshot, &mut entry).as_bool();
+            while more {
+                let len = entry.szExeFile.iter().position(|c| *c == 0).unwrap_or(entry.szExeFile.len());
+                processes.push(ProcessInfo {
+                    pid: entry.th32ProcessID,
+                    parent: entry.th32ParentProcessID,
+                    name: String::from_utf16_lossy(&entry.szExeFile[..len]),
+                    ..Default::default()
+                });
+                more = Process32NextW(snapshot, &mut entry).as_bool();
+            }
+            CloseHandle(snapshot);
+            return Ok(processes);
+        }
+    }
+
+    // 100ns intervals.
+    fn filetime_ticks(time: &FILETIME) -> u64 {
+        return ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+    }
+
+    fn filetime_duration(time: &FILETIME) -> std::time::Duration {
+        return std::time::Duration::from_nanos(filetime_ticks(time) * 100);
+    }
+
+    // Path, memory, cpu time, creation time and priority, left empty for processes that can't be opened
+    // like system processes when not elevated.
+    unsafe fn read_details(process: &mut ProcessInfo) {
+        let processh = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, BOOL(0), process.pid);
+        if processh.is_invalid() {
+            return;
+        }
+
+        let mut path = [0u16; 1024];
+        let mut len = path.len() as u32;
+        if QueryFullProcessImageNameW(processh, PROCESS_NAME_WIN32, PWSTR(path.as_mut_ptr()), &mut len).as_bool() {
+            process.path = Some(String::from_utf16_lossy(&path[..len as usize]));
+        }
+
+        let mut counters: PROCESS_MEMORY_COUNTERS = std::mem::zeroed();
+        counters.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        if K32GetProcessMemoryInfo(processh, &mut counters, counters.cb).as_bool() {
+            process.memory = counters.WorkingSetSize as u64;
+        }
+
+        let mut creation = FILETIME::default();
+        let mut exit = FILETIME::default();
+        let mut kernel = FILETIME::default();
+        let mut user = FILETIME::default();
+        if GetProcessTimes(processh, &mut creation, &mut exit, &mut kernel, &mut user).as_bool() {
+            process.cpu_time = filetime_duration(&kernel) + filetime_duration(&user);
+            process.creation_time = Some(filetime_ticks(&creation));
+        }
+
+        let class = GetPriorityClass(processh);
+        process.priority = PRIORITY_CLASSES.iter().find(|(_, c)| c.0 == class).map(|(p, _)| *p);
+
+        CloseHandle(processh);
+    }
+
+    fn open(pid: u32, access: PROCESS_ACCESS_RIGHTS) -> anyhow::Result<HANDLE> {
+        let processh = unsafe { OpenProcess(access, BOOL(0), pid) };
+        if processh.is_invalid() {
+            return Err(windows::core::Error::from_win32().into());
+        }
+        return Ok(processh);
+    }
+
+    impl ProcessSource for Toolhelp {
+        fn processes(&self) -> anyhow::Result<Vec<ProcessInfo>> {
+            let mut processes = snapshot()?;
+            for process in processes.iter_mut() {
+                unsafe { read_details(process) };
+            }
+            return Ok(processes);
+        }
+
+        fn kill(&self, pid: u32) -> anyhow::Result<()> {
+            let processh = open(pid, PROCESS_TERMINATE)?;
+            let killed = unsafe { TerminateProcess(processh, 1).as_bool() };
+            let error = windows::core::Error::from_win32();
+            unsafe { CloseHandle(processh) };
+            if !killed {
+                return Err(error.into());
+            }
+            return Ok(());
+        }
+
+        fn set_priority(&self, pid: u32, priority: Priority) -> anyhow::Result<()> {
+            let class = PRIORITY_CLASSES.iter().find(|(p, _)| *p == priority).map(|(_, c)| *c).unwrap();
+            let processh = open(pid, PROCESS_SET_INFORMATION)?;
+            let set = unsafe { SetPriorityClass(processh, class).as_bool() };
+            let error = windows::core::Error::from_win32();
+            unsafe { CloseHandle(processh) };
+            if !set {
+                return Err(error.into());
+            }
+            return Ok(());
+        }
+    }
+}
+
+// The backend for the platform switch is running on.
+pub fn system() -> Box<dyn ProcessSource> {
+    #[cfg(windows)]
+    return Box::new(Toolhelp);
+    #[cfg(not(windows))]
+    return Box::new(ProcFs::new());
+}
@@ -0,0 +1,299 @@
+use crate::listcontentprovider::{Action, ListContentProvider, ListItem};
+use crate::keymap::Command;
+use crate::processes::{ProcessInfo, ProcessSource};
+
+use crate::log::*;
+
+pub struct ProcessEntry {
+    pub process: ProcessInfo,
+    // Indent in the tree, 0 when searching.
+    pub depth: usize,
+}
+
+impl ListItem for ProcessEntry {
+    fn as_any(&self) -> &dyn std::any::Any {
+        return self;
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        return self;
+    }
+
+    // name  pid  parent pid  memory  cpu time  priority
+    fn as_string(&self) -> String {
+        let process = &self.process;
+        let mut text = format!("{}{}  {}  {}  {}  {}",
+            "  ".repeat(self.depth),
+            process.name,
+            process.pid,
+            process.parent,
+            crate::processes::format_memory(process.memory),
+            crate::processes::format_cpu_time(process.cpu_time));
+        if let Some(priority) = process.priority {
+            text += &format!("  {:?}", priority);
+        }
+        return text;
+    }
+
+    fn as_matchable_string(&self) -> String {
+        return self.process.name.clone();
+    }
+}
+
+// Every running process, as a tree of parents and children on empty input and a list of
+// matches by name or pid when searching. Enter switches to the process's window, delete
+// kills it, shift+delete kills it and its children and alt+up and alt+down change its
+// priority. Alt+o shows the executable in explorer and alt+w copies the pid.
+pub struct ProcessProvider {
+    source: Box<dyn ProcessSource>,
+    processes: Vec<ProcessInfo>,
+    query: String,
+    filtered: Vec<ProcessEntry>,
+}
+
+impl ProcessProvider {
+    pub fn new() -> Box<Self> {
+        return Self::with_source(crate::processes::system());
+    }
+
+    pub fn with_source(source: Box<dyn ProcessSource>) -> Box<Self> {
+        let mut provider = Box::new(ProcessProvider {
+            source,
+            processes: vec![],
+            query: String::new(),
+            filtered: vec![],
+        });
+        provider.refresh();
+        return provider;
+    }
+
+    pub fn refresh(&mut self) {
+        self.processes = match self.source.processes() {
+            Ok(processes) => processes,
+            Err(e) => {
+                crate::trace!("process", log::Level::Error, "Failed to list processes: {:?}", e);
+                vec![]
+            },
+        };
+        self.filter();
+    }
+
+    fn filter(&mut self) {
+        let query = self.query.trim();
+        if query.len() == 0 {
+            self.filtered = crate::processes::tree(&self.processes).into_iter()
+                .map(|(depth, process)| ProcessEntry { process: process.clone(), depth })
+                .collect();
+            return;
+        }
+
+        let is_pid = query.chars().all(|c| c.is_ascii_digit());
+        let mut scored = self.processes.iter()
+            .filter_map(|process| {
+                if is_pid {
+                    return process.pid.to_string().starts_with(query).then(|| (0, process));
+                }
+                return crate::fuzzy::score(query, &process.name).map(|score| (score, process));
+            })
+            .collect::<Vec<(i32, &ProcessInfo)>>();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.pid.cmp(&b.1.pid)));
+
+        self.filtered = scored.into_iter()
+            .map(|(_, process)| ProcessEntry { process: process.clone(), depth: 0 })
+            .collect();
+    }
+
+    fn selected_pid(&self, filtered_index: usize) -> Option<u32> {
+        return self.filtered.get(filtered_index).map(|e| e.process.pid);
+    }
+
+    // Moves the process's priority one step, logging what can't be changed like system
+    // processes or realtime without the privilege for it.
+    fn step_priority(&mut self, filtered_index: usize, up: bool) -> bool {
+        let process = match self.filtered.get(filtered_index) {
+            Some(entry) => &entry.process,
+            None => return false,
+        };
+        let priority = match process.priority.and_then(|priority| priority.step(up)) {
+            Some(priority) => priority,
+            None => return true,
+        };
+
+        crate::trace!("process", log::Level::Info, "Set priority of {} to {:?}", process.pid, priority);
+        if let Err(e) = self.source.set_priority(process.pid, priority) {
+            crate::trace!("process", log::Level::Error, "Set priority error: {:?}", e);
+        }
+        self.refresh();
+        return true;
+    }
+}
+
+#[cfg(windows)]
+fn activate_window(pid: u32) -> bool {
+    let windows = crate::windowprovider::enum_window().unwrap_or_default();
+    let window = match windows.iter().find(|w| w.process_id == pid) {
+        Some(window) => window,
+        None => return false,
+    };
+    return crate::setforegroundwindow::set_foreground_window_terminal(window.windowh).is_ok();
+}
+
+#[cfg(not(windows))]
+fn activate_window(_pid: u32) -> bool {
+    return false;
+}
+
+impl ListContentProvider for ProcessProvider {
+    fn name(&self) -> &str {
+        return "Processes";
+    }
+
+    fn qualifiers(&self) -> Vec<(String, String)> {
+        return vec![("4312".to_string(), "a process by pid".to_string())];
+    }
+
+    fn total(&self) -> Option<usize> {
+        return Some(self.processes.len());
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "switch"), (Command::Remove, "kill"), (Command::KillTree, "kill tree"), (Command::Copy, "copy pid")];
+    }
+
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        return self.filtered.iter_mut().map(|e| e as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        return self.filtered.iter().map(|e| e.as_string()).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.filter();
+    }
+
+    fn start(&mut self, filtered_index: usize, _elevated: bool) {
+        let pid = match self.selected_pid(filtered_index) {
+            Some(pid) => pid,
+            None => return,
+        };
+
+        crate::trace!("start", log::Level::Info, "Activate window of process {}", pid);
+        if !activate_window(pid) {
+            crate::trace!("start", log::Level::Info, "Process {} has no window", pid);
+        }
+    }
+
+    fn remove(&mut self, filtered_index: usize) {
+        let pid = match self.selected_pid(filtered_index) {
+            Some(pid) => pid,
+            None => return,
+        };
+
+        crate::trace!("process", log::Level::Info, "Kill {}", pid);
+        if let Err(e) = self.source.kill(pid) {
+            crate::trace!("process", log::Level::Error, "Kill error: {:?}", e);
+        }
+        self.refresh();
+    }
+
+    // Kills them all before refreshing, the indices don't hold after a refresh.
+    fn remove_all(&mut self, filtered_indices: &[usize]) {
+        let pids = filtered_indices.iter().filter_map(|&i| self.selected_pid(i)).collect::<Vec<u32>>();
+        for pid in pids {
+            crate::trace!("process", log::Level::Info, "Kill {}", pid);
+            if let Err(e) = self.source.kill(pid) {
+                crate::trace!("process", log::Level::Error, "Kill error: {:?}", e);
+            }
+        }
+        self.refresh();
+    }
+
+    // Kill tree and priority changes read the processes again, so the processes are found
+    // before any of them is changed.
+    fn run_action_all(&mut self, filtered_indices: &[usize], action: Action) -> bool {
+        let processes = filtered_indices.iter()
+            .filter_map(|&i| self.filtered.get(i).map(|entry| entry.process.clone()))
+            .collect::<Vec<ProcessInfo>>();
+        match action {
+            Action::KillTree => {
+                for process in &processes {
+                    crate::trace!("process", log::Level::Info, "Kill tree {}", process.pid);
+                    if let Err(e) = crate::processes::kill_tree(self.source.as_ref(), process.pid) {
+                        crate::trace!("process", log::Level::Error, "Kill tree error: {:?}", e);
+                    }
+                }
+            },
+            Action::RaisePriority | Action::LowerPriority => {
+                for process in &processes {
+                    let priority = match process.priority.and_then(|priority| priority.step(action == Action::RaisePriority)) {
+                        Some(priority) => priority,
+                        None => continue,
+                    };
+                    crate::trace!("process", log::Level::Info, "Set priority of {} to {:?}", process.pid, priority);
+                    if let Err(e) = self.source.set_priority(process.pid, priority) {
+                        crate::trace!("process", log::Level::Error, "Set priority error: {:?}", e);
+                    }
+                }
+            },
+            Action::OpenFolder | Action::OpenTerminal => {
+                let mut ran = false;
+                for &filtered_index in filtered_indices {
+                    ran |= self.run_action(filtered_index, action, false);
+                }
+                return ran;
+            },
+        }
+        self.refresh();
+        return processes.len() > 0;
+    }
+
+    fn run_action(&mut self, filtered_index: usize, action: Action, elevated: bool) -> bool {
+        let process = match self.filtered.get(filtered_index) {
+            Some(entry) => &entry.process,
+            None => return false,
+        };
+
+        match action {
+            Action::KillTree => {
+                crate::trace!("process", log::Level::Info, "Kill tree {}", process.pid);
+                if let Err(e) = crate::processes::kill_tree(self.source.as_ref(), process.pid) {
+                    crate::trace!("process", log::Level::Error, "Kill tree error: {:?}", e);
+                }
+                self.refresh();
+                return true;
+            },
+            Action::RaisePriority => return self.step_priority(filtered_index, true),
+            Action::LowerPriority => return self.step_priority(filtered_index, false),
+            Action::OpenFolder | Action::OpenTerminal => {
+                let path = match &process.path {
+                    Some(path) => path,
+                    None => return false,
+                };
+                let result = if action == Action::OpenFolder {
+                    crate::open::open_folder(path, elevated)
+                } else {
+                    crate::open::open_terminal(path, elevated)
+                };
+                if let Err(e) = result {
+                    crate::trace!("start", log::Level::Error, "{:?} error: {:?}", action, e);
+                }
+                return true;
+            },
+        }
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.selected_pid(filtered_index).map(|pid| pid.to_string());
+    }
+
+    // Not $, commands like $HOME\bin\tool.exe start with it.
+    fn prefix(&self) -> Option<&str> {
+        return Some("&");
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "switch to"), (Command::Remove, "kill"), (Command::KillTree, "kill tree"), (Command::RaisePriority, "raise priority"), (Command::LowerPriority, "lower priority"), (Command::OpenFolder, "open folder"), (Command::OpenTerminal, "open terminal"), (Command::Copy, "copy pid")];
+    }
+}
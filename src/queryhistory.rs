@@ -0,0 +1,102 @@
+// Queries entered in each provider, newest first, persisted as a json object of provider
+// name to queries in %APPDATA%\switch\query_history.json.
+
+pub const DEFAULT_MAX_ENTRIES: usize = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryHistory {
+    entries: std::collections::BTreeMap<String, Vec<String>>,
+    // Per provider.
+    max_entries: usize,
+}
+
+impl QueryHistory {
+    pub fn new(max_entries: usize) -> Self {
+        return QueryHistory {
+            entries: std::collections::BTreeMap::new(),
+            max_entries,
+        };
+    }
+
+    pub fn entries(&self, provider: &str) -> &[String] {
+        return self.entries.get(provider).map(|entries| entries.as_slice()).unwrap_or(&[]);
+    }
+
+    // Puts query at the front of the provider's queries, removing an earlier copy of it.
+    // Returns false if query is blank and wasn't recorded.
+    pub fn record(&mut self, provider: &str, query: &str) -> bool {
+        if query.trim().len() == 0 || self.max_entries == 0 {
+            return false;
+        }
+
+        let entries = self.entries.entry(provider.to_owned()).or_default();
+        entries.retain(|e| e != query);
+        entries.insert(0, query.to_owned());
+        entries.truncate(self.max_entries);
+        return true;
+    }
+
+    // Index of the first query from start on containing text, ignoring case.
+    pub fn search(&self, provider: &str, text: &str, start: usize) -> Option<usize> {
+        let text = text.to_lowercase();
+        return self.entries(provider).iter()
+            .enumerate()
+            .skip(start)
+            .find(|(_, query)| query.to_lowercase().contains(&text))
+            .map(|(i, _)| i);
+    }
+
+    pub fn parse(json: &str, max_entries: usize) -> anyhow::Result<Self> {
+        let mut entries: std::collections::BTreeMap<String, Vec<String>> = serde_json::from_str(json)?;
+        for queries in entries.values_mut() {
+            let mut seen = std::collections::HashSet::new();
+            queries.retain(|query| query.trim().len() > 0 && seen.insert(query.clone()));
+            queries.truncate(max_entries);
+        }
+        return Ok(QueryHistory { entries, max_entries });
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        return Ok(serde_json::to_string(&self.entries)?);
+    }
+
+    pub fn load_from<IntoPath: Into<std::path::PathBuf>>(path: IntoPath, max_entries: usize) -> anyhow::Result<Self> {
+        let path = path.into();
+        if !path.exists() {
+            return Ok(Self::new(max_entries));
+        }
+
+        return Self::parse(&std::fs::read_to_string(&path)?, max_entries);
+    }
+
+    pub fn save_to<IntoPath: Into<std::path::PathBuf>>(&self, path: IntoPath) -> anyhow::Result<()> {
+        std::fs::write(path.into(), self.to_json()?)?;
+        return Ok(());
+    }
+
+    pub fn path() -> anyhow::Result<String> {
+        return crate::path::get_app_data_path("query_history.json");
+    }
+}
+
+// Ctrl+R search through the current provider's queries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistorySearch {
+    pub text: String,
+    // Index of the query shown, None before anything matched.
+    pub index: Option<usize>,
+    // False when nothing matches text, the last match stays.
+    pub found: bool,
+    // The input to go back to when the search is cancelled.
+    pub original: String,
+}
+
+impl HistorySearch {
+    pub fn new(original: String) -> Self {
+        return HistorySearch { text: String::new(), index: None, found: true, original };
+    }
+
+    pub fn prompt(&self) -> String {
+        return format!("({}history '{}') ", if self.found { "" } else { "failed " }, self.text);
+    }
+}
@@ -0,0 +1,1114 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
+};
+
+use crate::actionsmenu::ActionsMenu;
+use crate::clipboard::{Clipboard, SystemClipboard};
+use crate::help::Help;
+use crate::inputline::InputLine;
+use crate::keymap::{Command, Keymap};
+use crate::listcontentprovider::{Action, ListContentProvider};
+use crate::queryhistory::{HistorySearch, QueryHistory};
+use crate::theme::Theme;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::log::*;
+
+// The switch UI: an input line over the list of the selected provider. Events come from an
+// EventSource so run_app can be driven by a script against tui's TestBackend in tests.
+
+/// This struct holds the current state of the app. In particular, it has the `items` field which is a wrapper
+/// around `ListState`. Keeping track of the items state let us render the associated widget with its state
+/// and have access to features such as natural scrolling.
+///
+/// Check the event handling at the bottom to see how to change the state on incoming events.
+/// Check the drawing logic for items on how to specify the highlighting style for selected items.
+pub struct SearchableListApp {
+    pub input_line: InputLine,
+    pub list_state: ListState,
+    pub providers: Vec<Box<dyn ListContentProvider>>,
+    pub selected_provider: usize,
+    // Provider we came from when a prefix switched providers, backspace on empty input goes back.
+    pub previous_provider: Option<usize>,
+    // The current provider claimed the query without a prefix, see claims_query, and goes back
+    // to previous_provider when it doesn't claim it anymore.
+    pub claimed_query: bool,
+    pub screen_width: u16,
+    pub screen_height: u16,
+    // Copy and paste, a MemoryClipboard in tests.
+    pub clipboard: Box<dyn Clipboard>,
+    // Resizing a real console leaves scrollback that has to be cleared, see main.
+    pub clear_console_on_resize: bool,
+    // What each key does, see keymap.rs.
+    pub keymap: Keymap,
+    pub theme: Theme,
+    // Queries entered before in each provider, saved to history_path when it's set.
+    pub history: QueryHistory,
+    pub history_path: Option<std::path::PathBuf>,
+    // Which of the current provider's queries up recalled.
+    pub history_index: Option<usize>,
+    pub history_search: Option<HistorySearch>,
+    // First row of the list on screen, kept here since tui's ListState doesn't share it. Where
+    // the list was last drawn, for clicks.
+    pub list_offset: usize,
+    pub list_area: Rect,
+    // When and which row was last clicked, a second click on it soon after activates it.
+    last_click: Option<(Instant, usize)>,
+    // Rows marked to start, remove or copy together, cleared when the query changes.
+    pub marked: std::collections::BTreeSet<usize>,
+    // Moved through with keys or the mouse since the query last changed, space marks rows
+    // instead of typing then.
+    pub list_focused: bool,
+    // Shown over everything while it's open.
+    pub help: Option<Help>,
+    // Shown over the list while it's open, and where it was drawn for clicks.
+    pub actions: Option<ActionsMenu>,
+    pub actions_area: Rect,
+}
+
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(500);
+const WHEEL_ROWS: usize = 3;
+
+impl<'a> SearchableListApp {
+    pub fn new(providers: Vec<Box<dyn ListContentProvider>>, screen_width: u16, screen_height: u16) -> SearchableListApp {
+        SearchableListApp {
+            input_line: InputLine::default(),
+            list_state: ListState::default(),
+            providers,
+            selected_provider: 0,
+            previous_provider: None,
+            claimed_query: false,
+            screen_width,
+            screen_height,
+            clipboard: Box::new(SystemClipboard),
+            clear_console_on_resize: false,
+            keymap: Keymap::default(),
+            theme: Theme::default(),
+            history: QueryHistory::new(crate::queryhistory::DEFAULT_MAX_ENTRIES),
+            history_path: None,
+            history_index: None,
+            history_search: None,
+            list_offset: 0,
+            list_area: Rect::default(),
+            last_click: None,
+            marked: std::collections::BTreeSet::new(),
+            list_focused: false,
+            help: None,
+            actions: None,
+            actions_area: Rect::default(),
+        }
+    }
+
+    pub fn current_provider(&self) -> &dyn ListContentProvider {
+        assert!(self.selected_provider < self.providers.len());
+        return self.providers[self.selected_provider].as_ref()
+    }
+
+    pub fn current_provider_mut(&mut self) -> &mut dyn ListContentProvider {
+        assert!(self.selected_provider < self.providers.len());
+        return self.providers[self.selected_provider].as_mut()
+    }
+
+    pub fn next_provider(&mut self) {
+        let next = if self.selected_provider >= self.providers.len() - 1 {
+            0
+        } else {
+            self.selected_provider + 1
+        };
+        self.select_provider(next);
+    }
+
+    // Switches to a provider with an empty query, like clicking its tab.
+    pub fn select_provider(&mut self, index: usize) {
+        if index >= self.providers.len() {
+            return;
+        }
+        self.list_state = ListState::default();
+        self.input_line.clear();
+        self.set_query((&self.input_line).into());
+        self.current_provider_mut().leave();
+        self.selected_provider = index;
+        self.previous_provider = None;
+        self.claimed_query = false;
+        self.history_index = None;
+    }
+
+    // If filter starts with another provider's prefix, switch to that provider and
+    // remove the prefix from the input line. Returns the query for the current provider.
+    pub fn route_prefix(&mut self, filter: String) -> String {
+        if self.claimed_query && !self.current_provider().claims_query(&filter) {
+            self.leave_routed_provider_keeping_query();
+        }
+
+        let routed = self.providers.iter().enumerate()
+            .filter(|&(i, _)| i != self.selected_provider)
+            .find_map(|(i, p)| p.prefix().filter(|prefix| filter.starts_with(*prefix)).map(|prefix| (i, prefix.len())));
+
+        let (index, prefix_len) = match routed {
+            Some(routed) => routed,
+            None => {
+                if self.previous_provider.is_none() {
+                    self.route_claimed_query(&filter);
+                }
+                return filter;
+            },
+        };
+
+        let pos = self.input_line.cursor_pos().saturating_sub(filter[..prefix_len].graphemes(true).count());
+        let filter = filter[prefix_len..].to_owned();
+        self.input_line.reset_buffer(&filter);
+        self.input_line.set_cursor_pos(pos);
+
+        self.current_provider_mut().leave();
+        self.previous_provider = Some(self.selected_provider);
+        self.claimed_query = false;
+        self.selected_provider = index;
+        self.list_state = ListState::default();
+        return filter;
+    }
+
+    // Switch to another provider that claims the whole query, like the calculator does for 2 * 3.
+    fn route_claimed_query(&mut self, filter: &str) {
+        let claimed = self.providers.iter().enumerate()
+            .filter(|&(i, _)| i != self.selected_provider)
+            .find(|(_, p)| p.claims_query(filter))
+            .map(|(i, _)| i);
+
+        if let Some(index) = claimed {
+            self.current_provider_mut().leave();
+            self.previous_provider = Some(self.selected_provider);
+            self.claimed_query = true;
+            self.selected_provider = index;
+            self.list_state = ListState::default();
+        }
+    }
+
+    fn leave_routed_provider_keeping_query(&mut self) {
+        if let Some(previous) = self.previous_provider.take() {
+            self.current_provider_mut().leave();
+            self.list_state = ListState::default();
+            self.selected_provider = previous;
+        }
+        self.claimed_query = false;
+    }
+
+    // Go back to the provider we were in before a prefix switched providers.
+    pub fn leave_routed_provider(&mut self) -> bool {
+        if self.previous_provider.is_none() {
+            return false;
+        }
+
+        self.leave_routed_provider_keeping_query();
+        self.set_query((&self.input_line).into());
+        return true;
+    }
+
+    pub fn set_query(&mut self, filter: String) {
+        self.clear_marks();
+        let selected = self.list_state.selected();
+        self.current_provider_mut().highlight(selected);
+        let filter = self.route_prefix(filter);
+        self.current_provider_mut().set_query(filter);
+    }
+
+    pub fn list_next(&mut self) {
+        let list_len = self.current_provider_mut().query_for_items().len();
+        if list_len == 0 {
+            return;
+        }
+
+        let i = match self.list_state.selected() {
+            Some(i) => {
+                if i >= list_len - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn list_previous(&mut self) {
+        let list_len = self.current_provider_mut().query_for_items().len();
+        if list_len == 0 {
+            return;
+        }
+
+        let i = match self.list_state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    list_len - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    // Rows of the list on screen.
+    pub fn page_size(&self) -> usize {
+        let (_, _, list) = self.theme.layout(Rect::new(0, 0, self.screen_width, self.screen_height));
+        return std::cmp::max(list.height as usize, 1);
+    }
+
+    pub fn list_page_next(&mut self) {
+        let list_len = self.current_provider_mut().query_for_items().len();
+        if list_len == 0 {
+            return;
+        }
+
+        let i = match self.list_state.selected() {
+            Some(i) => {
+                if i + self.page_size() >= list_len {
+                    0
+                } else {
+                    i + self.page_size()
+                }
+            }
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn list_page_prev(&mut self) {
+        let list_len = self.current_provider_mut().query_for_items().len();
+        if list_len == 0 {
+            return;
+        }
+
+        let i = match self.list_state.selected() {
+            Some(i) => {
+                if i < self.page_size() {
+                    list_len - 1
+                } else {
+                    i - self.page_size()
+                }
+            }
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    // Scrolls as little as tui would to keep the selected row on screen, the first row when
+    // nothing is selected.
+    fn scroll_to_selected(&mut self, list_len: usize) {
+        let height = self.list_area.height as usize;
+        let selected = match self.list_state.selected() {
+            Some(selected) => selected,
+            None => {
+                self.list_offset = 0;
+                return;
+            }
+        };
+
+        let mut offset = std::cmp::min(self.list_offset, list_len.saturating_sub(1));
+        if height > 0 && selected >= offset + height {
+            offset = selected + 1 - height;
+        }
+        if selected < offset {
+            offset = selected;
+        }
+        self.list_offset = offset;
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.list_focused = false;
+    }
+
+    pub fn toggle_mark(&mut self, index: usize) {
+        if index >= self.current_provider_mut().query_for_items().len() {
+            return;
+        }
+        if !self.marked.remove(&index) {
+            self.marked.insert(index);
+        }
+    }
+
+    // What start, remove and the actions apply to, the marked rows or else the selected one.
+    fn targets(&self) -> Vec<usize> {
+        if self.marked.len() > 0 {
+            return self.marked.iter().copied().collect();
+        }
+        return vec![self.selected_or_none()];
+    }
+
+    // Index of the item drawn at a screen position.
+    pub fn item_at(&mut self, column: u16, row: u16) -> Option<usize> {
+        let area = self.list_area;
+        if column < area.x || column >= area.x + area.width || row < area.y || row >= area.y + area.height {
+            return None;
+        }
+        let index = self.list_offset + (row - area.y) as usize;
+        return if index < self.current_provider_mut().query_for_items().len() { Some(index) } else { None };
+    }
+
+    // Moves the list by rows, keeping the selection on screen.
+    pub fn wheel(&mut self, rows: isize) {
+        let list_len = self.current_provider_mut().query_for_items().len();
+        let height = self.list_area.height as usize;
+        if list_len == 0 || height == 0 {
+            return;
+        }
+
+        let max_offset = list_len.saturating_sub(height);
+        let offset = std::cmp::min((self.list_offset as isize + rows).max(0) as usize, max_offset);
+        let selected = self.list_state.selected().unwrap_or(0);
+        let selected = selected.clamp(offset, std::cmp::min(offset + height, list_len) - 1);
+        self.list_offset = offset;
+        self.list_state.select(Some(selected));
+    }
+
+    // Replaces the input with a query from the history.
+    fn recall(&mut self, query: String) {
+        self.input_line.reset_buffer(&query);
+        self.set_query(query);
+        self.list_state.select(Some(0));
+    }
+
+    pub fn record_query(&mut self) {
+        let query = String::from(&self.input_line);
+        let provider = self.current_provider().name().to_owned();
+        if !self.history.record(&provider, &query) {
+            return;
+        }
+        if let Some(path) = &self.history_path {
+            if let Err(e) = self.history.save_to(path) {
+                crate::trace!("query", log::Level::Error, "Failed to save query history: {:?}", e);
+            }
+        }
+    }
+
+    // Up with nothing typed at the top of the list, or again after that, puts the query
+    // before in the input line. Returns false if up should move in the list instead.
+    pub fn history_previous(&mut self) -> bool {
+        let at_top = self.list_state.selected().unwrap_or(0) == 0;
+        if self.history_index.is_none() && !(self.input_line.len() == 0 && at_top) {
+            return false;
+        }
+
+        let index = self.history_index.map(|i| i + 1).unwrap_or(0);
+        let query = match self.history.entries(self.current_provider().name()).get(index) {
+            Some(query) => query.clone(),
+            // Stays at the oldest.
+            None => return self.history_index.is_some(),
+        };
+        self.history_index = Some(index);
+        self.recall(query);
+        return true;
+    }
+
+    // Down after recalling a query goes back towards the newest and then the empty input.
+    pub fn history_next(&mut self) -> bool {
+        let index = match self.history_index {
+            Some(index) => index,
+            None => return false,
+        };
+
+        let query = if index == 0 {
+            self.history_index = None;
+            String::new()
+        } else {
+            self.history_index = Some(index - 1);
+            self.history.entries(self.current_provider().name()).get(index - 1).cloned().unwrap_or_default()
+        };
+        self.recall(query);
+        return true;
+    }
+
+    // Shows the first query from start on containing what's been searched for.
+    fn history_search_from(&mut self, start: usize) {
+        let search = match &self.history_search {
+            Some(search) => search,
+            None => return,
+        };
+        let found = self.history.search(self.current_provider().name(), &search.text, start);
+        let query = found.map(|index| self.history.entries(self.current_provider().name())[index].clone());
+
+        let search = self.history_search.as_mut().unwrap();
+        search.found = found.is_some();
+        if let (Some(index), Some(query)) = (found, query) {
+            search.index = Some(index);
+            self.recall(query);
+        }
+    }
+
+    // While searching, ctrl+r again finds an older query, typing and backspace change what's
+    // searched for and exit puts the input back. Anything else takes the query shown and runs.
+    fn history_search_key<B: Backend>(&mut self, terminal: &mut Terminal<B>, key: KeyEvent) -> io::Result<bool> {
+        let search = self.history_search.as_mut().unwrap();
+        // Characters are searched for even when they're bound, like space and ?.
+        if let KeyCode::Char(c) = key.code {
+            if (key.modifiers - KeyModifiers::SHIFT).is_empty() {
+                search.text.push(c);
+                self.history_search_from(0);
+                return Ok(false);
+            }
+        }
+        match self.keymap.lookup(&key) {
+            Some(Command::HistorySearch) => {
+                let start = search.index.map(|i| i + 1).unwrap_or(0);
+                self.history_search_from(start);
+            },
+            Some(Command::BackwardDeleteChar) => {
+                search.text.pop();
+                self.history_search_from(0);
+            },
+            Some(Command::Exit) => {
+                let original = self.history_search.take().unwrap().original;
+                self.recall(original);
+            },
+            Some(command) => {
+                self.history_search = None;
+                return self.run_command(terminal, command);
+            },
+            // Unbound characters with modifiers too, AltGr ones arrive with ctrl and alt.
+            None => if let KeyCode::Char(c) = key.code {
+                search.text.push(c);
+                self.history_search_from(0);
+            },
+        }
+        return Ok(false);
+    }
+
+    pub fn list_unselect(&mut self) {
+        self.list_state.select(None);
+    }
+
+    pub fn on_tick(&mut self) {
+        // let mut message = MSG::default();
+        // unsafe {
+        //     while PeekMessageA(&mut message, HWND(0), 0, 0, PM_REMOVE).into() {
+        //         DispatchMessageA(&message);
+        //     }
+        // }
+    }
+}
+
+impl Drop for SearchableListApp {
+    fn drop(&mut self) {
+        // unsafe {
+            // DestroyWindow(self.input_window);
+        // }
+    }
+}
+
+impl SearchableListApp {
+    fn selected_or_none(&self) -> usize {
+        return self.list_state.selected().unwrap_or(std::usize::MAX);
+    }
+
+    // Filters again after editing the input line.
+    fn query_changed<B: Backend>(&mut self, terminal: &mut Terminal<B>) {
+        self.set_query((&self.input_line).into());
+        self.list_state.select(Some(0));
+        // Hide cursor before redrawing input line to prevent flickering cursor.
+        let _ = terminal.hide_cursor();
+    }
+
+    // Leaves a provider a prefix switched to when there is nothing left to delete.
+    fn backspace_empty(&mut self) -> bool {
+        if self.input_line.len() > 0 {
+            return false;
+        }
+        if let Some(query) = self.current_provider_mut().backspace_query() {
+            self.input_line.reset_buffer(&query);
+            self.set_query(query);
+            self.list_state.select(Some(0));
+            return true;
+        }
+        if self.leave_routed_provider() {
+            self.list_state.select(Some(0));
+        }
+        return true;
+    }
+
+    // Runs a command from the keymap, returns true when switch should exit.
+    pub fn run_command<B: Backend>(&mut self, terminal: &mut Terminal<B>, command: Command) -> io::Result<bool> {
+        let app = self;
+        match command {
+            Command::Exit => return Ok(true),
+            Command::Accept | Command::AcceptElevated if app.marked.len() > 0 => {
+                let targets = app.targets();
+                app.record_query();
+                crate::trace!("start", log::Level::Info, "Start {} marked apps", targets.len());
+                app.current_provider_mut().start_all(&targets, command == Command::AcceptElevated);
+                return Ok(true);
+            },
+            Command::Accept | Command::AcceptElevated => {
+                let selected = app.selected_or_none();
+                if let Some(query) = app.current_provider_mut().navigate(selected) {
+                    app.input_line.reset_buffer(&query);
+                    app.set_query(query);
+                    app.list_state.select(Some(0));
+                    return Ok(false);
+                }
+
+                app.record_query();
+                if command == Command::AcceptElevated {
+                    crate::trace!("start", log::Level::Info, "Start app elevated");
+                    app.current_provider_mut().start(selected, true);
+                } else {
+                    crate::trace!("start", log::Level::Info, "Start app`");
+                    app.current_provider_mut().start(selected, false);
+                }
+
+                return Ok(true)
+            },
+            Command::ListNext => if !app.history_next() {
+                app.list_next();
+                app.list_focused = true;
+            },
+            Command::ListPrevious => if !app.history_previous() {
+                app.list_previous();
+                app.list_focused = true;
+            },
+            Command::HistorySearch => {
+                app.history_search = Some(HistorySearch::new(String::from(&app.input_line)));
+            },
+            Command::PageDown => {
+                app.list_page_next();
+                app.list_focused = true;
+            },
+            Command::PageUp => {
+                app.list_page_prev();
+                app.list_focused = true;
+            },
+            // Marks and moves on, so space marks a run of rows.
+            Command::ToggleMark => if let Some(selected) = app.list_state.selected() {
+                app.toggle_mark(selected);
+                app.list_focused = true;
+                if selected + 1 < app.current_provider_mut().query_for_items().len() {
+                    app.list_state.select(Some(selected + 1));
+                }
+            },
+            Command::Complete => {
+                if let Some(selected) = app.list_state.selected() {
+                    if let Some(query) = app.current_provider_mut().complete(selected) {
+                        app.input_line.reset_buffer(&query);
+                        app.set_query(query);
+                        app.list_state.select(Some(0));
+                        return Ok(false);
+                    }
+                    if selected >= app.current_provider_mut().query_for_items().len() {
+                        return Ok(false);
+                    }
+                    let s = (&app.current_provider_mut().query_for_items()[selected]).as_matchable_string();
+                    app.input_line.reset_buffer(&s);
+                    app.set_query(s);
+                    app.list_state.select(Some(0));
+                }
+            },
+            Command::Help => app.help = Some(Help::new(&app.keymap, &app.providers)),
+            Command::ActionsMenu => {
+                if let Some(selected) = app.list_state.selected().filter(|&selected| selected < app.current_provider_mut().query_for_items().len()) {
+                    let actions = app.current_provider_mut().actions(selected);
+                    app.actions = ActionsMenu::new(&app.keymap, actions);
+                }
+            },
+            Command::NextProvider => app.next_provider(),
+            Command::SelectProvider(index) => app.select_provider(index),
+            Command::BeginningOfLine => app.input_line.cursor_begin(),
+            Command::EndOfLine => app.input_line.cursor_end(),
+            Command::BackwardChar => app.input_line.cursor_move(-1),
+            Command::ForwardChar => app.input_line.cursor_move(1),
+            Command::BackwardWord => app.input_line.backward_word(),
+            Command::ForwardWord => app.input_line.forward_word(),
+            Command::BackwardDeleteChar => {
+                if app.backspace_empty() {
+                    return Ok(false);
+                }
+                if app.input_line.cursor_pos() == app.input_line.len() {
+                    if let Some(query) = app.current_provider_mut().backspace_query() {
+                        app.input_line.reset_buffer(&query);
+                        app.set_query(query);
+                        app.list_state.select(Some(0));
+                        return Ok(false);
+                    }
+                }
+                app.input_line.backward_remove_cursor();
+                app.query_changed(terminal);
+            },
+            Command::BackwardKillWord => {
+                if app.backspace_empty() {
+                    return Ok(false);
+                }
+                app.input_line.backward_kill_word();
+                app.query_changed(terminal);
+            },
+            Command::DeleteChar | Command::KillWord | Command::BackwardKillLine | Command::KillLine |
+            Command::Yank | Command::YankPop | Command::Undo | Command::Redo | Command::TransposeChars |
+            Command::CapitalizeWord | Command::UpcaseWord | Command::DowncaseWord => {
+                match command {
+                    Command::DeleteChar => app.input_line.forward_remove_cursor(),
+                    Command::KillWord => app.input_line.forward_kill_word(),
+                    Command::BackwardKillLine => app.input_line.backward_kill_line(),
+                    Command::KillLine => app.input_line.forward_kill_line(),
+                    Command::Yank => app.input_line.yank(),
+                    Command::YankPop => { app.input_line.yank_pop(); },
+                    Command::Undo => { app.input_line.undo(); },
+                    Command::Redo => { app.input_line.redo(); },
+                    Command::TransposeChars => app.input_line.transpose_chars(),
+                    Command::CapitalizeWord => app.input_line.capitalize_word(),
+                    Command::UpcaseWord => app.input_line.upcase_word(),
+                    _ => app.input_line.downcase_word(),
+                }
+                app.query_changed(terminal);
+            },
+            Command::Remove => {
+                let targets = app.targets();
+                if targets.len() > 1 {
+                    app.current_provider_mut().remove_all(&targets);
+                } else {
+                    app.current_provider_mut().remove(targets[0]);
+                }
+                app.clear_marks();
+            },
+            Command::KillTree | Command::RaisePriority | Command::LowerPriority => {
+                let action = match command {
+                    Command::KillTree => Action::KillTree,
+                    Command::RaisePriority => Action::RaisePriority,
+                    _ => Action::LowerPriority,
+                };
+                let targets = app.targets();
+                app.current_provider_mut().run_action_all(&targets, action);
+                app.clear_marks();
+            },
+            Command::OpenFolder | Command::OpenTerminal => {
+                let action = if command == Command::OpenFolder { Action::OpenFolder } else { Action::OpenTerminal };
+                let targets = app.targets();
+                return Ok(app.current_provider_mut().run_action_all(&targets, action));
+            },
+            Command::ToggleHidden => {
+                app.current_provider_mut().toggle_hidden();
+                app.clear_marks();
+                app.list_state.select(Some(0));
+            },
+            Command::Copy => {
+                // Copy the path, command or result of the selected item, a line for each marked one.
+                let targets = app.targets();
+                let texts = targets.into_iter().filter_map(|target| app.current_provider_mut().copy_text(target)).collect::<Vec<String>>();
+                if texts.len() > 0 {
+                    let text = texts.join("\n");
+                    crate::trace!("start", log::Level::Info, "Copy selected item");
+                    if let Err(e) = app.clipboard.set_text(&text) {
+                        crate::trace!("start", log::Level::Error, "Copy failed: {:?}", e);
+                    }
+                    return Ok(true)
+                }
+            },
+        }
+        return Ok(false);
+    }
+
+    // The list and movement keys scroll the help, anything else closes it.
+    fn help_key(&mut self, key: KeyEvent) {
+        let page = self.help_height();
+        let help = match self.help.as_mut() {
+            Some(help) => help,
+            None => return,
+        };
+        match self.keymap.lookup(&key) {
+            Some(Command::ListNext) => help.scroll_by(1, page),
+            Some(Command::ListPrevious) => help.scroll_by(-1, page),
+            Some(Command::PageDown) => help.scroll_by(page as isize, page),
+            Some(Command::PageUp) => help.scroll_by(-(page as isize), page),
+            Some(Command::BeginningOfLine) => help.scroll = 0,
+            Some(Command::EndOfLine) => help.scroll_by(help.lines.len() as isize, page),
+            _ => self.help = None,
+        }
+    }
+
+    // Up and down pick an action, enter or the action's own key runs it and anything else
+    // closes the menu.
+    fn actions_key<B: Backend>(&mut self, terminal: &mut Terminal<B>, key: KeyEvent) -> io::Result<bool> {
+        let actions = match self.actions.as_mut() {
+            Some(actions) => actions,
+            None => return Ok(false),
+        };
+        match self.keymap.lookup(&key) {
+            Some(Command::ListNext) => actions.move_by(1),
+            Some(Command::ListPrevious) => actions.move_by(-1),
+            Some(Command::Accept) => {
+                let command = actions.command();
+                self.actions = None;
+                return self.run_command(terminal, command);
+            },
+            Some(command) if actions.commands.contains(&command) => {
+                self.actions = None;
+                return self.run_command(terminal, command);
+            },
+            _ => self.actions = None,
+        }
+        return Ok(false);
+    }
+
+    // Lines of help on screen, inside its borders.
+    fn help_height(&self) -> usize {
+        return std::cmp::max(self.screen_height.saturating_sub(2) as usize, 1);
+    }
+
+    // Clicks select the row under the pointer, a double click starts it like enter.
+    fn mouse<B: Backend>(&mut self, terminal: &mut Terminal<B>, mouse: MouseEvent) -> io::Result<bool> {
+        let app = self;
+        if app.help.is_some() {
+            let page = app.help_height();
+            if let Some(help) = app.help.as_mut() {
+                match mouse.kind {
+                    MouseEventKind::ScrollDown => help.scroll_by(WHEEL_ROWS as isize, page),
+                    MouseEventKind::ScrollUp => help.scroll_by(-(WHEEL_ROWS as isize), page),
+                    _ => {},
+                }
+            }
+            return Ok(false);
+        }
+        if let Some(actions) = app.actions.as_ref() {
+            // A click on an action runs it, anywhere else closes the menu.
+            let area = app.actions_area;
+            let inside = mouse.column > area.x && mouse.column + 1 < area.x + area.width && mouse.row > area.y && mouse.row + 1 < area.y + area.height;
+            match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left) if inside && ((mouse.row - area.y - 1) as usize) < actions.commands.len() => {
+                    let command = actions.commands[(mouse.row - area.y - 1) as usize];
+                    app.actions = None;
+                    return app.run_command(terminal, command);
+                },
+                MouseEventKind::Down(_) => app.actions = None,
+                _ => {},
+            }
+            return Ok(false);
+        }
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let index = match app.item_at(mouse.column, mouse.row) {
+                    Some(index) => index,
+                    None => {
+                        app.last_click = None;
+                        return Ok(false);
+                    }
+                };
+                app.list_state.select(Some(index));
+                app.list_focused = true;
+                if mouse.modifiers.contains(KeyModifiers::CONTROL) {
+                    app.toggle_mark(index);
+                    app.last_click = None;
+                    return Ok(false);
+                }
+                let now = Instant::now();
+                let double = matches!(app.last_click, Some((time, last)) if last == index && now.duration_since(time) <= DOUBLE_CLICK_TIME);
+                if double {
+                    app.last_click = None;
+                    return app.run_command(terminal, Command::Accept);
+                }
+                app.last_click = Some((now, index));
+            },
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some(index) = app.item_at(mouse.column, mouse.row) {
+                    app.list_state.select(Some(index));
+                    app.list_focused = true;
+                }
+            },
+            // Opens the actions menu for the row under the pointer.
+            MouseEventKind::Down(MouseButton::Middle) => {
+                if let Some(index) = app.item_at(mouse.column, mouse.row) {
+                    app.list_state.select(Some(index));
+                    app.list_focused = true;
+                    return app.run_command(terminal, Command::ActionsMenu);
+                }
+            },
+            MouseEventKind::Down(MouseButton::Right) => {
+                let text = app.clipboard.get_text().unwrap_or_else(|e| {
+                    crate::trace!("start", log::Level::Error, "Paste failed: {:?}", e);
+                    String::new()
+                });
+                app.input_line.insert_string(text);
+                let line = String::from(&app.input_line);
+                app.clear_marks();
+                app.current_provider_mut().set_query(line);
+                app.list_state.select(Some(0));
+            },
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                app.wheel(if mouse.kind == MouseEventKind::ScrollDown { WHEEL_ROWS as isize } else { -(WHEEL_ROWS as isize) });
+                app.list_focused = true;
+            },
+            _ => {},
+        }
+        return Ok(false);
+    }
+
+    // Applies one event, returns true when switch should exit, like after starting an item.
+    pub fn handle_event<B: Backend>(&mut self, terminal: &mut Terminal<B>, event: Event) -> io::Result<bool> {
+        let app = self;
+        match event {
+            Event::Key(key) if app.help.is_some() => app.help_key(key),
+            Event::Key(key) if app.actions.is_some() => return app.actions_key(terminal, key),
+            Event::Key(key) if app.history_search.is_some() => return app.history_search_key(terminal, key),
+            Event::Key(key) => match app.keymap.lookup(&key) {
+                Some(command) => {
+                    // Keys like space and ? type themselves where their command doesn't apply yet,
+                    // space marks once the list has been moved through and ? is help on empty input.
+                    if let KeyCode::Char(c) = key.code {
+                        let types = match command {
+                            Command::ToggleMark => !app.list_focused,
+                            Command::Help => app.input_line.len() > 0,
+                            _ => false,
+                        };
+                        if types {
+                            app.history_index = None;
+                            app.input_line.insert(c);
+                            app.query_changed(terminal);
+                            return Ok(false);
+                        }
+                    }
+                    if command != Command::ListPrevious && command != Command::ListNext {
+                        app.history_index = None;
+                    }
+                    return app.run_command(terminal, command);
+                },
+                // Unbound characters are typed, AltGr ones arrive with ctrl and alt.
+                None => if let KeyCode::Char(c) = key.code {
+                    app.history_index = None;
+                    app.input_line.insert(c);
+                    app.query_changed(terminal);
+                },
+            },
+            Event::Mouse(mouse) => return app.mouse(terminal, mouse),
+            Event::Resize(width, height) => {
+                app.screen_width = width;
+                app.screen_height = height;
+                terminal.clear()?;
+                if app.clear_console_on_resize {
+                    unsafe {
+                        crate::console::clear_console()?;
+                    }
+                }
+            },
+        }
+        return Ok(false);
+    }
+}
+
+// Where run_app gets its events, the console or a script in tests.
+pub trait EventSource {
+    // None if nothing happened before timeout.
+    fn next_event(&mut self, timeout: Duration) -> io::Result<Option<Event>>;
+}
+
+pub struct ConsoleEvents;
+
+impl EventSource for ConsoleEvents {
+    fn next_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        if crossterm::event::poll(timeout)? {
+            return Ok(Some(crossterm::event::read()?));
+        }
+        return Ok(None);
+    }
+}
+
+// Events in order, then an error so run_app returns if the script doesn't exit by itself.
+pub struct ScriptedEvents {
+    pub events: std::collections::VecDeque<Event>,
+}
+
+impl ScriptedEvents {
+    pub fn new(events: Vec<Event>) -> Self {
+        return ScriptedEvents { events: events.into() };
+    }
+}
+
+impl EventSource for ScriptedEvents {
+    fn next_event(&mut self, _timeout: Duration) -> io::Result<Option<Event>> {
+        return match self.events.pop_front() {
+            Some(event) => Ok(Some(event)),
+            None => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Out of scripted events")),
+        };
+    }
+}
+
+pub fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut SearchableListApp,
+    events: &mut dyn EventSource,
+    tick_rate: Duration,
+) -> io::Result<()> {
+    let mut last_tick = Instant::now();
+
+    loop {
+        // This causes flicker. Figure out how to double buffer.
+        // terminal.clear()?;
+
+        terminal.draw(|f| ui(f, app))?;
+        let timeout = tick_rate
+            .checked_sub(last_tick.elapsed())
+            .unwrap_or_else(|| Duration::from_secs(0));
+
+        if let Some(event) = events.next_event(timeout)? {
+            if app.handle_event(terminal, event)? {
+                return Ok(());
+            }
+        }
+
+        if last_tick.elapsed() >= tick_rate {
+            // app.on_tick();
+            last_tick = Instant::now();
+        }
+    }
+}
+
+pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut SearchableListApp) {
+    let query = String::from(&app.input_line);
+    let names = app.providers[app.selected_provider].query_for_names();
+    let area = f.size();
+    let (input_area, status_area, list_area) = app.theme.layout(area);
+    app.list_area = list_area;
+    app.scroll_to_selected(names.len());
+
+    // Only the rows on screen are aligned and highlighted, lists can be long.
+    let theme = &app.theme;
+    let first = std::cmp::min(app.list_offset, names.len());
+    let last = std::cmp::min(first + list_area.height as usize, names.len());
+    let mut visible = names[first..last].to_vec();
+    if theme.columns {
+        visible = crate::theme::align_columns(&visible);
+    }
+
+    let normal = theme.normal.style();
+    let matched = normal.patch(theme.matched.style());
+    let gutter = if app.marked.len() > 0 { theme.marker.width() } else { 0 };
+    let items: Vec<ListItem> = visible.iter()
+        .enumerate()
+        .map(|(row, name)| {
+            let index = first + row;
+            let mut spans = vec![];
+            if gutter > 0 {
+                let marker = if app.marked.contains(&index) { theme.marker.clone() } else { " ".repeat(gutter) };
+                spans.push(Span::styled(marker, normal));
+            }
+            // Matched characters in their own spans.
+            let positions = crate::fuzzy::match_positions(&query, name);
+            let mut text = String::new();
+            let mut text_matched = false;
+            for (i, c) in name.chars().enumerate() {
+                let is_matched = positions.binary_search(&i).is_ok();
+                if is_matched != text_matched && text.len() > 0 {
+                    spans.push(Span::styled(std::mem::take(&mut text), if text_matched { matched } else { normal }));
+                }
+                text_matched = is_matched;
+                text.push(c);
+            }
+            spans.push(Span::styled(text, if text_matched { matched } else { normal }));
+            ListItem::new(Spans::from(spans))
+        })
+        .collect();
+
+    if theme.borders {
+        f.render_widget(Block::default().borders(Borders::ALL).style(normal), area);
+    }
+
+    // The prompt stays, the line scrolls after it.
+    let prompt = match &app.history_search {
+        Some(search) => search.prompt(),
+        None => theme.prompt.clone(),
+    };
+    let prompt_width = prompt.width();
+    // How many rows are marked, at the end of the input line if it fits.
+    let marked = format!(" {} marked", app.marked.len());
+    let marked_width = if app.marked.len() > 0 && marked.width() + prompt_width < input_area.width as usize { marked.width() } else { 0 };
+    if marked_width > 0 {
+        let marked_area = Rect { x: input_area.x + input_area.width - marked_width as u16, width: marked_width as u16, ..input_area };
+        f.render_widget(Paragraph::new(marked).style(normal), marked_area);
+    }
+    let input_area = Rect { width: input_area.width - marked_width as u16, ..input_area };
+    let (line, cursor_col) = app.input_line.view((input_area.width as usize).saturating_sub(prompt_width));
+    let rendered_input_line = prompt + &line;
+    let cursor_col = std::cmp::min(prompt_width + cursor_col, input_area.width.saturating_sub(1) as usize);
+    f.render_widget(Paragraph::new(Spans::from(rendered_input_line)).style(normal), input_area);
+
+    // Only the rows on screen go to tui, so list_offset is the one place the scroll lives.
+    let highlight = theme.highlight.style();
+    if status_area.height > 0 {
+        let status = status_bar(app, names.len());
+        f.render_widget(Paragraph::new(status).style(normal), status_area);
+    }
+    let mut state = ListState::default();
+    state.select(app.list_state.selected()
+        .and_then(|selected| selected.checked_sub(app.list_offset))
+        .filter(|&row| row < items.len()));
+
+    // Create a List from all list items and highlight the currently selected one
+    let items = List::new(items)
+        .style(normal)
+        .highlight_style(highlight);
+    f.render_stateful_widget(items, list_area, &mut state);
+
+    if let Some(actions) = &app.actions {
+        // Over the top of the list, as wide as its widest line.
+        let width = actions.lines.iter().map(|line| line.width()).max().unwrap_or(0) + 2;
+        let width = std::cmp::min(std::cmp::max(width as u16, 11), list_area.width);
+        let height = std::cmp::min(actions.lines.len() as u16 + 2, list_area.height);
+        let actions_area = Rect { width, height, ..list_area };
+        let items = actions.lines.iter().map(|line| ListItem::new(line.clone())).collect::<Vec<ListItem>>();
+        let mut state = ListState::default();
+        state.select(Some(actions.selected));
+        let menu = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" Actions "))
+            .style(normal)
+            .highlight_style(highlight);
+        f.render_widget(Clear, actions_area);
+        f.render_stateful_widget(menu, actions_area, &mut state);
+        app.actions_area = actions_area;
+    }
+
+    if let Some(help) = &app.help {
+        let lines = help.lines.iter().skip(help.scroll).map(|line| Spans::from(line.clone())).collect::<Vec<Spans>>();
+        let block = Block::default().borders(Borders::ALL).title(" Help, esc closes ");
+        f.render_widget(Clear, area);
+        f.render_widget(Paragraph::new(lines).block(block).style(normal), area);
+        return;
+    }
+    // Show cursor after drawing finishes to prevent flickering cursor.
+    f.set_cursor(input_area.x + cursor_col as u16, input_area.y);
+}
+
+// The providers as tabs, the current one highlighted and numbered for alt+1..9, then how many
+// items matched, the provider's mode and what the keys do to the selected item.
+fn status_bar(app: &mut SearchableListApp, matched: usize) -> Spans<'static> {
+    let normal = app.theme.normal.style();
+    let mut spans = vec![];
+    for (i, provider) in app.providers.iter().enumerate() {
+        let tab = if i < 9 { format!(" {} {} ", i + 1, provider.name()) } else { format!(" {} ", provider.name()) };
+        let style = if i == app.selected_provider { normal.patch(app.theme.highlight.style()) } else { normal };
+        spans.push(Span::styled(tab, style));
+    }
+
+    let provider = app.providers[app.selected_provider].as_mut();
+    let mut info = match provider.total() {
+        Some(total) => format!("  {}/{}", matched, total),
+        None => format!("  {}", matched),
+    };
+    if let Some(mode) = provider.mode() {
+        info += &format!(" {}", mode);
+    }
+    if let Some(selected) = app.list_state.selected().filter(|&selected| selected < matched) {
+        for (command, hint) in provider.hints(selected) {
+            if let Some(chord) = app.keymap.chords(command).first() {
+                info += &format!("  {} {}", chord, hint);
+            }
+        }
+    }
+    spans.push(Span::styled(info, normal));
+    return Spans::from(spans);
+}
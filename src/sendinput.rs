@@ -0,0 +1,104 @@
+use windows::Win32::Foundation::*;
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+// Types text into the foreground window as unicode key presses, so it works for
+// characters that aren't on the keyboard layout.
+pub fn type_text(text: &str) -> anyhow::Result<()> {
+    let mut inputs = vec![];
+    for unit in text.encode_utf16() {
+        for flags in [KEYEVENTF_UNICODE, KEYBD_EVENT_FLAGS(KEYEVENTF_UNICODE.0 | KEYEVENTF_KEYUP.0)] {
+            inputs.push(INPUT {
+                r#type: INPUT_KEYBOARD,
+                Anonymous: INPUT_0 {
+                    ki: KEYBDINPUT {
+                        wVk: VIRTUAL_KEY(0),
+                        wScan: unit,
+                        dwFlags: flags,
+                        time: 0,
+                        dwExtraInfo: 0,
+                    },
+                },
+            });
+        }
+    }
+
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        return Err(anyhow::Error::from(windows::core::Error::from_win32()));
+    }
+    return Ok(());
+}
+
+fn key_input(key: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    return INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: key,
+                wScan: 0,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    };
+}
+
+// Ctrl+V in the foreground window.
+pub fn paste() -> anyhow::Result<()> {
+    let inputs = [
+        key_input(VK_CONTROL, KEYBD_EVENT_FLAGS(0)),
+        key_input(VK_V, KEYBD_EVENT_FLAGS(0)),
+        key_input(VK_V, KEYEVENTF_KEYUP),
+        key_input(VK_CONTROL, KEYEVENTF_KEYUP),
+    ];
+
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        return Err(anyhow::Error::from(windows::core::Error::from_win32()));
+    }
+    return Ok(());
+}
+
+// Waits for the foreground window to be something other than window, for example after
+// the quake terminal hides. Returns false on timeout.
+pub fn wait_for_foreground_change(window: HWND, timeout: std::time::Duration) -> bool {
+    let start = std::time::Instant::now();
+    while start.elapsed() < timeout {
+        let foreground = unsafe { GetForegroundWindow() };
+        if foreground != window && foreground.0 != 0 {
+            return true;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    return false;
+}
+
+// The quake terminal switch runs in, which is the foreground window while switch handles a key.
+// quakerun starts after switch exits, by then the terminal may already be hidden.
+fn quake_window_arg() -> String {
+    return unsafe { GetForegroundWindow() }.0.to_string();
+}
+
+// switch exits before the window it was opened over is back in the foreground, so typing
+// is left to quakerun --type which waits for that.
+pub fn type_text_after_exit(text: &str) -> anyhow::Result<()> {
+    std::process::Command::new(crate::path::get_installed_exe_path("quakerun.exe"))
+        .arg("--window")
+        .arg(quake_window_arg())
+        .arg("--type")
+        .arg(text)
+        .spawn()?;
+    return Ok(());
+}
+
+// Like type_text_after_exit but pastes what is on the clipboard.
+pub fn paste_after_exit() -> anyhow::Result<()> {
+    std::process::Command::new(crate::path::get_installed_exe_path("quakerun.exe"))
+        .arg("--window")
+        .arg(quake_window_arg())
+        .arg("--paste")
+        .spawn()?;
+    return Ok(());
+}
@@ -0,0 +1,243 @@
+use crate::listcontentprovider::{ListContentProvider, ListItem};
+use crate::keymap::Command;
+use crate::clipboard::{Clipboard, SystemClipboard};
+use crate::snippets::Snippet;
+
+use crate::log::*;
+
+pub struct SnippetEntry {
+    pub text: String,
+    // Index in the loaded snippets.
+    index: usize,
+}
+
+impl ListItem for SnippetEntry {
+    fn as_any(&self) -> &dyn std::any::Any {
+        return self;
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        return self;
+    }
+
+    fn as_string(&self) -> String {
+        return self.text.clone();
+    }
+
+    fn as_matchable_string(&self) -> String {
+        return self.text.clone();
+    }
+}
+
+// A snippet with input placeholders waiting for their values.
+struct Prompt {
+    index: usize,
+    inputs: Vec<String>,
+    // (label, value) for the inputs answered so far.
+    values: Vec<(String, String)>,
+}
+
+// Searches snippets by name and tag, enter copies the expanded snippet and ctrl+enter
+// pastes it into the window switch was opened over.
+// If the snippet has input placeholders, enter first asks for each value in the input line.
+pub struct SnippetProvider {
+    clipboard: Box<dyn Clipboard>,
+    snippets: Vec<Snippet>,
+    query: String,
+    prompt: Option<Prompt>,
+    filtered: Vec<SnippetEntry>,
+}
+
+fn one_line(text: &str) -> String {
+    return crate::clipboardhistoryprovider::ClipboardEntry::display_text(text);
+}
+
+impl SnippetProvider {
+    pub fn new(config: &crate::config::Config) -> Box<Self> {
+        let mut snippets = vec![];
+        for path in config.snippets.iter() {
+            let path = crate::expand::expand(path);
+            match crate::snippets::load(&path) {
+                Ok(loaded) => snippets.extend(loaded),
+                Err(e) => {
+                    crate::trace!("snippets", log::Level::Error, "Failed to load snippets from {}: {:?}", path, e);
+                }
+            }
+        }
+
+        return Self::with_snippets(snippets, Box::new(SystemClipboard));
+    }
+
+    pub fn with_snippets(snippets: Vec<Snippet>, clipboard: Box<dyn Clipboard>) -> Box<Self> {
+        let mut provider = Box::new(SnippetProvider {
+            clipboard,
+            snippets,
+            query: String::new(),
+            prompt: None,
+            filtered: vec![],
+        });
+        provider.filter();
+        return provider;
+    }
+
+    pub fn clipboard(&mut self) -> &mut dyn Clipboard {
+        return self.clipboard.as_mut();
+    }
+
+    // Label of the input being asked for.
+    pub fn prompting(&self) -> Option<&str> {
+        let prompt = self.prompt.as_ref()?;
+        return prompt.inputs.get(prompt.values.len()).map(|label| label.as_str());
+    }
+
+    fn filter(&mut self) {
+        if let Some(prompt) = self.prompt.as_ref().filter(|p| p.values.len() < p.inputs.len()) {
+            let snippet = &self.snippets[prompt.index];
+            let label = &prompt.inputs[prompt.values.len()];
+            let mut values = prompt.values.clone();
+            values.push((label.clone(), self.query.clone()));
+            self.filtered = vec![SnippetEntry {
+                text: format!("{} {}: {}", snippet.name, label, one_line(&Self::expand_with(snippet, &values))),
+                index: prompt.index,
+            }];
+            return;
+        }
+
+        let snippets = &self.snippets;
+        self.filtered = crate::snippets::search(snippets, &self.query).into_iter()
+            .map(|snippet| {
+                let index = snippets.iter().position(|s| std::ptr::eq(s, snippet)).unwrap();
+                let tags = if snippet.tags.len() > 0 {
+                    format!(" [{}]", snippet.tags.join(", "))
+                } else {
+                    String::new()
+                };
+                SnippetEntry {
+                    text: format!("{}{}  {}", snippet.name, tags, one_line(&snippet.text)),
+                    index,
+                }
+            })
+            .collect();
+    }
+
+    // Preview with what was typed so far, the clipboard isn't read for it.
+    fn expand_with(snippet: &Snippet, values: &[(String, String)]) -> String {
+        let now = chrono::Local::now().naive_local();
+        return crate::snippets::expand(&snippet.text, values, &now, &mut || "{clipboard}".to_owned());
+    }
+
+    // The snippet at filtered_index with placeholders filled in.
+    pub fn expand(&mut self, filtered_index: usize) -> Option<String> {
+        let index = self.filtered.get(filtered_index)?.index;
+        let values = match &self.prompt {
+            Some(prompt) if prompt.index == index => prompt.values.clone(),
+            _ => vec![],
+        };
+
+        let now = chrono::Local::now().naive_local();
+        let clipboard = &mut self.clipboard;
+        return Some(crate::snippets::expand(&self.snippets[index].text, &values, &now, &mut || {
+            clipboard.get_text().unwrap_or_default()
+        }));
+    }
+}
+
+impl ListContentProvider for SnippetProvider {
+    fn name(&self) -> &str {
+        return "Snippets";
+    }
+
+    fn total(&self) -> Option<usize> {
+        return Some(self.snippets.len());
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "copy"), (Command::AcceptElevated, "paste")];
+    }
+
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        return self.filtered.iter_mut().map(|e| e as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        return self.filtered.iter().map(|e| e.as_string()).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.filter();
+    }
+
+    // Copy the expanded snippet, elevated is ctrl+enter which also pastes it.
+    fn start(&mut self, filtered_index: usize, elevated: bool) {
+        let text = match self.expand(filtered_index) {
+            Some(text) => text,
+            None => return,
+        };
+
+        crate::trace!("start", log::Level::Info, "Snippet {} paste {:?}", self.snippets[self.filtered[filtered_index].index].name, elevated);
+        self.prompt = None;
+        self.filter();
+        if let Err(e) = self.clipboard.set_text(&text) {
+            crate::trace!("start", log::Level::Error, "Copy snippet failed: {:?}", e);
+            return;
+        }
+        if elevated {
+            if let Err(e) = crate::sendinput::paste_after_exit() {
+                crate::trace!("start", log::Level::Error, "Paste snippet failed: {:?}", e);
+            }
+        }
+    }
+
+    fn remove(&mut self, _filtered_index: usize) {
+    }
+
+    // Enter on a snippet with inputs asks for them one at a time in the input line,
+    // after the last one the snippet is started.
+    fn navigate(&mut self, filtered_index: usize) -> Option<String> {
+        if let Some(prompt) = self.prompt.as_mut().filter(|p| p.values.len() < p.inputs.len()) {
+            let label = prompt.inputs[prompt.values.len()].clone();
+            prompt.values.push((label, self.query.clone()));
+            if prompt.values.len() == prompt.inputs.len() {
+                // Leave the prompt in place for start to use its values.
+                return None;
+            }
+            return Some(String::new());
+        }
+
+        let index = self.filtered.get(filtered_index)?.index;
+        let inputs = self.snippets[index].inputs();
+        if inputs.len() == 0 {
+            return None;
+        }
+
+        self.prompt = Some(Prompt { index, inputs, values: vec![] });
+        return Some(String::new());
+    }
+
+    // Backspace in an empty input gives up on the inputs and goes back to the snippets.
+    fn backspace_query(&mut self) -> Option<String> {
+        if self.prompt.is_none() || self.query.len() > 0 {
+            return None;
+        }
+        self.prompt = None;
+        return Some(String::new());
+    }
+
+    fn leave(&mut self) {
+        self.prompt = None;
+        self.filter();
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.expand(filtered_index);
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        return Some(";");
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "copy"), (Command::AcceptElevated, "paste")];
+    }
+}
@@ -0,0 +1,146 @@
+use serde::Deserialize;
+
+// Named pieces of text for the snippets provider, loaded from a directory of text files
+// or a TOML file.
+//
+// In a directory every file is a snippet named after the file, subdirectories are its tags,
+// so review\lgtm.txt is the snippet lgtm tagged review.
+//
+// A TOML file has a table for every snippet:
+// [[snippet]]
+// name = "lgtm"
+// tags = ["review"]
+// text = "Looks good to me, thanks {input:name}!"
+//
+// Placeholders:
+// {date} and {time} are the current local date and time.
+// {clipboard} is the text on the clipboard.
+// {input} and {input:label} are asked for before the snippet is copied, every label once.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Snippet {
+    pub name: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+struct SnippetFile {
+    #[serde(default)]
+    snippet: Vec<Snippet>,
+}
+
+impl Snippet {
+    // Labels of the input placeholders in the order they first appear, {input} is "input".
+    pub fn inputs(&self) -> Vec<String> {
+        let mut inputs: Vec<String> = vec![];
+        crate::template::expand_placeholders(&self.text, &mut |name| {
+            if let Some(label) = input_label(name) {
+                if !inputs.iter().any(|i| i == label) {
+                    inputs.push(label.to_owned());
+                }
+            }
+            return None;
+        });
+        return inputs;
+    }
+}
+
+fn input_label(placeholder: &str) -> Option<&str> {
+    if placeholder == "input" {
+        return Some("input");
+    }
+    return placeholder.strip_prefix("input:").map(|label| label.trim());
+}
+
+pub fn parse_toml(text: &str) -> anyhow::Result<Vec<Snippet>> {
+    return Ok(toml::from_str::<SnippetFile>(text)?.snippet);
+}
+
+// Editors add a newline at the end of files that isn't part of the snippet.
+fn trim_final_newline(text: &str) -> &str {
+    return text.strip_suffix("\r\n").or_else(|| text.strip_suffix('\n')).unwrap_or(text);
+}
+
+pub fn load_directory<IntoPath: Into<std::path::PathBuf>>(root: IntoPath) -> std::io::Result<Vec<Snippet>> {
+    let root = root.into();
+    let mut snippets = vec![];
+
+    crate::index::walk_ignoring(&root, &[".git/".to_owned()], 8, &mut |path, is_dir| {
+        if is_dir {
+            return;
+        }
+
+        // Not text, skip it.
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) => return,
+        };
+
+        let tags = path.parent()
+            .and_then(|parent| parent.strip_prefix(&root).ok())
+            .map(|relative| relative.iter().map(|tag| tag.to_string_lossy().into_owned()).collect())
+            .unwrap_or_default();
+
+        snippets.push(Snippet {
+            name: path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+            tags,
+            text: trim_final_newline(&text).to_owned(),
+        });
+    })?;
+
+    snippets.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    return Ok(snippets);
+}
+
+// Snippets from a directory or TOML file, nothing if it doesn't exist.
+pub fn load<IntoPath: Into<std::path::PathBuf>>(path: IntoPath) -> anyhow::Result<Vec<Snippet>> {
+    let path = path.into();
+    if path.is_dir() {
+        return Ok(load_directory(&path)?);
+    } else if path.is_file() {
+        return parse_toml(&std::fs::read_to_string(&path)?);
+    }
+    return Ok(vec![]);
+}
+
+// Every query word has to fuzzy match the name or a tag, a tag counts for less.
+// Everything is listed by name for an empty query.
+pub fn search<'a>(snippets: &'a [Snippet], query: &str) -> Vec<&'a Snippet> {
+    const TAG_PENALTY: i32 = 16;
+
+    let mut scored = snippets.iter().filter_map(|snippet| {
+        let mut total = 0;
+        for word in query.split_whitespace() {
+            let name_score = crate::fuzzy::score(word, &snippet.name);
+            let tag_score = snippet.tags.iter()
+                .filter_map(|tag| crate::fuzzy::score(word, tag))
+                .max()
+                .map(|score| score - TAG_PENALTY);
+            total += name_score.max(tag_score)?;
+        }
+        return Some((total, snippet));
+    }).collect::<Vec<(i32, &Snippet)>>();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score.cmp(a_score).then(a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    return scored.into_iter().map(|(_, s)| s).collect();
+}
+
+// Substitutes the placeholders of text. inputs are (label, value) pairs, clipboard is only
+// called if the text uses it. Unknown placeholders are left as they are.
+pub fn expand(text: &str, inputs: &[(String, String)], now: &chrono::NaiveDateTime, clipboard: &mut dyn FnMut() -> String) -> String {
+    return crate::template::expand_placeholders(text, &mut |name| {
+        if let Some(label) = input_label(name) {
+            return inputs.iter().find(|(l, _)| l == label).map(|(_, value)| value.clone());
+        }
+
+        return match name {
+            "date" => Some(now.format("%Y-%m-%d").to_string()),
+            "time" => Some(now.format("%H:%M").to_string()),
+            "clipboard" => Some(clipboard()),
+            _ => None,
+        };
+    });
+}
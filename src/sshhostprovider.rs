@@ -0,0 +1,202 @@
+use crate::listcontentprovider::{ListContentProvider, ListItem};
+use crate::keymap::Command;
+use crate::startappsprovider::{AppEntry, AppEntryKind};
+use crate::sshhosts::{KnownHosts, SshHost, SshUseCounts};
+
+use crate::log::*;
+
+pub struct SshHostEntry {
+    pub host: SshHost,
+    pub use_count: u32,
+}
+
+impl SshHostEntry {
+    // The ssh command, without the terminal it runs in.
+    pub fn ssh_command(&self) -> String {
+        return format!("ssh {}", self.host.ssh_args());
+    }
+
+    // ssh in the configured terminal, or in its own console if there is none.
+    pub fn app(&self, terminal: &str) -> AppEntry {
+        let ssh = self.ssh_command();
+        let command = if terminal.trim().len() > 0 {
+            format!("{} {}", terminal.trim(), ssh)
+        } else {
+            ssh.clone()
+        };
+
+        return AppEntry {
+            name: ssh,
+            use_count: self.use_count,
+            kind: AppEntryKind::Command { command },
+            ..Default::default()
+        };
+    }
+}
+
+impl ListItem for SshHostEntry {
+    fn as_any(&self) -> &dyn std::any::Any {
+        return self;
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        return self;
+    }
+
+    fn as_string(&self) -> String {
+        return match self.host.destination() {
+            Some(destination) => format!("{} ({})", self.host.name, destination),
+            None => self.host.name.clone(),
+        };
+    }
+
+    fn as_matchable_string(&self) -> String {
+        return self.host.name.clone();
+    }
+}
+
+// Hosts from ~/.ssh/config and known_hosts, enter runs ssh to the host in a terminal.
+// Hosts used most come first.
+pub struct SshHostProvider {
+    hosts: Vec<SshHostEntry>,
+    known_hosts: KnownHosts,
+    terminal: String,
+    // Where use counts are saved when a host is started, None in tests.
+    use_counts_path: Option<String>,
+    query: String,
+    filtered: Vec<SshHostEntry>,
+}
+
+impl SshHostProvider {
+    pub fn new(config: &crate::config::Config) -> Box<Self> {
+        let ssh_dir = std::path::PathBuf::from(crate::expand::expand_tilde("~/.ssh"));
+        let ssh_config = crate::sshhosts::load_config(ssh_dir.join("config"));
+        let known_hosts = crate::sshhosts::load_known_hosts(ssh_dir.join("known_hosts"));
+        let hosts = crate::sshhosts::merge_hosts(ssh_config, &known_hosts.hosts);
+
+        let mut provider = Self::with_hosts(hosts, known_hosts, config.terminal.clone());
+        provider.use_counts_path = SshUseCounts::path().ok();
+        let use_counts = provider.use_counts_path.as_ref().map(|path| SshUseCounts::load_from(path));
+        match use_counts {
+            Some(Ok(use_counts)) => provider.set_use_counts(&use_counts),
+            Some(Err(e)) => {
+                crate::trace!("init", log::Level::Error, "Load ssh use counts failed: {:?}", e);
+            },
+            None => {},
+        }
+        return provider;
+    }
+
+    pub fn with_hosts(hosts: Vec<SshHost>, known_hosts: KnownHosts, terminal: String) -> Box<Self> {
+        let mut provider = Box::new(SshHostProvider {
+            hosts: hosts.into_iter().map(|host| SshHostEntry { host, use_count: 0 }).collect(),
+            known_hosts,
+            terminal,
+            use_counts_path: None,
+            query: String::new(),
+            filtered: vec![],
+        });
+        provider.filter();
+        return provider;
+    }
+
+    pub fn set_use_counts(&mut self, use_counts: &SshUseCounts) {
+        for entry in self.hosts.iter_mut() {
+            entry.use_count = use_counts.get(&entry.host);
+        }
+        self.filter();
+    }
+
+    fn filter(&mut self) {
+        let query = self.query.trim();
+        let mut scored = self.hosts.iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                if query.len() == 0 {
+                    return Some((0, i, entry));
+                }
+                let hostname = entry.host.hostname.as_deref().unwrap_or("");
+                return crate::fuzzy::score(query, &entry.host.name)
+                    .or_else(|| crate::fuzzy::score(query, hostname))
+                    .map(|score| (score, i, entry));
+            })
+            .collect::<Vec<(i32, usize, &SshHostEntry)>>();
+        // Most used first, then best match, then the order of the files.
+        scored.sort_by(|a, b| b.2.use_count.cmp(&a.2.use_count).then(b.0.cmp(&a.0)).then(a.1.cmp(&b.1)));
+
+        let mut filtered = scored.into_iter()
+            .map(|(_, _, entry)| SshHostEntry { host: entry.host.clone(), use_count: entry.use_count })
+            .collect::<Vec<SshHostEntry>>();
+
+        // A host typed in full that is only in known_hosts hashed.
+        if query.len() > 0 && !query.contains(char::is_whitespace) && !filtered.iter().any(|e| e.host.name == query) {
+            let host = SshHost::new(query);
+            if self.known_hosts.contains_hashed(&host.name, host.port) {
+                filtered.insert(0, SshHostEntry { host, use_count: 0 });
+            }
+        }
+
+        self.filtered = filtered;
+    }
+}
+
+impl ListContentProvider for SshHostProvider {
+    fn name(&self) -> &str {
+        return "SSH";
+    }
+
+    fn total(&self) -> Option<usize> {
+        return Some(self.hosts.len());
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "connect"), (Command::Copy, "copy")];
+    }
+
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        return self.filtered.iter_mut().map(|e| e as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        return self.filtered.iter().map(|e| e.as_string()).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.filter();
+    }
+
+    fn start(&mut self, filtered_index: usize, elevated: bool) {
+        let entry = match self.filtered.get(filtered_index) {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        if let Some(path) = &self.use_counts_path {
+            if let Err(e) = SshUseCounts::record_to(path, &entry.host) {
+                crate::trace!("start", log::Level::Error, "Save ssh use counts failed: {:?}", e);
+            }
+        }
+
+        let app = entry.app(&self.terminal);
+        crate::trace!("start", log::Level::Info, "ssh elevated {:?}: {:?}", elevated, app);
+        if let Err(e) = app.launch(elevated) {
+            crate::trace!("start", log::Level::Error, "ssh error: {:?}", e);
+        }
+    }
+
+    fn remove(&mut self, _filtered_index: usize) {
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.filtered.get(filtered_index).map(|e| e.ssh_command());
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        return Some("@");
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "connect"), (Command::AcceptElevated, "connect as admin"), (Command::Copy, "copy command")];
+    }
+}
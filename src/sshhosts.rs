@@ -0,0 +1,430 @@
+// Hosts for the ssh provider from ~/.ssh/config and ~/.ssh/known_hosts.
+//
+// Host lines in the config name hosts, patterns with * ? or ! only apply settings and
+// aren't listed. Include directives are followed, relative paths are relative to ~/.ssh.
+// known_hosts entries hashed with HashKnownHosts can't be listed, but a host typed in
+// full can be checked against them.
+
+const MAX_INCLUDES: usize = 64;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SshHost {
+    // What goes after ssh, a Host alias from the config or a name from known_hosts.
+    pub name: String,
+    pub hostname: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    // Host from the config, ssh applies its settings itself.
+    pub configured: bool,
+}
+
+impl SshHost {
+    pub fn new(name: &str) -> SshHost {
+        return SshHost {
+            name: name.to_owned(),
+            ..Default::default()
+        };
+    }
+
+    // Arguments for ssh, only hosts from known_hosts on another port need more than the name.
+    pub fn ssh_args(&self) -> String {
+        return match self.port.filter(|_| !self.configured) {
+            Some(port) => format!("-p {} {}", port, crate::cmdline::quote_windows(&self.name)),
+            None => crate::cmdline::quote_windows(&self.name),
+        };
+    }
+
+    // user@hostname:port as far as it is known, None if that's just the name.
+    pub fn destination(&self) -> Option<String> {
+        if self.hostname.is_none() && self.user.is_none() && self.port.is_none() {
+            return None;
+        }
+
+        let mut destination = String::new();
+        if let Some(user) = &self.user {
+            destination += user;
+            destination += "@";
+        }
+        destination += self.hostname.as_deref().unwrap_or(&self.name);
+        if let Some(port) = self.port {
+            destination += &format!(":{}", port);
+        }
+        return Some(destination);
+    }
+}
+
+fn is_pattern(host: &str) -> bool {
+    return host.contains(|c| c == '*' || c == '?' || c == '!');
+}
+
+// Splits a config line into its keyword and arguments. The keyword can be followed by
+// whitespace or =, arguments can be in double quotes.
+fn split_config_line(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.len() == 0 || line.starts_with('#') {
+        return None;
+    }
+
+    let end = line.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(line.len());
+    let keyword = line[..end].to_lowercase();
+    let rest = line[end..].trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest);
+
+    let mut args = vec![];
+    let mut arg = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+    for c in rest.chars() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+            has_arg = true;
+        } else if c.is_whitespace() && !in_quotes {
+            if has_arg {
+                args.push(std::mem::take(&mut arg));
+                has_arg = false;
+            }
+        } else {
+            arg.push(c);
+            has_arg = true;
+        }
+    }
+    if has_arg {
+        args.push(arg);
+    }
+
+    return Some((keyword, args));
+}
+
+// Adds the hosts of a config file to hosts. include is called with each Include argument
+// and returns the text of the files it names.
+pub fn parse_config(text: &str, hosts: &mut Vec<SshHost>, include: &mut dyn FnMut(&str) -> Vec<String>) {
+    // Indexes in hosts of the hosts the current Host block applies to.
+    let mut block: Vec<usize> = vec![];
+
+    for line in text.lines() {
+        let (keyword, args) = match split_config_line(line) {
+            Some(line) => line,
+            None => continue,
+        };
+
+        match keyword.as_str() {
+            "host" => {
+                block.clear();
+                for name in args.iter().filter(|name| !is_pattern(name)) {
+                    match hosts.iter().position(|h| &h.name == name) {
+                        // ssh uses the first value it finds for a setting, so does a repeated host.
+                        Some(i) => block.push(i),
+                        None => {
+                            hosts.push(SshHost { configured: true, ..SshHost::new(name) });
+                            block.push(hosts.len() - 1);
+                        },
+                    }
+                }
+            },
+            "match" => block.clear(),
+            "include" => {
+                for pattern in args.iter() {
+                    for included in include(pattern) {
+                        parse_config(&included, hosts, include);
+                    }
+                }
+            },
+            "hostname" | "user" | "port" => {
+                let value = match args.first() {
+                    Some(value) => value,
+                    None => continue,
+                };
+                for &i in block.iter() {
+                    let host = &mut hosts[i];
+                    match keyword.as_str() {
+                        "hostname" if host.hostname.is_none() => host.hostname = Some(value.clone()),
+                        "user" if host.user.is_none() => host.user = Some(value.clone()),
+                        "port" if host.port.is_none() => host.port = value.parse().ok(),
+                        _ => {},
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+// Files an Include argument names, sorted like ssh does. Wildcards are supported in the
+// file name.
+fn include_paths(pattern: &str, ssh_dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let path = std::path::PathBuf::from(crate::expand::expand_tilde(pattern));
+    let path = if path.is_absolute() { path } else { ssh_dir.join(path) };
+
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    if !is_pattern(&name) {
+        return vec![path];
+    }
+
+    let dir = path.parent().unwrap_or(ssh_dir);
+    let mut paths = std::fs::read_dir(dir).map(|entries| {
+        entries.filter_map(|e| e.ok())
+            .filter(|e| crate::ignore::match_segment(&name, &e.file_name().to_string_lossy()))
+            .map(|e| e.path())
+            .collect::<Vec<std::path::PathBuf>>()
+    }).unwrap_or_default();
+    paths.sort();
+    return paths;
+}
+
+// Hosts in the config file at path, following includes.
+pub fn load_config<IntoPath: Into<std::path::PathBuf>>(path: IntoPath) -> Vec<SshHost> {
+    let path = path.into();
+    let ssh_dir = path.parent().map(|p| p.to_owned()).unwrap_or_default();
+    let mut hosts = vec![];
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(_) => return hosts,
+    };
+
+    // Files can include each other, give up on loops after a while.
+    let mut includes = 0;
+    let mut include = |pattern: &str| -> Vec<String> {
+        includes += 1;
+        if includes > MAX_INCLUDES {
+            return vec![];
+        }
+        return include_paths(pattern, &ssh_dir).iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .collect();
+    };
+    parse_config(&text, &mut hosts, &mut include);
+    return hosts;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct HashedHost {
+    salt: Vec<u8>,
+    hash: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+pub struct KnownHosts {
+    pub hosts: Vec<SshHost>,
+    hashed: Vec<HashedHost>,
+}
+
+impl KnownHosts {
+    pub fn hashed_len(&self) -> usize {
+        return self.hashed.len();
+    }
+
+    // Whether host is one of the hashed entries, they are hashed as host or [host]:port.
+    pub fn contains_hashed(&self, host: &str, port: Option<u16>) -> bool {
+        let name = match port {
+            Some(port) if port != 22 => format!("[{}]:{}", host, port),
+            _ => host.to_owned(),
+        };
+        return self.hashed.iter().any(|h| hmac_sha1(&h.salt, name.as_bytes()) == h.hash);
+    }
+}
+
+// Entries are hostnames separated by commas followed by the key. Hostnames can be
+// [host]:port or |1|salt|hash when hashed. Wildcard patterns, negations and the lines
+// marked @cert-authority or @revoked aren't hosts to connect to.
+pub fn parse_known_hosts(text: &str) -> KnownHosts {
+    let mut known_hosts = KnownHosts::default();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.len() == 0 || line.starts_with('#') || line.starts_with('@') {
+            continue;
+        }
+
+        let names = match line.split_whitespace().next() {
+            Some(names) => names,
+            None => continue,
+        };
+        for name in names.split(',') {
+            if let Some(hashed) = name.strip_prefix("|1|") {
+                let mut parts = hashed.split('|');
+                let salt = parts.next().and_then(base64_decode);
+                let hash = parts.next().and_then(base64_decode);
+                if let (Some(salt), Some(hash)) = (salt, hash) {
+                    known_hosts.hashed.push(HashedHost { salt, hash });
+                }
+                continue;
+            }
+
+            if is_pattern(name) || name.len() == 0 {
+                continue;
+            }
+
+            let mut host = SshHost::new(name);
+            if let Some(bracketed) = name.strip_prefix('[') {
+                if let Some((host_name, port)) = bracketed.split_once("]:") {
+                    host.name = host_name.to_owned();
+                    host.port = port.parse().ok().filter(|&p| p != 22);
+                }
+            }
+            if !known_hosts.hosts.iter().any(|h| h.name == host.name && h.port == host.port) {
+                known_hosts.hosts.push(host);
+            }
+        }
+    }
+
+    return known_hosts;
+}
+
+pub fn load_known_hosts<IntoPath: Into<std::path::PathBuf>>(path: IntoPath) -> KnownHosts {
+    return std::fs::read_to_string(path.into())
+        .map(|text| parse_known_hosts(&text))
+        .unwrap_or_default();
+}
+
+// Config hosts first, then known hosts the config doesn't have. Known hosts are often
+// listed by name and by ip, the ones the config already names by HostName are left out.
+pub fn merge_hosts(config: Vec<SshHost>, known: &[SshHost]) -> Vec<SshHost> {
+    let mut hosts = config;
+    for host in known {
+        let duplicate = hosts.iter().any(|h| {
+            h.port == host.port && (h.name == host.name || h.hostname.as_deref() == Some(host.name.as_str()))
+        });
+        if !duplicate {
+            hosts.push(host.clone());
+        }
+    }
+    return hosts;
+}
+
+// How often each host was connected to, keyed by its ssh arguments, persisted as a json
+// object in %APPDATA%\switch\ssh_hosts.json. Kept apart from the start apps history so
+// hosts don't show up there.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SshUseCounts {
+    counts: std::collections::BTreeMap<String, u32>,
+}
+
+impl SshUseCounts {
+    pub fn get(&self, host: &SshHost) -> u32 {
+        return self.counts.get(&host.ssh_args()).copied().unwrap_or(0);
+    }
+
+    pub fn record(&mut self, host: &SshHost) {
+        *self.counts.entry(host.ssh_args()).or_insert(0) += 1;
+    }
+
+    pub fn parse(json: &str) -> anyhow::Result<Self> {
+        return Ok(SshUseCounts { counts: serde_json::from_str(json)? });
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        return Ok(serde_json::to_string(&self.counts)?);
+    }
+
+    pub fn load_from<IntoPath: Into<std::path::PathBuf>>(path: IntoPath) -> anyhow::Result<Self> {
+        let path = path.into();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        return Self::parse(&std::fs::read_to_string(&path)?);
+    }
+
+    pub fn save_to<IntoPath: Into<std::path::PathBuf>>(&self, path: IntoPath) -> anyhow::Result<()> {
+        std::fs::write(path.into(), self.to_json()?)?;
+        return Ok(());
+    }
+
+    pub fn path() -> anyhow::Result<String> {
+        return crate::path::get_app_data_path("ssh_hosts.json");
+    }
+
+    // Read the counts, count host and write them back.
+    pub fn record_to<IntoPath: Into<std::path::PathBuf>>(path: IntoPath, host: &SshHost) -> anyhow::Result<()> {
+        let path = path.into();
+        let mut counts = Self::load_from(&path)?;
+        counts.record(host);
+        counts.save_to(&path)?;
+        return Ok(());
+    }
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in text.trim_end_matches('=').chars() {
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' => 62,
+            '/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    return Some(bytes);
+}
+
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (x, y) in h.iter_mut().zip([a, b, c, d, e]) {
+            *x = x.wrapping_add(y);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (i, x) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&x.to_be_bytes());
+    }
+    return digest;
+}
+
+pub fn hmac_sha1(key: &[u8], message: &[u8]) -> Vec<u8> {
+    const BLOCK_SIZE: usize = 64;
+    let mut key = if key.len() > BLOCK_SIZE { sha1(key).to_vec() } else { key.to_vec() };
+    key.resize(BLOCK_SIZE, 0);
+
+    let mut inner = key.iter().map(|k| k ^ 0x36).collect::<Vec<u8>>();
+    inner.extend_from_slice(message);
+    let mut outer = key.iter().map(|k| k ^ 0x5c).collect::<Vec<u8>>();
+    outer.extend_from_slice(&sha1(&inner));
+    return sha1(&outer).to_vec();
+}
@@ -1,8 +1,10 @@
 use std::io::Read;
 use serde::{Serialize, Deserialize};
 
+use crate::listcontentprovider::Action;
 use crate::listcontentprovider::ListContentProvider;
 use crate::listcontentprovider::ListItem;
+use crate::keymap::Command;
 
 use windows::core::*;
 use windows::Win32::UI::WindowsAndMessaging::*;
@@ -31,9 +33,21 @@ pub struct StartAppsProvider {
     apps: Vec<AppEntry>,
     // If query is a directory path, then this holds the list of
     // directory entry listings corresponding to the queried path.
-    directory_listing: Option<Vec<AppEntry>>,
-    directory_listing_path: Option<std::path::PathBuf>,
+    directory_listing: Vec<AppEntry>,
+    // The directory being listed, read once and filtered as the query changes.
+    directory_cache: crate::directorylisting::DirectoryCache,
+    show_hidden: bool,
+    // Entry highlighted last in DirectoryListing mode, or its directory for a file, for
+    // {selected_dir} in templates.
+    selected_directory: Option<std::path::PathBuf>,
     mode: StartAppsProviderMode,
+    templates: Vec<crate::template::LaunchTemplate>,
+    // Templates whose keyword matches the query, expanded for the query.
+    template_apps: Vec<AppEntry>,
+    // Directories recently opened or navigated into, most recent first.
+    recent_directories: Vec<String>,
+    // Recent directories matching the query.
+    recent_apps: Vec<AppEntry>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -157,6 +171,17 @@ impl ListItem for AppEntry {
     }
 }
 
+// Expand and split a typed command into path and params for ShellExecute.
+// Urls are passed through whole.
+fn parse_command(command: &str) -> Option<(String, String)> {
+    let command = crate::create_process::shell_expand(command);
+    if command.starts_with("http://") || command.starts_with("https://") {
+        return Some((command, String::new()));
+    }
+
+    return crate::cmdline::split_program(&command);
+}
+
 impl AppEntry {
     // Switch usually runs as elevated so that it can set foreground.
     fn start(&self) -> anyhow::Result<()> {
@@ -195,16 +220,9 @@ impl AppEntry {
                 }
             },
             AppEntryKind::Command { command } => {
-                let command = crate::create_process::shell_expand(command);
-                let args: Vec<String> = command.split(" ").map(String::from).collect();
-                if args.len() < 1 {
-                    return Ok(());
-                }
-
-                let (path, params) = if command.starts_with("http://") || command.starts_with("https://") {
-                    (command.clone(), String::new())
-                } else {
-                    (args[0].clone(), if args.len() > 1 { args[1..].join(" ") } else { "".to_string() })
+                let (path, params) = match parse_command(command) {
+                    Some(parsed) => parsed,
+                    None => return Ok(()),
                 };
 
                 unsafe {
@@ -368,16 +386,9 @@ impl AppEntry {
                     );
                 },
                 AppEntryKind::Command { command } => {
-                    let command = crate::create_process::shell_expand(command);
-                    let args: Vec<String> = command.split(" ").map(String::from).collect();
-                    if args.len() < 1 {
-                        return Ok(());
-                    }
-    
-                    let (path, params) = if command.starts_with("http://") || command.starts_with("https://") {
-                        (command.clone(), String::new())
-                    } else {
-                        (args[0].clone(), if args.len() > 1 { args[1..].join(" ") } else { "".to_string() })
+                    let (path, params) = match parse_command(command) {
+                        Some(parsed) => parsed,
+                        None => return Ok(()),
                     };
 
                     crate::trace!("start", log::Level::Error, "Start app medium: command {:?} {:?}", path, params);
@@ -402,6 +413,25 @@ impl AppEntry {
         }
     }
 
+    // Elevated starts with our token, otherwise start as the shell's medium integrity user.
+    pub fn launch(&self, elevated: bool) -> anyhow::Result<()> {
+        if elevated {
+            return self.start();
+        }
+        return self.start_medium();
+    }
+
+    // What the copy action puts on the clipboard, the path for files and the command or url otherwise.
+    pub fn copy_text(&self) -> String {
+        return match &self.kind {
+            AppEntryKind::Exe { path, .. } | AppEntryKind::Link { path, .. } => path.clone(),
+            AppEntryKind::Appx { identity_id, publisher_id, application_id, .. } => {
+                format!("shell:AppsFolder\\{}_{}!{}", identity_id, publisher_id, application_id)
+            },
+            AppEntryKind::Command { command } => crate::create_process::shell_expand(command),
+        };
+    }
+
     fn exact_match(&self, query: &str) -> bool {
         if self.name.eq(query) {
             return true;
@@ -450,7 +480,7 @@ impl From<&AppEntry> for String {
 }
 
 impl StartAppsProvider {
-    pub fn new() -> Box<Self> {
+    pub fn new(config: &crate::config::Config) -> Box<Self> {
         // let mut new = Box::new(StartAppsProvider {
         //     apps: vec![],
         //     query: "".into(),
@@ -463,8 +493,14 @@ impl StartAppsProvider {
             apps: Self::enumerate_start_apps().unwrap(),
             query: String::new(),
             mode: StartAppsProviderMode::StartApps,
-            directory_listing: None,
-            directory_listing_path: None,
+            directory_listing: vec![],
+            directory_cache: Default::default(),
+            show_hidden: false,
+            selected_directory: None,
+            templates: config.templates.clone(),
+            template_apps: vec![],
+            recent_directories: crate::directorylisting::load_recent(),
+            recent_apps: vec![],
         });
     }
 
@@ -669,71 +705,195 @@ impl StartAppsProvider {
     }
 
     fn query_directory(&mut self) -> Vec<&mut dyn ListItem> {
-        let maybe_dir_entry = std::path::Path::new(&self.query);
+        return self.directory_listing.iter_mut().map(|app| {
+            app as &mut dyn ListItem
+        }).collect::<Vec<&mut dyn ListItem>>();
+    }
 
-        let (path, query) = if self.query.ends_with("\\") && maybe_dir_entry.exists() {
-            (maybe_dir_entry.to_owned(), String::new())
-        } else if maybe_dir_entry.parent().map(|d| d.exists()).unwrap_or(false) {
-            (maybe_dir_entry.parent().unwrap().to_owned(), maybe_dir_entry.file_name().unwrap().to_str().unwrap_or("").to_owned())
-        } else {
-            // panic!("Bad directory, how did we get here? {:?}", self.query);
-            // return vec![];
-            ("".into(), "".into())
+    fn directory_app(path: String) -> AppEntry {
+        return AppEntry {
+            name: path.clone(),
+            kind: AppEntryKind::Exe {
+                path,
+                params: "".into(),
+            },
+            ..Default::default()
         };
+    }
 
-        let query_words = query.split(" ").collect::<Vec<_>>().len();
+    fn command_app(command: String) -> AppEntry {
+        return AppEntry {
+            name: command.clone(),
+            kind: AppEntryKind::Command {
+                command,
+            },
+            ..Default::default()
+        };
+    }
 
-        let query_app = if query_words > 1 {
-            Some(AppEntry {
-                name: self.query.clone(),
-                kind: AppEntryKind::Command {
-                    command: self.query.clone(),
-                },
-                ..Default::default()
-                // ..self.get_query_app().clone()
-            })
+    fn update_directory_listing(&mut self) {
+        let (path, filter) = crate::directorylisting::split_query(&self.query);
+
+        // Parameters after a path, only show the command. Under a drive or share that exists
+        // spaces are part of names like C:\Program Files, the command is shown if nothing matches.
+        let in_directory = crate::directorylisting::is_rooted(&self.query) && path.is_dir();
+        if !in_directory && filter.split(" ").count() > 1 {
+            self.directory_listing = vec![Self::command_app(self.query.clone())];
+            return;
+        }
+
+        crate::trace!("query", log::Level::Info, "update_directory_listing: {:?}, {:?}", path, filter);
+
+        // The directory itself goes first so enter on an unfiltered listing opens it.
+        self.directory_listing = if filter.len() == 0 && path.as_os_str().len() > 0 {
+            vec![Self::directory_app(path.to_string_lossy().into_owned())]
         } else {
-            None
+            vec![]
         };
 
-        crate::trace!("query", log::Level::Info, "query_directory query_app: {:?}", &query_app);
-
-        if self.directory_listing_path.is_none() && query_app.is_none() /* || self.directory_listing_path.as_ref().unwrap().to_str() != path.to_str() */ {
-            crate::trace!("query", log::Level::Info, "query_directory get_directory_listing: {:?}, {:?}", path, query);
-
-            self.directory_listing_path = Some(path.clone());
-            self.directory_listing = Some(crate::path::get_directory_listing(&*path, &*query).unwrap().iter().map(|p| {
-                let name = p.to_str().unwrap().to_owned();
-                // Checking if directory is really slow over file shares.
-                // if p.is_dir() && !name.ends_with("\\") {
-                //     name += "\\";
-                // }
-                return AppEntry {
-                    name: name.clone(),
-                    kind: AppEntryKind::Exe {
-                        path: name,
-                        params: "".into(),
-                    },
-                    ..Default::default()
-                };
-            }).collect::<Vec<AppEntry>>());
+        let show_hidden = self.show_hidden;
+        let entries = self.directory_cache.entries(&path);
+        self.directory_listing.extend(crate::directorylisting::filter_entries(entries, &filter, show_hidden).into_iter().map(|e| {
+            Self::directory_app(e.display_path())
+        }));
+        if self.directory_listing.len() == 0 && filter.split(" ").count() > 1 {
+            self.directory_listing.push(Self::command_app(self.query.clone()));
+        }
+    }
+
+    fn update_recent_apps(&mut self) {
+        self.recent_apps = crate::directorylisting::suggest_recent(&self.recent_directories, &self.query, 5).into_iter().map(|d| {
+            Self::directory_app(d.clone())
+        }).collect();
+    }
+
+    // Remember the directory of a started entry to suggest it later.
+    fn record_recent_directory(&mut self, app: &AppEntry) {
+        let path = match &app.kind {
+            AppEntryKind::Exe { path, .. } => std::path::PathBuf::from(path),
+            _ => return,
+        };
+
+        let dir = if path.to_string_lossy().ends_with(crate::directorylisting::is_separator) || path.is_dir() {
+            path
+        } else if let StartAppsProviderMode::DirectoryListing = self.mode {
+            match path.parent() {
+                Some(parent) => parent.to_owned(),
+                None => return,
+            }
         } else {
-            if let Some(app) = query_app {
-                self.directory_listing = Some(vec![app]);
+            return;
+        };
+
+        let dir = dir.to_string_lossy().into_owned();
+        crate::directorylisting::push_recent(&mut self.recent_directories, &dir, crate::directorylisting::MAX_RECENT_DIRECTORIES);
+        if let Err(e) = crate::directorylisting::record_recent(&dir) {
+            crate::trace!("start", log::Level::Error, "Failed to record recent directory: {:?}", e);
+        }
+    }
+
+    fn selected_app(&mut self, filtered_index: usize) -> Option<AppEntry> {
+        let mut apps = self.query_for_items();
+        if filtered_index >= apps.len() {
+            return None;
+        }
+        return Some(apps[filtered_index].as_mut_any().downcast_mut::<AppEntry>().unwrap().clone());
+    }
+
+    fn update_template_apps(&mut self) {
+        self.template_apps.clear();
+
+        for template in self.templates.iter() {
+            let query = match template.match_query(&self.query) {
+                Some(query) => query,
+                None => continue,
+            };
+
+            let command = Self::expand_template(template, query, &self.selected_directory, false);
+
+            crate::trace!("query", log::Level::Info, "update_template_apps {}: {}", template.keyword, command);
+
+            self.template_apps.push(AppEntry {
+                // The name is used as key in history so include the query.
+                name: format!("{}: {}", template.display_name(), query),
+                kind: AppEntryKind::Command { command },
+                ..Default::default()
+            });
+        }
+    }
+
+    // The clipboard is only read when the template is started, reading it on every key is
+    // slow and it can change before then. Until then {clipboard} is left in the command.
+    fn expand_template(template: &crate::template::LaunchTemplate, query: &str, selected_directory: &Option<std::path::PathBuf>, read_clipboard: bool) -> String {
+        let mut clipboard = None;
+        return template.expand(&mut |name| {
+            match name {
+                "query" => Some(query.to_owned()),
+                "clipboard" if read_clipboard => Some(clipboard.get_or_insert_with(crate::clipboard::get_text).clone()),
+                "selected_dir" => selected_directory.as_ref().map(|d| d.to_string_lossy().into_owned()),
+                _ => None,
             }
+        });
+    }
+
+    // The app at filtered_index as it's started, templates are listed first in the order they
+    // match and get the clipboard now.
+    fn launched_app(&mut self, filtered_index: usize) -> Option<AppEntry> {
+        let mut app = self.selected_app(filtered_index)?;
+        if filtered_index < self.template_apps.len() {
+            let query = &self.query;
+            let (template, rest) = self.templates.iter()
+                .filter_map(|template| template.match_query(query).map(|rest| (template, rest)))
+                .nth(filtered_index)?;
+            app.kind = AppEntryKind::Command { command: Self::expand_template(template, rest, &self.selected_directory, true) };
         }
-        return self.directory_listing.as_mut().unwrap().iter_mut().map(|app| {
-            app as &mut dyn ListItem
-        }).collect::<Vec<&mut dyn ListItem>>();
+        return Some(app);
     }
 
     fn clear_directory_listing(&mut self) {
-        self.directory_listing = None;
-        self.directory_listing_path = None;
+        self.directory_listing.clear();
+        self.directory_cache.clear();
     }
 }
 
 impl ListContentProvider for StartAppsProvider {
+    fn name(&self) -> &str {
+        return "Apps";
+    }
+
+    fn qualifiers(&self) -> Vec<(String, String)> {
+        let mut qualifiers = vec![
+            ("C:\\path\\".to_string(), "list a directory".to_string()),
+            ("https://...".to_string(), "open a url".to_string()),
+        ];
+        qualifiers.extend(self.templates.iter().map(|template| (format!("{} <args>", template.keyword), template.display_name().to_string())));
+        return qualifiers;
+    }
+
+    // Directory listings and urls are made from the query.
+    fn total(&self) -> Option<usize> {
+        return match self.mode {
+            StartAppsProviderMode::StartApps => Some(self.apps.len()),
+            _ => None,
+        };
+    }
+
+    fn mode(&self) -> Option<&str> {
+        return Some(match self.mode {
+            StartAppsProviderMode::StartApps => "apps",
+            StartAppsProviderMode::DirectoryListing => "directory",
+            StartAppsProviderMode::Url => "url",
+        });
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return match self.mode {
+            StartAppsProviderMode::StartApps => vec![(Command::Accept, "start"), (Command::AcceptElevated, "as admin"), (Command::Remove, "forget"), (Command::Copy, "copy")],
+            StartAppsProviderMode::DirectoryListing => vec![(Command::Accept, "open"), (Command::Complete, "complete"), (Command::ToggleHidden, "hidden"), (Command::Copy, "copy path")],
+            StartAppsProviderMode::Url => vec![(Command::Accept, "open"), (Command::Copy, "copy url")],
+        };
+    }
+
     // type ListItem = AppEntry;
 
     // // fn query_for_items(&self) -> Vec<&AppEntry> {
@@ -829,7 +989,10 @@ impl ListContentProvider for StartAppsProvider {
             result.remove(0);
         }
 
-        let result = result.into_iter().map(|app| {
+        // Matching templates go first since typing their keyword is asking for them.
+        let result = self.template_apps.iter_mut()
+            .chain(self.recent_apps.iter_mut())
+            .chain(result.into_iter()).map(|app| {
             app as &mut dyn ListItem
         }).collect::<Vec<&mut dyn ListItem>>();
         return result;
@@ -850,9 +1013,7 @@ impl ListContentProvider for StartAppsProvider {
         // }
         self.query = query;
 
-        if self.query.starts_with("~") {
-            self.query = std::env::var("USERPROFILE").unwrap_or("~".into()) + &self.query[1..];
-        }
+        self.query = crate::expand::expand_tilde(&self.query);
 
         let maybe_dir_entry = std::path::Path::new(&self.query);
 
@@ -863,7 +1024,6 @@ impl ListContentProvider for StartAppsProvider {
             // Fall through to remember if we're in DirectoryListing mode.
             crate::trace!("query", log::Level::Info, "set_query AppEntryKind::DirEntry: {}", &self.query);
             if (maybe_dir_entry.is_absolute() && maybe_dir_entry.exists()) || maybe_dir_entry.parent().map(|d| d.exists()).unwrap_or(false) {
-                self.clear_directory_listing();
                 self.mode = StartAppsProviderMode::DirectoryListing;
             }
         } else if self.query.starts_with("http:") || self.query.starts_with("https:") {
@@ -883,23 +1043,29 @@ impl ListContentProvider for StartAppsProvider {
         } else {
             // self.apps[0].name = self.query.clone();
         }
+
+        if let StartAppsProviderMode::DirectoryListing = self.mode {
+            self.update_directory_listing();
+            self.recent_apps.clear();
+        } else {
+            self.update_recent_apps();
+        }
+
+        self.update_template_apps();
     }
 
     fn start(&mut self, filtered_index: usize, elevated: bool) {
-        let mut apps = self.query_for_items();
-        let app = apps[filtered_index].as_mut_any().downcast_mut::<AppEntry>().unwrap();
+        let app = match self.launched_app(filtered_index) {
+            Some(app) => app,
+            None => return,
+        };
 
-        Self::update_history(&*app);
+        Self::update_history(&app);
+        self.record_recent_directory(&app);
 
         crate::trace!("start", log::Level::Info, "Start app elevated {:?}: {:?}", elevated, app);
 
-        let start_method = if elevated {
-            AppEntry::start
-        } else {
-            AppEntry::start_medium
-        };
-
-        if let Err(e) = start_method(app) {
+        if let Err(e) = app.launch(elevated) {
             crate::trace!("start", log::Level::Info, "Start app error: {:?}", e);
         }
     }
@@ -926,4 +1092,84 @@ impl ListContentProvider for StartAppsProvider {
             }
         }
     }
+
+    // In directory mode complete the common prefix of the matching entries.
+    fn complete(&mut self, _filtered_index: usize) -> Option<String> {
+        if let StartAppsProviderMode::DirectoryListing = self.mode {
+            let (path, _) = crate::directorylisting::split_query(&self.query);
+            let entries = self.directory_cache.entries(&path);
+            return crate::directorylisting::complete(&self.query, entries, self.show_hidden);
+        }
+        return None;
+    }
+
+    // Enter on a directory in the listing descends into it, except for the listed directory itself.
+    fn navigate(&mut self, filtered_index: usize) -> Option<String> {
+        if let StartAppsProviderMode::DirectoryListing = self.mode {
+            let app = self.selected_app(filtered_index)?;
+            let (path, _) = crate::directorylisting::split_query(&self.query);
+            if app.name.ends_with(crate::directorylisting::is_separator) && std::path::Path::new(&app.name) != path {
+                self.record_recent_directory(&app);
+                return Some(app.name);
+            }
+        }
+        return None;
+    }
+
+    fn backspace_query(&mut self) -> Option<String> {
+        if let StartAppsProviderMode::DirectoryListing = self.mode {
+            return crate::directorylisting::ascend(&self.query);
+        }
+        return None;
+    }
+
+    // A directory highlighted in the listing is {selected_dir} for templates typed after it.
+    fn highlight(&mut self, filtered_index: Option<usize>) {
+        if let StartAppsProviderMode::DirectoryListing = self.mode {
+            let path = match filtered_index.and_then(|i| self.selected_app(i)).map(|app| app.kind) {
+                Some(AppEntryKind::Exe { path, .. }) => std::path::PathBuf::from(path),
+                _ => return,
+            };
+            self.selected_directory = if path.is_dir() { Some(path) } else { path.parent().map(|parent| parent.to_owned()) };
+        }
+    }
+
+    fn toggle_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        if let StartAppsProviderMode::DirectoryListing = self.mode {
+            self.update_directory_listing();
+        }
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.launched_app(filtered_index).map(|app| app.copy_text());
+    }
+
+    // In directory mode the selected entry can be shown in explorer or get a terminal.
+    fn run_action(&mut self, filtered_index: usize, action: Action, elevated: bool) -> bool {
+        if let StartAppsProviderMode::DirectoryListing = self.mode {
+            let path = match self.selected_app(filtered_index).map(|app| app.kind) {
+                Some(AppEntryKind::Exe { path, .. }) => path,
+                _ => return false,
+            };
+            let result = match action {
+                Action::OpenFolder => crate::open::open_folder(&path, elevated),
+                Action::OpenTerminal => crate::open::open_terminal(&path, elevated),
+                _ => return false,
+            };
+            if let Err(e) = result {
+                crate::trace!("start", log::Level::Error, "{:?} error: {:?}", action, e);
+            }
+            return true;
+        }
+        return false;
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return match self.mode {
+            StartAppsProviderMode::StartApps => vec![(Command::Accept, "start"), (Command::AcceptElevated, "start as admin"), (Command::Remove, "forget"), (Command::Copy, "copy command")],
+            StartAppsProviderMode::DirectoryListing => vec![(Command::Accept, "open"), (Command::AcceptElevated, "open as admin"), (Command::Complete, "complete"), (Command::OpenFolder, "open folder"), (Command::OpenTerminal, "open terminal"), (Command::ToggleHidden, "show hidden files"), (Command::Copy, "copy path")],
+            StartAppsProviderMode::Url => vec![(Command::Accept, "open"), (Command::Copy, "copy url")],
+        };
+    }
 }
\ No newline at end of file
@@ -0,0 +1,113 @@
+use crate::listcontentprovider::{ListContentProvider, ListItem};
+use crate::keymap::Command;
+use crate::symbols::Symbol;
+
+use crate::log::*;
+
+const MAX_RESULTS: usize = 100;
+
+pub struct SymbolEntry {
+    pub symbol: Symbol,
+}
+
+impl ListItem for SymbolEntry {
+    fn as_any(&self) -> &dyn std::any::Any {
+        return self;
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        return self;
+    }
+
+    fn as_string(&self) -> String {
+        return format!("{}  {} ({})", self.symbol.text, self.symbol.name, self.symbol.codepoints());
+    }
+
+    fn as_matchable_string(&self) -> String {
+        return self.symbol.name.clone();
+    }
+}
+
+// Emoji and symbol picker, enter copies the symbol and ctrl+enter types it into the
+// window switch was opened over.
+pub struct SymbolProvider {
+    symbols: Vec<Symbol>,
+    query: String,
+    results: Vec<SymbolEntry>,
+}
+
+impl SymbolProvider {
+    pub fn new() -> Box<Self> {
+        return Box::new(SymbolProvider {
+            symbols: crate::symbols::symbols(),
+            query: String::new(),
+            results: vec![],
+        });
+    }
+}
+
+impl ListContentProvider for SymbolProvider {
+    fn name(&self) -> &str {
+        return "Symbols";
+    }
+
+    fn qualifiers(&self) -> Vec<(String, String)> {
+        return vec![("U+2713".to_string(), "a symbol by code point".to_string())];
+    }
+
+    fn total(&self) -> Option<usize> {
+        return Some(self.symbols.len());
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "copy"), (Command::AcceptElevated, "type")];
+    }
+
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        return self.results.iter_mut().map(|r| r as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        return self.results.iter().map(|r| r.as_string()).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.results = crate::symbols::search(&self.symbols, &self.query, MAX_RESULTS).into_iter()
+            .map(|symbol| SymbolEntry { symbol: symbol.clone() })
+            .collect();
+    }
+
+    // Elevated is ctrl+enter, which types the symbol instead of copying it.
+    fn start(&mut self, filtered_index: usize, elevated: bool) {
+        let symbol = match self.results.get(filtered_index) {
+            Some(entry) => &entry.symbol,
+            None => return,
+        };
+
+        crate::trace!("start", log::Level::Info, "Symbol {} type {:?}", symbol.codepoints(), elevated);
+        let result = if elevated {
+            crate::sendinput::type_text_after_exit(&symbol.text)
+        } else {
+            crate::clipboard::set_text(&symbol.text)
+        };
+        if let Err(e) = result {
+            crate::trace!("start", log::Level::Error, "Symbol error: {:?}", e);
+        }
+    }
+
+    fn remove(&mut self, _filtered_index: usize) {
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.results.get(filtered_index).map(|r| r.symbol.text.clone());
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        return Some(":");
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "copy"), (Command::AcceptElevated, "type")];
+    }
+}
@@ -0,0 +1,99 @@
+// Emoji and Unicode symbols searchable by name, from src/data/symbols.txt which is
+// compiled into the binary.
+
+pub const SYMBOLS_DATA: &str = include_str!("data/symbols.txt");
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub text: String,
+    pub name: String,
+    pub keywords: String,
+}
+
+impl Symbol {
+    // U+2713 style code points, space separated for sequences.
+    pub fn codepoints(&self) -> String {
+        return self.text.chars().map(|c| format!("U+{:04X}", c as u32)).collect::<Vec<String>>().join(" ");
+    }
+}
+
+// Lines are code points, name and keywords separated by tabs. Code points are hex,
+// space separated for sequences. Lines starting with # are comments.
+pub fn parse_symbols(data: &str) -> Vec<Symbol> {
+    return data.lines().filter_map(|line| {
+        if line.trim().len() == 0 || line.starts_with('#') {
+            return None;
+        }
+
+        let mut fields = line.split('\t');
+        let text = fields.next()?.split_whitespace()
+            .map(|hex| u32::from_str_radix(hex, 16).ok().and_then(char::from_u32))
+            .collect::<Option<String>>()?;
+        let name = fields.next()?.trim().to_owned();
+        let keywords = fields.next().unwrap_or("").trim().to_owned();
+
+        return Some(Symbol { text, name, keywords });
+    }).collect();
+}
+
+pub fn symbols() -> Vec<Symbol> {
+    return parse_symbols(SYMBOLS_DATA);
+}
+
+fn split_words(words: &str) -> impl Iterator<Item = &str> {
+    return words.split(|c: char| c.is_whitespace() || c == '-').filter(|w| w.len() > 0);
+}
+
+// How well a query word matches the words of a name, None if it doesn't.
+fn word_score(word: &str, words: &str, exact: i32, prefix: i32) -> Option<i32> {
+    return split_words(words)
+        .filter_map(|w| {
+            if w == word {
+                Some(exact)
+            } else if w.starts_with(word) {
+                Some(prefix)
+            } else {
+                None
+            }
+        })
+        .max();
+}
+
+// Every query word has to start a word of the name or keywords. Whole words beat prefixes,
+// which count more in the name than in keywords. Each word of the name the query doesn't
+// mention counts against it, so arrow right prefers rightwards arrow over left right arrow.
+// Shorter names win ties. The query can also be the symbol itself or its code point like U+2713.
+pub fn search<'a>(symbols: &'a [Symbol], query: &str, max_results: usize) -> Vec<&'a Symbol> {
+    let query = query.trim().to_lowercase();
+    if query.len() == 0 {
+        return vec![];
+    }
+
+    let codepoint = query.strip_prefix("u+")
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .and_then(char::from_u32);
+
+    let mut scored = symbols.iter().filter_map(|symbol| {
+        if symbol.text == query || codepoint.map(|c| symbol.text.starts_with(c)).unwrap_or(false) {
+            return Some((i32::MAX, symbol));
+        }
+
+        let mut total = 0;
+        for word in query.split_whitespace() {
+            let name_score = word_score(word, &symbol.name, 8, 4);
+            let keyword_score = word_score(word, &symbol.keywords, 8, 2);
+            total += name_score.max(keyword_score)?;
+        }
+        let unmatched = split_words(&symbol.name)
+            .filter(|w| !query.split_whitespace().any(|word| w.starts_with(word)))
+            .count();
+        total -= unmatched as i32;
+        return Some((total, symbol));
+    }).collect::<Vec<(i32, &Symbol)>>();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score.cmp(a_score).then(a.name.len().cmp(&b.name.len()))
+    });
+
+    return scored.into_iter().take(max_results).map(|(_, s)| s).collect();
+}
@@ -0,0 +1,109 @@
+use serde::{Serialize, Deserialize};
+
+// A user defined command with placeholders, for example
+// { "keyword": "gh", "command": "https://github.com/search?q={query}" }
+// so typing "gh rust tui" offers to open the search.
+//
+// Placeholders:
+// {query} is what was typed after the keyword.
+// {clipboard} is the text on the clipboard when the template is started.
+// {selected_dir} is the directory highlighted last in directory mode, or the directory of
+// the file highlighted.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LaunchTemplate {
+    pub keyword: String,
+    pub command: String,
+    // Shown in the list instead of the keyword.
+    #[serde(default)]
+    pub name: String,
+}
+
+impl LaunchTemplate {
+    pub fn display_name(&self) -> &str {
+        if self.name.len() > 0 {
+            return &self.name;
+        }
+        return &self.keyword;
+    }
+
+    // If query invokes this template returns the rest of the query after the keyword.
+    pub fn match_query<'a>(&self, query: &'a str) -> Option<&'a str> {
+        let query = query.trim_start();
+        let (keyword, rest) = match query.find(char::is_whitespace) {
+            Some(i) => (&query[..i], query[i..].trim_start()),
+            None => (query, ""),
+        };
+
+        if self.keyword.len() > 0 && keyword.eq_ignore_ascii_case(&self.keyword) {
+            return Some(rest);
+        }
+        return None;
+    }
+
+    // Substitute placeholders with values from lookup. Values are percent encoded if the
+    // command is a url, otherwise everything but {query}, which is already command line
+    // text typed by the user, is quoted as a single argument.
+    pub fn expand(&self, lookup: &mut dyn FnMut(&str) -> Option<String>) -> String {
+        let is_url = self.command.starts_with("http://") || self.command.starts_with("https://");
+
+        return expand_placeholders(&self.command, &mut |name| {
+            let value = lookup(name)?;
+            if is_url {
+                return Some(url_encode(&value));
+            } else if name == "query" {
+                return Some(value);
+            }
+            return Some(crate::cmdline::quote(&value));
+        });
+    }
+}
+
+// Replace {name} in text with lookup(name). Unknown placeholders are left as they are
+// and {{ and }} produce literal braces.
+pub fn expand_placeholders(text: &str, lookup: &mut dyn FnMut(&str) -> Option<String>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(i) = rest.find(|c| c == '{' || c == '}') {
+        result.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            result.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+
+        if rest.starts_with('{') {
+            if let Some(end) = rest.find('}') {
+                let name = &rest[1..end];
+                if !name.contains('{') {
+                    if let Some(value) = lookup(name) {
+                        result.push_str(&value);
+                        rest = &rest[end + 1..];
+                        continue;
+                    }
+                }
+            }
+        }
+
+        result.push_str(&rest[..1]);
+        rest = &rest[1..];
+    }
+
+    result.push_str(rest);
+    return result;
+}
+
+// Percent encode everything except the unreserved characters of RFC 3986.
+pub fn url_encode(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for b in text.bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.~".contains(&b) {
+            result.push(b as char);
+        } else {
+            result.push_str(&format!("%{:02X}", b));
+        }
+    }
+    return result;
+}
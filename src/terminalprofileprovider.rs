@@ -0,0 +1,147 @@
+use crate::listcontentprovider::{Action, ListContentProvider, ListItem};
+use crate::keymap::Command;
+use crate::terminalprofiles::TerminalProfile;
+
+use crate::log::*;
+
+pub struct TerminalProfileEntry {
+    pub profile: TerminalProfile,
+}
+
+impl ListItem for TerminalProfileEntry {
+    fn as_any(&self) -> &dyn std::any::Any {
+        return self;
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        return self;
+    }
+
+    fn as_string(&self) -> String {
+        let mut text = self.profile.name.clone();
+        if self.profile.default {
+            text += " (default)";
+        }
+        if let Some(detail) = self.profile.commandline.as_ref().or(self.profile.source.as_ref()) {
+            text += "  ";
+            text += detail;
+        }
+        return text;
+    }
+
+    fn as_matchable_string(&self) -> String {
+        return self.profile.name.clone();
+    }
+}
+
+// Windows Terminal profiles, enter opens the profile in a new window and alt+t in a new tab
+// of the quake window.
+pub struct TerminalProfileProvider {
+    profiles: Vec<TerminalProfile>,
+    query: String,
+    filtered: Vec<TerminalProfileEntry>,
+}
+
+impl TerminalProfileProvider {
+    pub fn new() -> Box<Self> {
+        return Self::with_profiles(crate::terminalprofiles::load());
+    }
+
+    pub fn with_profiles(profiles: Vec<TerminalProfile>) -> Box<Self> {
+        let mut provider = Box::new(TerminalProfileProvider {
+            profiles,
+            query: String::new(),
+            filtered: vec![],
+        });
+        provider.filter();
+        return provider;
+    }
+
+    fn filter(&mut self) {
+        let mut scored = crate::terminalprofiles::visible_profiles(&self.profiles).into_iter()
+            .enumerate()
+            .filter_map(|(i, profile)| {
+                if self.query.trim().len() == 0 {
+                    return Some((0, i, profile));
+                }
+                let detail = profile.source.as_deref().unwrap_or("");
+                let score = crate::fuzzy::score(self.query.trim(), &profile.name)
+                    .or_else(|| crate::fuzzy::score(self.query.trim(), detail));
+                return score.map(|score| (score, i, profile));
+            })
+            .collect::<Vec<(i32, usize, &TerminalProfile)>>();
+        // Settings order for ties, which is the order of the new tab menu.
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        self.filtered = scored.into_iter()
+            .map(|(_, _, profile)| TerminalProfileEntry { profile: profile.clone() })
+            .collect();
+    }
+
+    fn open(&self, filtered_index: usize, quake: bool, elevated: bool) -> bool {
+        let profile = match self.filtered.get(filtered_index) {
+            Some(entry) => &entry.profile,
+            None => return false,
+        };
+
+        let args = profile.wt_args(quake);
+        crate::trace!("start", log::Level::Info, "wt {}", args);
+        if let Err(e) = crate::open::windows_terminal(args, elevated) {
+            crate::trace!("start", log::Level::Error, "Open terminal profile error: {:?}", e);
+        }
+        return true;
+    }
+}
+
+impl ListContentProvider for TerminalProfileProvider {
+    fn name(&self) -> &str {
+        return "Terminal";
+    }
+
+    fn total(&self) -> Option<usize> {
+        return Some(self.profiles.len());
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "open"), (Command::OpenTerminal, "quake")];
+    }
+
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        return self.filtered.iter_mut().map(|e| e as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        return self.filtered.iter().map(|e| e.as_string()).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.filter();
+    }
+
+    fn start(&mut self, filtered_index: usize, elevated: bool) {
+        self.open(filtered_index, false, elevated);
+    }
+
+    fn remove(&mut self, _filtered_index: usize) {
+    }
+
+    fn run_action(&mut self, filtered_index: usize, action: Action, elevated: bool) -> bool {
+        return match action {
+            Action::OpenTerminal => self.open(filtered_index, true, elevated),
+            _ => false,
+        };
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.filtered.get(filtered_index).map(|e| format!("wt {}", e.profile.wt_args(false)));
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        return Some(">");
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "open"), (Command::AcceptElevated, "open as admin"), (Command::OpenTerminal, "open in quake window"), (Command::Copy, "copy command")];
+    }
+}
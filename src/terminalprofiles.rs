@@ -0,0 +1,243 @@
+// Windows Terminal profiles read from its settings.json and the json fragments extensions
+// install, for the terminal profile provider.
+//
+// settings.json allows comments and trailing commas. Profiles are either an array or an
+// object with the array in list. Profiles generated by dynamic profile sources like WSL
+// have a source, and disabledProfileSources hides all profiles of a source.
+
+use crate::log::*;
+
+const PACKAGED_SETTINGS: [&str; 2] = [
+    "%LOCALAPPDATA%\\Packages\\Microsoft.WindowsTerminal_8wekyb3d8bbwe\\LocalState\\settings.json",
+    "%LOCALAPPDATA%\\Packages\\Microsoft.WindowsTerminalPreview_8wekyb3d8bbwe\\LocalState\\settings.json",
+];
+const UNPACKAGED_SETTINGS: &str = "%LOCALAPPDATA%\\Microsoft\\Windows Terminal\\settings.json";
+const FRAGMENT_DIRECTORIES: [&str; 2] = [
+    "%LOCALAPPDATA%\\Microsoft\\Windows Terminal\\Fragments",
+    "%ProgramData%\\Microsoft\\Windows Terminal\\Fragments",
+];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TerminalProfile {
+    pub name: String,
+    pub guid: Option<String>,
+    // Dynamic profile source like Windows.Terminal.Wsl, or the extension of a fragment.
+    pub source: Option<String>,
+    pub commandline: Option<String>,
+    pub hidden: bool,
+    pub default: bool,
+}
+
+impl TerminalProfile {
+    // Arguments for wt.exe to open the profile in a new window, or a new tab of the
+    // quake window.
+    pub fn wt_args(&self, quake: bool) -> String {
+        // wt splits its command line into subcommands at ;, even in quotes.
+        let name = crate::cmdline::quote_windows(&self.name).replace(';', "\\;");
+        if quake {
+            return format!("-w _quake nt -p {}", name);
+        }
+        return format!("-p {}", name);
+    }
+}
+
+// Removes // and /* */ comments and trailing commas so serde_json can read jsonc.
+pub fn strip_jsonc(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    // Index in result of a comma that is trailing if a } or ] comes next.
+    let mut comma: Option<usize> = None;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                comma = None;
+                result.push(c);
+                while let Some(c) = chars.next() {
+                    result.push(c);
+                    if c == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            result.push(escaped);
+                        }
+                    } else if c == '"' {
+                        break;
+                    }
+                }
+            },
+            '/' if chars.peek() == Some(&'/') => {
+                while let Some(&c) = chars.peek() {
+                    if c == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            },
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                // Keep tokens on either side apart.
+                result.push(' ');
+            },
+            ',' => {
+                comma = Some(result.len());
+                result.push(c);
+            },
+            '}' | ']' => {
+                if let Some(i) = comma.take() {
+                    result.replace_range(i..i + 1, " ");
+                }
+                result.push(c);
+            },
+            c if c.is_whitespace() => result.push(c),
+            c => {
+                comma = None;
+                result.push(c);
+            },
+        }
+    }
+
+    return result;
+}
+
+fn string_field(profile: &serde_json::Value, name: &str) -> Option<String> {
+    return profile.get(name).and_then(|v| v.as_str()).map(|s| s.to_owned());
+}
+
+fn parse_profile(profile: &serde_json::Value) -> Option<TerminalProfile> {
+    return Some(TerminalProfile {
+        name: string_field(profile, "name")?,
+        guid: string_field(profile, "guid").map(|guid| guid.to_lowercase()),
+        source: string_field(profile, "source"),
+        commandline: string_field(profile, "commandline"),
+        hidden: profile.get("hidden").and_then(|v| v.as_bool()).unwrap_or(false),
+        default: false,
+    });
+}
+
+fn parse_profile_list(list: Option<&serde_json::Value>) -> Vec<TerminalProfile> {
+    return list.and_then(|list| list.as_array())
+        .map(|list| list.iter().filter_map(parse_profile).collect())
+        .unwrap_or_default();
+}
+
+// Every profile in settings.json, hidden ones included.
+pub fn parse_settings(text: &str) -> anyhow::Result<Vec<TerminalProfile>> {
+    let settings: serde_json::Value = serde_json::from_str(&strip_jsonc(text))?;
+
+    let profiles = settings.get("profiles");
+    let list = match profiles {
+        Some(serde_json::Value::Array(_)) => profiles,
+        Some(profiles) => profiles.get("list"),
+        None => None,
+    };
+    let mut profiles = parse_profile_list(list);
+
+    let disabled_sources = settings.get("disabledProfileSources")
+        .and_then(|v| v.as_array())
+        .map(|sources| sources.iter().filter_map(|s| s.as_str()).collect::<Vec<&str>>())
+        .unwrap_or_default();
+    let default_profile = string_field(&settings, "defaultProfile").map(|p| p.to_lowercase());
+
+    for profile in profiles.iter_mut() {
+        if profile.source.as_ref().map(|s| disabled_sources.contains(&s.as_str())).unwrap_or(false) {
+            profile.hidden = true;
+        }
+        // defaultProfile is a guid, or a name in older settings.
+        profile.default = default_profile.is_some()
+            && (profile.guid == default_profile || Some(profile.name.to_lowercase()) == default_profile);
+    }
+
+    return Ok(profiles);
+}
+
+// Profiles added by a fragment, source is the name of the directory the fragment is in.
+// Entries that only update other profiles are skipped.
+pub fn parse_fragment(text: &str, source: &str) -> anyhow::Result<Vec<TerminalProfile>> {
+    let fragment: serde_json::Value = serde_json::from_str(&strip_jsonc(text))?;
+    let list = match fragment.get("profiles").and_then(|list| list.as_array()) {
+        Some(list) => list,
+        None => return Ok(vec![]),
+    };
+    return Ok(list.iter()
+        .filter(|profile| profile.get("updates").is_none())
+        .filter_map(parse_profile)
+        .map(|profile| TerminalProfile { source: Some(source.to_owned()), ..profile })
+        .collect());
+}
+
+// Adds fragment profiles that settings.json doesn't have yet, settings.json wins for the
+// ones it already lists.
+pub fn merge_fragments(profiles: &mut Vec<TerminalProfile>, fragments: Vec<TerminalProfile>) {
+    for fragment in fragments {
+        let known = profiles.iter().any(|p| {
+            (p.guid.is_some() && p.guid == fragment.guid) || (p.name == fragment.name && p.source == fragment.source)
+        });
+        if !known {
+            profiles.push(fragment);
+        }
+    }
+}
+
+// Profiles that should be listed, in settings order.
+pub fn visible_profiles(profiles: &[TerminalProfile]) -> Vec<&TerminalProfile> {
+    return profiles.iter().filter(|p| !p.hidden).collect();
+}
+
+pub fn settings_path() -> Option<std::path::PathBuf> {
+    return PACKAGED_SETTINGS.iter()
+        .chain(std::iter::once(&UNPACKAGED_SETTINGS))
+        .map(|path| std::path::PathBuf::from(crate::expand::expand(path)))
+        .find(|path| path.is_file());
+}
+
+// Fragments are in Fragments\<extension>\<name>.json.
+fn load_fragments() -> Vec<TerminalProfile> {
+    let mut profiles = vec![];
+    for dir in FRAGMENT_DIRECTORIES.iter().map(|dir| crate::expand::expand(dir)) {
+        let extensions = match std::fs::read_dir(&dir) {
+            Ok(extensions) => extensions,
+            Err(_) => continue,
+        };
+
+        for extension in extensions.filter_map(|e| e.ok()) {
+            let source = extension.file_name().to_string_lossy().into_owned();
+            let files = match std::fs::read_dir(extension.path()) {
+                Ok(files) => files,
+                Err(_) => continue,
+            };
+            for file in files.filter_map(|e| e.ok()).filter(|f| f.path().extension().map(|e| e == "json").unwrap_or(false)) {
+                match std::fs::read_to_string(file.path()).map_err(anyhow::Error::from).and_then(|text| parse_fragment(&text, &source)) {
+                    Ok(fragment) => profiles.extend(fragment),
+                    Err(e) => {
+                        crate::trace!("terminal", log::Level::Error, "Failed to read fragment {:?}: {:?}", file.path(), e);
+                    }
+                }
+            }
+        }
+    }
+    return profiles;
+}
+
+// Profiles of the installed Windows Terminal, empty if its settings can't be read.
+pub fn load() -> Vec<TerminalProfile> {
+    let path = match settings_path() {
+        Some(path) => path,
+        None => return vec![],
+    };
+
+    let mut profiles = match std::fs::read_to_string(&path).map_err(anyhow::Error::from).and_then(|text| parse_settings(&text)) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            crate::trace!("terminal", log::Level::Error, "Failed to read {:?}: {:?}", path, e);
+            return vec![];
+        }
+    };
+    merge_fragments(&mut profiles, load_fragments());
+    return profiles;
+}
@@ -0,0 +1,273 @@
+use serde::{Serialize, Deserialize};
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use unicode_width::UnicodeWidthStr;
+
+use crate::log::*;
+
+// How the switch UI looks. A theme is one of the built in ones with whatever the theme
+// section of config.json sets on top, like {"name": "high-contrast", "prompt": "$ "}.
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InputPosition {
+    Top,
+    Bottom,
+}
+
+// A color name like lightgreen, #rrggbb or a 0-255 palette index.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct ThemeColor(pub Color);
+
+const COLOR_NAMES: &[(&str, Color)] = &[
+    ("reset", Color::Reset),
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("gray", Color::Gray),
+    ("darkgray", Color::DarkGray),
+    ("lightred", Color::LightRed),
+    ("lightgreen", Color::LightGreen),
+    ("lightyellow", Color::LightYellow),
+    ("lightblue", Color::LightBlue),
+    ("lightmagenta", Color::LightMagenta),
+    ("lightcyan", Color::LightCyan),
+    ("white", Color::White),
+];
+
+impl TryFrom<String> for ThemeColor {
+    type Error = anyhow::Error;
+
+    fn try_from(text: String) -> anyhow::Result<ThemeColor> {
+        let name = text.trim().to_lowercase().replace(|c| c == '_' || c == '-' || c == ' ', "");
+        if let Some((_, color)) = COLOR_NAMES.iter().find(|(n, _)| *n == name) {
+            return Ok(ThemeColor(*color));
+        }
+        if let Some(hex) = name.strip_prefix('#').filter(|hex| hex.len() == 6) {
+            let rgb = u32::from_str_radix(hex, 16)?;
+            return Ok(ThemeColor(Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)));
+        }
+        if let Ok(index) = name.parse::<u8>() {
+            return Ok(ThemeColor(Color::Indexed(index)));
+        }
+        anyhow::bail!("Unknown color {}", text);
+    }
+}
+
+impl From<ThemeColor> for String {
+    fn from(color: ThemeColor) -> String {
+        if let Some((name, _)) = COLOR_NAMES.iter().find(|(_, c)| *c == color.0) {
+            return name.to_string();
+        }
+        return match color.0 {
+            Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+            Color::Indexed(index) => index.to_string(),
+            _ => String::new(),
+        };
+    }
+}
+
+// Colors left out keep what is underneath, the terminal's or the normal style's.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ThemeStyle {
+    pub fg: Option<ThemeColor>,
+    pub bg: Option<ThemeColor>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reversed: bool,
+}
+
+impl ThemeStyle {
+    pub fn style(&self) -> Style {
+        let mut style = Style::default();
+        if let Some(fg) = self.fg {
+            style = style.fg(fg.0);
+        }
+        if let Some(bg) = self.bg {
+            style = style.bg(bg.0);
+        }
+        for (on, modifier) in [(self.bold, Modifier::BOLD), (self.italic, Modifier::ITALIC), (self.underline, Modifier::UNDERLINED), (self.reversed, Modifier::REVERSED)] {
+            if on {
+                style = style.add_modifier(modifier);
+            }
+        }
+        return style;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub normal: ThemeStyle,
+    // The selected row.
+    pub highlight: ThemeStyle,
+    // Characters of a row the query matched.
+    pub matched: ThemeStyle,
+    pub prompt: String,
+    // In front of marked rows, the other rows are indented to match while anything is marked.
+    pub marker: String,
+    pub borders: bool,
+    pub input_position: InputPosition,
+    // Provider tabs, counts and key hints next to the input line.
+    pub status_bar: bool,
+    // Lines the parts of rows separated by two spaces up in columns, like a process's name,
+    // pid and memory.
+    pub columns: bool,
+}
+
+// The theme section of config.json, everything left out comes from the named theme.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub name: Option<String>,
+    pub normal: Option<ThemeStyle>,
+    pub highlight: Option<ThemeStyle>,
+    pub matched: Option<ThemeStyle>,
+    pub prompt: Option<String>,
+    pub marker: Option<String>,
+    pub borders: Option<bool>,
+    pub input_position: Option<InputPosition>,
+    pub status_bar: Option<bool>,
+    pub columns: Option<bool>,
+}
+
+pub const THEMES: &[&str] = &["default", "high-contrast", "fzf"];
+
+fn color(color: Color) -> Option<ThemeColor> {
+    return Some(ThemeColor(color));
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        return Theme::named("default").unwrap();
+    }
+}
+
+impl Theme {
+    pub fn named(name: &str) -> anyhow::Result<Theme> {
+        // How switch always looked.
+        let default = Theme {
+            normal: ThemeStyle::default(),
+            highlight: ThemeStyle { bg: color(Color::LightGreen), bold: true, ..Default::default() },
+            matched: ThemeStyle::default(),
+            prompt: "> ".into(),
+            marker: "+ ".into(),
+            borders: false,
+            input_position: InputPosition::Top,
+            status_bar: true,
+            columns: false,
+        };
+
+        return match name {
+            "default" | "" => Ok(default),
+            // Black and white with yellow, matches are underlined so they still show on the
+            // selected row.
+            "high-contrast" => Ok(Theme {
+                normal: ThemeStyle { fg: color(Color::White), bg: color(Color::Black), ..Default::default() },
+                highlight: ThemeStyle { fg: color(Color::Black), bg: color(Color::Yellow), bold: true, ..Default::default() },
+                matched: ThemeStyle { fg: color(Color::LightYellow), bold: true, underline: true, ..Default::default() },
+                borders: true,
+                columns: true,
+                ..default
+            }),
+            "fzf" => Ok(Theme {
+                highlight: ThemeStyle { bg: color(Color::DarkGray), bold: true, ..Default::default() },
+                matched: ThemeStyle { fg: color(Color::LightGreen), ..Default::default() },
+                input_position: InputPosition::Bottom,
+                columns: true,
+                ..default
+            }),
+            _ => anyhow::bail!("Unknown theme {}, expected one of {}", name, THEMES.join(", ")),
+        };
+    }
+
+    pub fn from_config(config: &ThemeConfig) -> Theme {
+        let name = config.name.as_deref().unwrap_or("default");
+        let theme = Theme::named(name).unwrap_or_else(|e| {
+            crate::trace!("config", log::Level::Error, "{:?}", e);
+            Theme::default()
+        });
+
+        return Theme {
+            normal: config.normal.clone().unwrap_or(theme.normal),
+            highlight: config.highlight.clone().unwrap_or(theme.highlight),
+            matched: config.matched.clone().unwrap_or(theme.matched),
+            prompt: config.prompt.clone().unwrap_or(theme.prompt),
+            marker: config.marker.clone().unwrap_or(theme.marker),
+            borders: config.borders.unwrap_or(theme.borders),
+            input_position: config.input_position.unwrap_or(theme.input_position),
+            status_bar: config.status_bar.unwrap_or(theme.status_bar),
+            columns: config.columns.unwrap_or(theme.columns),
+        };
+    }
+
+    // The input line, the status bar and the list in area. The status bar is on the list's side of
+    // the input line and gives way to the list when there is only room for one of them.
+    pub fn layout(&self, area: Rect) -> (Rect, Rect, Rect) {
+        let inner = if self.borders {
+            tui::widgets::Block::default().borders(tui::widgets::Borders::ALL).inner(area)
+        } else {
+            area
+        };
+
+        let input_height = std::cmp::min(1, inner.height);
+        let status_height = if self.status_bar && inner.height >= 3 { 1 } else { 0 };
+        let list = Rect { height: inner.height - input_height - status_height, ..inner };
+        return match self.input_position {
+            InputPosition::Top => (
+                Rect { height: input_height, ..inner },
+                Rect { y: inner.y + input_height, height: status_height, ..inner },
+                Rect { y: inner.y + input_height + status_height, ..list },
+            ),
+            InputPosition::Bottom => (
+                Rect { y: inner.y + list.height + status_height, height: input_height, ..inner },
+                Rect { y: inner.y + list.height, height: status_height, ..inner },
+                list,
+            ),
+        };
+    }
+}
+
+// Pads the parts of each row separated by two spaces to the widest of that part in rows, ui passes
+// only the rows on screen. Leading spaces, like the indent of a tree, stay with the first part.
+pub fn align_columns(rows: &[String]) -> Vec<String> {
+    let split = |row: &String| {
+        let indent = row.len() - row.trim_start_matches(' ').len();
+        let mut parts = row[indent..].split("  ").map(|part| part.to_owned()).collect::<Vec<String>>();
+        parts[0].insert_str(0, &row[..indent]);
+        return parts;
+    };
+    let rows = rows.iter().map(split).collect::<Vec<Vec<String>>>();
+
+    let mut widths: Vec<usize> = vec![];
+    for parts in &rows {
+        for (i, part) in parts.iter().enumerate() {
+            let width = part.width();
+            match widths.get_mut(i) {
+                Some(w) => *w = std::cmp::max(*w, width),
+                None => widths.push(width),
+            }
+        }
+    }
+
+    return rows.into_iter()
+        .map(|parts| {
+            let last = parts.len() - 1;
+            return parts.into_iter().enumerate()
+                .map(|(i, part)| {
+                    if i == last {
+                        return part;
+                    }
+                    let padding = widths[i] - part.width();
+                    return part + &" ".repeat(padding + 2);
+                })
+                .collect::<String>();
+        })
+        .collect();
+}
@@ -0,0 +1,136 @@
+use crate::listcontentprovider::{ListContentProvider, ListItem};
+use crate::keymap::Command;
+use crate::startappsprovider::{AppEntry, AppEntryKind};
+use crate::template::LaunchTemplate;
+
+use crate::log::*;
+
+// Bang style web searches, "g rust tui" searches google for "rust tui".
+// If the first word isn't a keyword then every engine is listed with the whole
+// query so the engine can be picked from the list.
+pub struct WebSearchProvider {
+    query: String,
+    engines: Vec<LaunchTemplate>,
+    results: Vec<AppEntry>,
+}
+
+fn engine(keyword: &str, name: &str, url: &str) -> LaunchTemplate {
+    return LaunchTemplate {
+        keyword: keyword.into(),
+        command: url.into(),
+        name: name.into(),
+    };
+}
+
+// Used when config.json doesn't have search_engines.
+pub fn default_search_engines() -> Vec<LaunchTemplate> {
+    return vec![
+        engine("g", "Google", "https://www.google.com/search?q={query}"),
+        engine("ddg", "DuckDuckGo", "https://duckduckgo.com/?q={query}"),
+        engine("b", "Bing", "https://www.bing.com/search?q={query}"),
+        engine("w", "Wikipedia", "https://en.wikipedia.org/wiki/Special:Search?search={query}"),
+        engine("docs", "docs.rs", "https://docs.rs/releases/search?query={query}"),
+        engine("crates", "crates.io", "https://crates.io/search?q={query}"),
+        engine("gh", "GitHub", "https://github.com/search?q={query}"),
+    ];
+}
+
+// Entries for query, each a Command with the resolved url.
+pub fn search_entries(engines: &[LaunchTemplate], query: &str) -> Vec<AppEntry> {
+    let matched = engines.iter()
+        .filter_map(|e| e.match_query(query).map(|terms| (e, terms.to_owned())))
+        .collect::<Vec<(&LaunchTemplate, String)>>();
+
+    let matched = if matched.len() > 0 {
+        matched
+    } else {
+        engines.iter().map(|e| (e, query.trim().to_owned())).collect()
+    };
+
+    return matched.into_iter().map(|(engine, terms)| {
+        let url = engine.expand(&mut |name| {
+            match name {
+                "query" => Some(terms.clone()),
+                _ => None,
+            }
+        });
+
+        AppEntry {
+            name: format!("{}: {}", engine.display_name(), terms),
+            kind: AppEntryKind::Command { command: url },
+            ..Default::default()
+        }
+    }).collect();
+}
+
+impl WebSearchProvider {
+    pub fn new(config: &crate::config::Config) -> Box<Self> {
+        return Self::with_engines(config.search_engines.clone());
+    }
+
+    pub fn with_engines(engines: Vec<LaunchTemplate>) -> Box<Self> {
+        let results = search_entries(&engines, "");
+        return Box::new(WebSearchProvider {
+            query: String::new(),
+            engines,
+            results,
+        });
+    }
+}
+
+impl ListContentProvider for WebSearchProvider {
+    fn name(&self) -> &str {
+        return "Web";
+    }
+
+    fn qualifiers(&self) -> Vec<(String, String)> {
+        return self.engines.iter()
+            .map(|engine| (format!("{} <terms>", engine.keyword), format!("search {}", engine.display_name())))
+            .collect();
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "search"), (Command::Copy, "copy url")];
+    }
+
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        return self.results.iter_mut().map(|r| r as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        return self.results.iter().map(String::from).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.results = search_entries(&self.engines, &self.query);
+    }
+
+    fn start(&mut self, filtered_index: usize, elevated: bool) {
+        if filtered_index >= self.results.len() {
+            return;
+        }
+
+        let entry = &self.results[filtered_index];
+        crate::trace!("start", log::Level::Info, "Web search elevated {:?}: {:?}", elevated, entry);
+        if let Err(e) = entry.launch(elevated) {
+            crate::trace!("start", log::Level::Info, "Web search error: {:?}", e);
+        }
+    }
+
+    // Engines come from config, nothing to remove.
+    fn remove(&mut self, _filtered_index: usize) {
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.results.get(filtered_index).map(|entry| entry.copy_text());
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        return Some("!");
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "search"), (Command::Copy, "copy url")];
+    }
+}
@@ -23,13 +23,6 @@ use windows::{
         OpenProcess,
         PROCESS_QUERY_LIMITED_INFORMATION,
     },
-    Win32::System::Diagnostics::ToolHelp::{
-        CreateToolhelp32Snapshot,
-        Process32First,
-        Process32Next,
-        TH32CS_SNAPPROCESS,
-        PROCESSENTRY32
-    },
 };
 use windows::Win32::UI::WindowsAndMessaging::*;
 use crate::{
@@ -37,6 +30,7 @@ use crate::{
     listcontentprovider::ListItem
 };
 use crate::listcontentprovider::ListContentProvider;
+use crate::keymap::Command;
 
 use crate::log::*;
 
@@ -159,37 +153,12 @@ pub fn enum_window() -> windows::core::Result<Vec<WindowInfo>> {
     }
 }
 
-pub fn getppid(pid: u32) -> u32 {
-    unsafe {
-        let mut pe32: PROCESSENTRY32 = std::mem::zeroed();
-
-        let mut ppid: u32 = u32::MAX;
-
-        let snapshot = CreateToolhelp32Snapshot( TH32CS_SNAPPROCESS, 0 );
-        if snapshot == INVALID_HANDLE_VALUE {
-            return u32::MAX
-        }
-
-        pe32.dwSize = std::mem::size_of::<PROCESSENTRY32>() as u32;
-        if !Process32First(snapshot, &mut pe32).as_bool() {
-            CloseHandle(snapshot);
-            return u32::MAX
-        }
-
-        loop {
-            if pe32.th32ProcessID == pid {
-                ppid = pe32.th32ParentProcessID;
-                break;
-            }
-            if !Process32Next(snapshot, &mut pe32).as_bool() {
-                break;
-            }
-        }
-
-        CloseHandle(snapshot);
-
-        return ppid;
-    }
+// Parent of pid in a snapshot of the processes, u32::MAX if it isn't in it.
+pub fn getppid(processes: &[crate::processes::ProcessInfo], pid: u32) -> u32 {
+    return processes.iter()
+        .find(|p| p.pid == pid)
+        .map(|p| p.parent)
+        .unwrap_or(u32::MAX);
 }
 
 pub struct WindowProvider {
@@ -200,8 +169,9 @@ pub struct WindowProvider {
 
 impl WindowProvider {
     pub fn new() -> Box<Self> {
-        let quakerun_pid = getppid(unsafe { GetCurrentProcessId() });
-        let terminal_host_pid = getppid(quakerun_pid);
+        let processes = crate::processes::snapshot().unwrap_or_default();
+        let quakerun_pid = getppid(&processes, unsafe { GetCurrentProcessId() });
+        let terminal_host_pid = getppid(&processes, quakerun_pid);
         Box::new(WindowProvider {
             windows: enum_window().unwrap(),
             query: "".into(),
@@ -211,6 +181,18 @@ impl WindowProvider {
 }
 
 impl ListContentProvider for WindowProvider {
+    fn name(&self) -> &str {
+        return "Windows";
+    }
+
+    fn total(&self) -> Option<usize> {
+        return Some(self.windows.len());
+    }
+
+    fn hints(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "switch"), (Command::Remove, "close"), (Command::Copy, "copy title")];
+    }
+
     // type ListItem = WindowInfo;
 
     // fn query_for_items(&self) -> Vec<&WindowInfo> {
@@ -268,4 +250,33 @@ impl ListContentProvider for WindowProvider {
         }
         self.windows =  enum_window().unwrap();
     }
+
+    // Closes them all before enumerating windows again, the indices don't hold after that.
+    fn remove_all(&mut self, filtered_indices: &[usize]) {
+        let windowhs = {
+            let windows = self.query_for_items();
+            filtered_indices.iter()
+                .filter(|&&i| i < windows.len())
+                .map(|&i| windows[i].as_any().downcast_ref::<WindowInfo>().unwrap().windowh)
+                .collect::<Vec<HWND>>()
+        };
+        for windowh in windowhs {
+            unsafe {
+                SendMessageW(windowh, WM_CLOSE, WPARAM(0), LPARAM(0));
+            }
+        }
+        self.windows =  enum_window().unwrap();
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        let windows = self.query_for_items();
+        if filtered_index >= windows.len() {
+            return None;
+        }
+        return Some(windows[filtered_index].as_any().downcast_ref::<WindowInfo>().unwrap().window_text.clone());
+    }
+
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        return vec![(Command::Accept, "switch to"), (Command::Remove, "close"), (Command::Copy, "copy title")];
+    }
 }
@@ -0,0 +1,33 @@
+use switch::actionsmenu::*;
+use switch::keymap::{Command, Keymap};
+
+// cargo test --test actionsmenu
+#[test]
+fn lines_and_moving() {
+    let mut keymap = Keymap::empty();
+    for (chord, command) in [("enter", "accept"), ("delete", "remove"), ("shift+delete", "kill-tree"), ("alt+w", "copy")] {
+        keymap.bind(chord, command).unwrap();
+    }
+    assert_eq!(ActionsMenu::new(&keymap, vec![]), None);
+
+    let actions = vec![(Command::Accept, "switch to"), (Command::KillTree, "kill"), (Command::OpenFolder, "open folder"), (Command::Copy, "copy")];
+    let mut menu = ActionsMenu::new(&keymap, actions).unwrap();
+    // Actions without a key are still listed.
+    assert_eq!(menu.lines, vec![
+        "switch to    enter",
+        "kill         shift+delete",
+        "open folder",
+        "copy         alt+w",
+    ]);
+
+    let cases: &[(isize, Command)] = &[
+        (0, Command::Accept),
+        (2, Command::OpenFolder),
+        (2, Command::Accept),
+        (-1, Command::Copy),
+    ];
+    for (lines, command) in cases {
+        menu.move_by(*lines);
+        assert_eq!(menu.command(), *command, "{}", lines);
+    }
+}
@@ -0,0 +1,131 @@
+use switch::bookmarks::*;
+use switch::bookmarkprovider::BookmarkProvider;
+use switch::ListContentProvider;
+
+fn bookmark(title: &str, url: &str, folder: &str) -> Bookmark {
+    return Bookmark {
+        title: title.to_owned(),
+        url: url.to_owned(),
+        folder: folder.to_owned(),
+    };
+}
+
+fn data(file: &str) -> std::path::PathBuf {
+    return std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join(file);
+}
+
+// cargo test --test bookmarks
+#[test]
+fn chromium() {
+    let bookmarks = load_chromium(&data("chromium_bookmarks.json")).unwrap();
+    assert_eq!(bookmarks, vec![
+        bookmark("The Rust Programming Language", "https://doc.rust-lang.org/book/", "Bookmarks bar"),
+        bookmark("tui - Rust", "https://docs.rs/tui/latest/tui/", "Bookmarks bar/Crates"),
+        // Untitled bookmarks show the url.
+        bookmark("https://learn.microsoft.com/windows/terminal/", "https://learn.microsoft.com/windows/terminal/", "Other bookmarks"),
+    ]);
+
+    assert!(parse_chromium("{}").is_err());
+    assert!(parse_chromium("not json").is_err());
+    assert_eq!(parse_chromium(r#"{"roots": {}}"#).unwrap(), vec![]);
+}
+
+#[test]
+fn firefox() {
+    let places = data("places.sqlite");
+    let bookmarks = load_file(&places).unwrap();
+    assert_eq!(bookmarks, vec![
+        bookmark("MDN", "https://developer.mozilla.org/", "Bookmarks Menu"),
+        // Page title when the bookmark has none, and folders in position order.
+        bookmark("Firefox", "https://www.mozilla.org/firefox/", "Bookmarks Toolbar"),
+        bookmark("rusqlite", "https://crates.io/crates/rusqlite", "Bookmarks Toolbar/Rust"),
+        bookmark("Database File Format", "https://www.sqlite.org/fileformat.html", "Other Bookmarks"),
+    ]);
+
+    // Read from a copy, nothing is left next to the original.
+    for suffix in ["-wal", "-shm", "-journal"] {
+        assert!(!std::path::PathBuf::from(format!("{}{}", places.to_string_lossy(), suffix)).exists(), "{}", suffix);
+    }
+
+    assert!(load_file(&data("missing").join(FIREFOX_PLACES)).is_err());
+}
+
+#[test]
+fn locations() {
+    let root = std::env::temp_dir().join(format!("switch-bookmarks-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let user_data = root.join("User Data");
+    let profiles = root.join("Profiles");
+    for dir in [user_data.join("Default"), user_data.join("Profile 1"), user_data.join("System Profile"), profiles.join("x1y2z3.default-release")] {
+        std::fs::create_dir_all(dir).unwrap();
+    }
+    std::fs::copy(data("chromium_bookmarks.json"), user_data.join("Default").join(CHROMIUM_BOOKMARKS)).unwrap();
+    std::fs::write(user_data.join("Profile 1").join(CHROMIUM_BOOKMARKS),
+        r#"{"roots": {"bookmark_bar": {"type": "folder", "name": "Bookmarks bar", "children": [
+            {"type": "url", "name": "Rust book", "url": "https://doc.rust-lang.org/book/"},
+            {"type": "url", "name": "crates.io", "url": "https://crates.io/"}
+        ]}}}"#).unwrap();
+    std::fs::copy(data("places.sqlite"), profiles.join("x1y2z3.default-release").join(FIREFOX_PLACES)).unwrap();
+
+    let cases: &[(std::path::PathBuf, Vec<std::path::PathBuf>)] = &[
+        (user_data.clone(), vec![
+            user_data.join("Default").join(CHROMIUM_BOOKMARKS),
+            user_data.join("Profile 1").join(CHROMIUM_BOOKMARKS),
+        ]),
+        (user_data.join("Default"), vec![user_data.join("Default").join(CHROMIUM_BOOKMARKS)]),
+        (profiles.join("x1y2z3.default-release").join(FIREFOX_PLACES), vec![profiles.join("x1y2z3.default-release").join(FIREFOX_PLACES)]),
+        (profiles.clone(), vec![profiles.join("x1y2z3.default-release").join(FIREFOX_PLACES)]),
+        (root.join("missing"), vec![]),
+    ];
+    for (location, files) in cases {
+        assert_eq!(&find_files(location), files, "{:?}", location);
+    }
+
+    // Merged in location order, the book bookmarked twice is listed once.
+    let locations = [user_data.to_string_lossy().into_owned(), profiles.to_string_lossy().into_owned(), root.join("missing").to_string_lossy().into_owned()];
+    let urls = load(&locations).into_iter().map(|b| b.url).collect::<Vec<String>>();
+    assert_eq!(urls, vec![
+        "https://doc.rust-lang.org/book/",
+        "https://docs.rs/tui/latest/tui/",
+        "https://learn.microsoft.com/windows/terminal/",
+        "https://crates.io/",
+        "https://developer.mozilla.org/",
+        "https://www.mozilla.org/firefox/",
+        "https://crates.io/crates/rusqlite",
+        "https://www.sqlite.org/fileformat.html",
+    ]);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn filter() {
+    let mut provider = BookmarkProvider::with_bookmarks(vec![
+        bookmark("MDN", "https://developer.mozilla.org/", "Bookmarks Menu"),
+        bookmark("tui - Rust", "https://docs.rs/tui/latest/tui/", "Bookmarks bar/Crates"),
+        bookmark("The Rust Programming Language", "https://doc.rust-lang.org/book/", "Bookmarks bar"),
+        bookmark("rusqlite", "https://crates.io/crates/rusqlite", "Bookmarks Toolbar/Rust"),
+    ]);
+    assert_eq!(provider.query_for_names().len(), 4);
+
+    let cases: &[(&str, &str)] = &[
+        // Title first.
+        ("mdn", "https://developer.mozilla.org/"),
+        ("programming", "https://doc.rust-lang.org/book/"),
+        // Then the url.
+        ("mozilla", "https://developer.mozilla.org/"),
+        // Then the folders.
+        ("crates/tui", "https://docs.rs/tui/latest/tui/"),
+        ("toolbar/rust", "https://crates.io/crates/rusqlite"),
+    ];
+    for (query, url) in cases {
+        provider.set_query(query.to_string());
+        assert!(provider.query_for_names().len() > 0, "{}", query);
+        assert_eq!(provider.copy_text(0).as_deref(), Some(*url), "{}", query);
+    }
+
+    provider.set_query("zzzz".into());
+    assert_eq!(provider.query_for_names().len(), 0);
+    assert_eq!(provider.copy_text(0), None);
+    assert_eq!(provider.prefix(), Some("*"));
+}
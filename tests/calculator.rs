@@ -0,0 +1,184 @@
+use switch::calculator::*;
+
+fn eval(input: &str) -> String {
+    return match evaluate(input) {
+        Ok(result) => result.to_string(),
+        Err(e) => format!("error: {}", e),
+    };
+}
+
+fn assert_cases(cases: &[(&str, &str)]) {
+    for (input, expected) in cases {
+        assert_eq!(eval(input), *expected, "{}", input);
+    }
+}
+
+// cargo test --test calculator
+#[test]
+fn arithmetic() {
+    assert_cases(&[
+        ("1 + 2", "3"),
+        ("1 + 2 * 3", "7"),
+        ("(1 + 2) * 3", "9"),
+        ("10 / 4", "2.5"),
+        ("10 % 4", "2"),
+        ("2 - 3 - 4", "-5"),
+        ("2 / 4 / 2", "0.25"),
+        ("-3 + 5", "2"),
+        ("--3", "3"),
+        ("+3", "3"),
+        ("0.1 + 0.2", "0.3"),
+        (".5 * 4", "2"),
+        ("1_000_000 / 1e3", "1000"),
+        ("1.5e3 + 2E-1", "1500.2"),
+        ("2 × 3 ÷ 4 − 1", "0.5"),
+        ("1 / 3", "0.3333333333"),
+        ("1 / 0", "∞"),
+        ("((2))", "2"),
+    ]);
+}
+
+#[test]
+fn powers() {
+    assert_cases(&[
+        ("2 ^ 10", "1024"),
+        ("2 ** 10", "1024"),
+        ("2 ^ 3 ^ 2", "512"),
+        ("-2 ^ 2", "-4"),
+        ("2 ^ -1", "0.5"),
+        ("4 ^ 0.5", "2"),
+        ("2 ^ 64", "1.844674407e19"),
+        ("10 ^ 20", "1e20"),
+        ("10 ^ -9", "1e-9"),
+    ]);
+}
+
+#[test]
+fn literals() {
+    assert_cases(&[
+        ("0xff", "255"),
+        ("0XFF + 1", "256"),
+        ("0b1010", "10"),
+        ("0o17", "15"),
+        ("0xff_ff", "65535"),
+        ("255 in hex", "0xFF"),
+        ("10 to bin", "0b1010"),
+        ("8 as oct", "0o10"),
+        ("0xff in dec", "255"),
+        ("-255 in hex", "-0xFF"),
+        ("1.5 in hex", "1.5"),
+        ("0xg", "error: Bad number '0x'"),
+    ]);
+}
+
+#[test]
+fn functions_and_constants() {
+    assert_cases(&[
+        ("sqrt(16)", "4"),
+        ("abs(-3)", "3"),
+        ("min(3, 1, 2)", "1"),
+        ("max(3, 1, 2)", "3"),
+        ("floor(2.7) + ceil(2.1) + round(2.5)", "8"),
+        ("log(1000)", "3"),
+        ("log(8, 2)", "3"),
+        ("log2(1024)", "10"),
+        ("ln(e)", "1"),
+        ("exp(0)", "1"),
+        ("pi", "3.141592654"),
+        ("2 * pi", "6.283185307"),
+        ("tau / 2", "3.141592654"),
+        ("sin(pi / 2)", "1"),
+        ("cos(0)", "1"),
+        ("deg(pi)", "180"),
+        ("pow(2, 8)", "256"),
+        ("sqrt(2) ^ 2", "2"),
+    ]);
+}
+
+#[test]
+fn unit_conversions() {
+    assert_cases(&[
+        ("3.5 GiB in MB", "3758.096384 MB"),
+        ("90 min to h", "1.5 h"),
+        ("1 GB in MiB", "953.6743164 MiB"),
+        ("1 GiB + 512 MiB in GiB", "1.5 GiB"),
+        ("1 GiB / 1 MiB", "1024"),
+        ("100 Mbit in MB", "12.5 MB"),
+        ("8 bits to B", "1 B"),
+        ("2 * 30 min in h", "1 h"),
+        ("1 day in hours", "24 h"),
+        ("1500 ms", "1500 ms"),
+        ("1 mile in km", "1.609344 km"),
+        ("12 inches in cm", "30.48 cm"),
+        ("1 lb in g", "453.59237 g"),
+        ("100 C in F", "212 F"),
+        ("32 F in C", "0 C"),
+        ("0 K in celsius", "-273.15 C"),
+        // Negating and scaling temperatures happens in the unit they're written in.
+        ("-40 C in F", "-40 F"),
+        ("-10 C", "-10 C"),
+        ("-(10 F) in F", "-10 F"),
+        ("2 * 30 C in C", "60 C"),
+        ("30 C * 2", "60 C"),
+        ("100 F / 4", "25 F"),
+        ("abs(-5 C)", "5 C"),
+        ("1 mb in kb", "1000 KB"),
+    ]);
+}
+
+#[test]
+fn errors() {
+    assert_cases(&[
+        ("", "error: Empty expression"),
+        ("1 +", "error: Unexpected end of expression"),
+        ("(1 + 2", "error: Expected ')'"),
+        ("1 + 2)", "error: Unexpected ')'"),
+        ("1 2", "error: Unexpected '2'"),
+        ("foo", "error: Unknown name 'foo'"),
+        ("foo(1)", "error: Unknown function 'foo'"),
+        ("1 $ 2", "error: Unexpected '$'"),
+        ("1 GB in h", "error: Can't convert to h"),
+        ("1 GB + 1 h", "error: Can't add quantities of different dimensions"),
+        ("1 GB * 1 GB", "error: Unsupported combination of units"),
+        ("5 in", "error: Expected a unit after 'in'"),
+        ("5 in parsecs", "error: Unknown unit 'parsecs'"),
+        ("10 C + 10 C", "error: Can't add temperatures"),
+        ("sqrt(1, 2)", "error: Wrong number of arguments to sqrt"),
+        ("sqrt(1 GB)", "error: sqrt takes a number"),
+        ("notepad", "error: Unknown name 'notepad'"),
+    ]);
+}
+
+#[test]
+fn number_formatting() {
+    assert_eq!(format_number(0.0), "0");
+    assert_eq!(format_number(-0.5), "-0.5");
+    assert_eq!(format_number(123456789.125), "123456789.1");
+    assert_eq!(format_number(1.0e-7), "1e-7");
+    assert_eq!(format_number(f64::NAN), "NaN");
+    assert_eq!(format_integer(255.0, Radix::Hex), Some("0xFF".to_owned()));
+    assert_eq!(format_integer(5.0, Radix::Binary), Some("0b101".to_owned()));
+    assert_eq!(format_integer(0.5, Radix::Hex), None);
+}
+
+#[test]
+fn provider_results() {
+    let texts = |query: &str| switch::calculatorprovider::calculator_results(query).into_iter()
+        .map(|r| (r.text, r.value))
+        .collect::<Vec<(String, String)>>();
+
+    assert_eq!(texts(""), vec![]);
+    assert_eq!(texts("6 * 7"), vec![
+        ("= 42".to_owned(), "42".to_owned()),
+        ("= 0x2A".to_owned(), "0x2A".to_owned()),
+        ("= 0b101010".to_owned(), "0b101010".to_owned()),
+    ]);
+    assert_eq!(texts("255 in hex"), vec![
+        ("= 0xFF".to_owned(), "0xFF".to_owned()),
+        ("= 255".to_owned(), "255".to_owned()),
+        ("= 0b11111111".to_owned(), "0b11111111".to_owned()),
+    ]);
+    assert_eq!(texts("90 min to h"), vec![("= 1.5 h".to_owned(), "1.5 h".to_owned())]);
+    // Errors are shown but there is nothing to copy.
+    assert_eq!(texts("1 +"), vec![("Unexpected end of expression".to_owned(), String::new())]);
+}
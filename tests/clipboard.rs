@@ -0,0 +1,119 @@
+use switch::clipboard::{Clipboard, MemoryClipboard};
+use switch::clipboardhistory::ClipboardHistory;
+use switch::clipboardhistoryprovider::ClipboardHistoryProvider;
+use switch::ListContentProvider;
+
+fn entries(history: &ClipboardHistory) -> Vec<&str> {
+    return history.entries().iter().map(|e| e.as_str()).collect();
+}
+
+fn names(provider: &mut ClipboardHistoryProvider) -> Vec<String> {
+    return provider.query_for_names();
+}
+
+// cargo test --test clipboard
+#[test]
+fn memory_clipboard() {
+    let mut clipboard = MemoryClipboard::default();
+    assert_eq!(clipboard.get_text().unwrap(), "");
+    clipboard.set_text("héllo wörld ✓").unwrap();
+    assert_eq!(clipboard.get_text().unwrap(), "héllo wörld ✓");
+}
+
+#[test]
+fn history_dedup_and_cap() {
+    let mut history = ClipboardHistory::new(3);
+    assert!(history.record("a"));
+    assert!(history.record("b"));
+    assert!(history.record("c"));
+    assert_eq!(entries(&history), vec!["c", "b", "a"]);
+
+    // Copying something again moves it to the front.
+    assert!(history.record("a"));
+    assert_eq!(entries(&history), vec!["a", "c", "b"]);
+
+    // Oldest entry falls off.
+    assert!(history.record("d"));
+    assert_eq!(entries(&history), vec!["d", "a", "c"]);
+
+    // Blank text isn't recorded.
+    assert!(!history.record(""));
+    assert!(!history.record(" \r\n\t"));
+    assert_eq!(history.len(), 3);
+
+    assert_eq!(history.remove(1), Some("a".to_owned()));
+    assert_eq!(history.remove(5), None);
+    assert_eq!(entries(&history), vec!["d", "c"]);
+
+    let mut disabled = ClipboardHistory::new(0);
+    assert!(!disabled.record("a"));
+}
+
+#[test]
+fn history_persistence() {
+    let mut history = ClipboardHistory::new(10);
+    history.record("first");
+    history.record("multi\r\nline \"quoted\"");
+    let json = history.to_json().unwrap();
+    assert_eq!(ClipboardHistory::parse(&json, 10).unwrap(), history);
+
+    // Loading applies the cap and drops duplicates and blanks from hand edited files.
+    let loaded = ClipboardHistory::parse(r#"["a", "b", "a", "", "c"]"#, 2).unwrap();
+    assert_eq!(entries(&loaded), vec!["a", "b"]);
+
+    let path = std::env::temp_dir().join(format!("switch_clipboard_history_{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(ClipboardHistory::load_from(&path, 10).unwrap().len(), 0);
+
+    ClipboardHistory::record_to(&path, "one", 10).unwrap();
+    ClipboardHistory::record_to(&path, "two", 10).unwrap();
+    ClipboardHistory::record_to(&path, "one", 10).unwrap();
+    assert_eq!(entries(&ClipboardHistory::load_from(&path, 10).unwrap()), vec!["one", "two"]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn provider_search_and_copy() {
+    let mut clipboard = MemoryClipboard::new();
+    clipboard.text = Some("current".to_owned());
+    let history = ClipboardHistory::with_entries(vec!["Foo bar".into(), "line 1\nline 2".into(), "baz".into()], 10);
+    let mut provider = ClipboardHistoryProvider::with_clipboard(clipboard, history, None);
+
+    provider.record_clipboard();
+    assert_eq!(names(&mut provider), vec!["current", "Foo bar", "line 1⏎line 2", "baz"]);
+
+    provider.set_query("FOO".into());
+    assert_eq!(names(&mut provider), vec!["Foo bar"]);
+    assert_eq!(provider.copy_text(0), Some("Foo bar".to_owned()));
+    assert_eq!(provider.copy_text(1), None);
+
+    // Starting copies the full text and moves it to the top.
+    provider.set_query("line".into());
+    provider.start(0, false);
+    assert_eq!(provider.clipboard().get_text().unwrap(), "line 1\nline 2");
+    assert_eq!(entries(provider.history()), vec!["line 1\nline 2", "current", "Foo bar", "baz"]);
+
+    provider.set_query("ba".into());
+    assert_eq!(names(&mut provider), vec!["Foo bar", "baz"]);
+    provider.remove(1);
+    assert_eq!(entries(provider.history()), vec!["line 1\nline 2", "current", "Foo bar"]);
+}
+
+#[test]
+fn provider_merges_saved_history() {
+    let path = std::env::temp_dir().join(format!("switch_clipboard_provider_{}.json", std::process::id()));
+    ClipboardHistory::with_entries(vec!["one".into(), "two".into()], 10).save_to(&path).unwrap();
+    let history = ClipboardHistory::load_from(&path, 10).unwrap();
+    let mut provider = ClipboardHistoryProvider::with_clipboard(MemoryClipboard::new(), history, Some(path.to_string_lossy().into_owned()));
+
+    // Recorded by quakerun while the list was open, removing and copying keep it.
+    ClipboardHistory::record_to(&path, "three", 10).unwrap();
+    provider.remove(1);
+    assert_eq!(entries(&ClipboardHistory::load_from(&path, 10).unwrap()), vec!["three", "one"]);
+    assert_eq!(names(&mut provider), vec!["three", "one"]);
+
+    ClipboardHistory::record_to(&path, "four", 10).unwrap();
+    provider.start(1, false);
+    assert_eq!(entries(&ClipboardHistory::load_from(&path, 10).unwrap()), vec!["one", "four", "three"]);
+    std::fs::remove_file(&path).unwrap();
+}
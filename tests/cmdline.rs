@@ -0,0 +1,116 @@
+use switch::cmdline::*;
+
+// cargo test --test cmdline
+#[test]
+fn split_windows_arguments() {
+    // Cases from https://docs.microsoft.com/en-us/cpp/c-language/parsing-c-command-line-arguments
+    // prefixed with a program name since the first argument follows different rules.
+    let cases: &[(&str, &[&str])] = &[
+        (r#"prog "abc" d e"#, &["prog", "abc", "d", "e"]),
+        (r#"prog a\\b d"e f"g h"#, &["prog", r"a\\b", "de fg", "h"]),
+        (r#"prog a\\\"b c d"#, &["prog", r#"a\"b"#, "c", "d"]),
+        (r#"prog a\\\\"b c" d e"#, &["prog", r"a\\b c", "d", "e"]),
+        // CommandLineToArgvW ends the quoted region here, unlike the 2008 msvcrt rules in the doc.
+        (r#"prog a"b"" c d"#, &["prog", r#"ab""#, "c", "d"]),
+        (r#"prog "a b" "c""#, &["prog", "a b", "c"]),
+        (r#"prog """"#, &["prog", r#"""#]),
+        (r#"prog """""#, &["prog", r#"""#]),
+        (r#"prog """""""#, &["prog", r#""""#]),
+        (r#"prog "" x"#, &["prog", "", "x"]),
+        (r#"prog a\"#, &["prog", r"a\"]),
+        (r#"prog \\server\share"#, &["prog", r"\\server\share"]),
+        ("prog \t a \t b  ", &["prog", "a", "b"]),
+        (r#"prog "unterminated arg"#, &["prog", "unterminated arg"]),
+    ];
+
+    for (cmdline, expected) in cases {
+        assert_eq!(split_windows(cmdline), *expected, "{}", cmdline);
+    }
+}
+
+#[test]
+fn split_windows_program() {
+    let cases: &[(&str, &[&str])] = &[
+        ("", &[]),
+        ("   ", &[]),
+        ("notepad", &["notepad"]),
+        ("  notepad  ", &["notepad"]),
+        (r#""C:\Program Files\foo.exe" --x "a b""#, &[r"C:\Program Files\foo.exe", "--x", "a b"]),
+        // Backslashes before a quote don't escape it in the program name.
+        (r#""C:\dir\" x"#, &[r"C:\dir\", "x"]),
+        (r"C:\Windows\system32\cmd.exe /c dir", &[r"C:\Windows\system32\cmd.exe", "/c", "dir"]),
+        (r#""a"b c"#, &["a", "b", "c"]),
+        (r#""unterminated program"#, &["unterminated program"]),
+    ];
+
+    for (cmdline, expected) in cases {
+        assert_eq!(split_windows(cmdline), *expected, "{}", cmdline);
+    }
+}
+
+#[test]
+fn split_posix_arguments() {
+    let cases: &[(&str, &[&str])] = &[
+        ("", &[]),
+        ("  ls  -la ", &["ls", "-la"]),
+        (r#"echo 'a b' "c \"d\"" e\ f"#, &["echo", "a b", r#"c "d""#, "e f"]),
+        (r#"a""b"#, &["ab"]),
+        ("''", &[""]),
+        (r#"'a\b'"#, &[r"a\b"]),
+        (r#""a\b""#, &[r"a\b"]),
+        (r#""\$HOME \\ \`""#, &[r"$HOME \ `"]),
+        ("a\\\nb", &["ab"]),
+        ("a\nb", &["a", "b"]),
+        ("'unterminated arg", &["unterminated arg"]),
+        (r"trailing\", &[r"trailing\"]),
+    ];
+
+    for (cmdline, expected) in cases {
+        assert_eq!(split_posix(cmdline), *expected, "{}", cmdline);
+    }
+}
+
+#[test]
+fn split_program_and_params() {
+    let cases: &[(&str, Syntax, Option<(&str, &str)>)] = &[
+        ("", Syntax::Windows, None),
+        ("  ", Syntax::Posix, None),
+        ("notepad", Syntax::Windows, Some(("notepad", ""))),
+        (r#""C:\Program Files\foo.exe" --x "a b""#, Syntax::Windows, Some((r"C:\Program Files\foo.exe", r#"--x "a b""#))),
+        (r#"code   "C:\my dir\""#, Syntax::Windows, Some(("code", r#""C:\my dir\""#))),
+        ("'my prog' -a 'b c'", Syntax::Posix, Some(("my prog", "-a 'b c'"))),
+        (r"my\ prog x", Syntax::Posix, Some(("my prog", "x"))),
+    ];
+
+    for (cmdline, syntax, expected) in cases {
+        let expected = expected.map(|(p, a)| (p.to_owned(), a.to_owned()));
+        assert_eq!(split_program_with(cmdline, *syntax), expected, "{}", cmdline);
+    }
+}
+
+#[test]
+fn quote_round_trip() {
+    let args = [
+        "simple",
+        "",
+        "with space",
+        r"C:\Program Files\",
+        r#"say "hi""#,
+        r#"back\\"slash"#,
+        r"trailing\\",
+        "tab\there",
+        "it's",
+        "$HOME",
+    ];
+
+    for arg in args {
+        let quoted = quote_windows(arg);
+        assert_eq!(split_windows(&format!("prog {}", quoted)), vec!["prog", arg], "{}", quoted);
+        let quoted = quote_posix(arg);
+        assert_eq!(split_posix(&format!("prog {}", quoted)), vec!["prog", arg], "{}", quoted);
+    }
+
+    assert_eq!(quote_windows("simple"), "simple");
+    assert_eq!(quote_windows("a b"), r#""a b""#);
+    assert_eq!(quote_posix("a b"), "'a b'");
+}
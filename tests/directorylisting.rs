@@ -0,0 +1,136 @@
+use switch::directorylisting::*;
+
+const SEP: char = std::path::MAIN_SEPARATOR;
+
+fn entry(name: &str, is_dir: bool, hidden: bool) -> DirectoryEntry {
+    return DirectoryEntry {
+        name: name.to_owned(),
+        path: std::path::PathBuf::from("C:\\code").join(name),
+        is_dir,
+        hidden,
+    };
+}
+
+fn entries() -> Vec<DirectoryEntry> {
+    return vec![
+        entry("switch", true, false),
+        entry("switchboard.txt", false, false),
+        entry("SwitchHelper", true, false),
+        entry("notes.md", false, false),
+        entry(".cache", true, true),
+        entry("src_backup", true, false),
+    ];
+}
+
+fn names(entries: Vec<&DirectoryEntry>) -> Vec<&str> {
+    return entries.into_iter().map(|e| e.name.as_str()).collect();
+}
+
+// cargo test --test directorylisting
+#[test]
+fn split_and_ascend() {
+    let cases: &[(&str, &str, &str)] = &[
+        ("C:\\code\\sw", "C:\\code\\", "sw"),
+        ("C:\\code\\", "C:\\code\\", ""),
+        ("C:\\", "C:\\", ""),
+        ("C:/code/sw", "C:/code/", "sw"),
+        ("sw", "", "sw"),
+    ];
+    for (query, dir, filter) in cases {
+        assert_eq!(split_query(query), (std::path::PathBuf::from(dir), filter.to_string()), "{}", query);
+    }
+
+    assert_eq!(ascend("C:\\code\\switch\\"), Some("C:\\code\\".to_owned()));
+    assert_eq!(ascend("C:\\code\\"), Some("C:\\".to_owned()));
+    assert_eq!(ascend("/home/user/"), Some("/home/".to_owned()));
+    assert_eq!(ascend("C:\\"), None);
+    assert_eq!(ascend("C:\\code\\sw"), None);
+
+    let rooted: &[(&str, bool)] = &[
+        ("C:\\Program Files\\Common Files", true),
+        ("d:/games", true),
+        ("\\\\server\\share\\My Documents", true),
+        ("C:", false),
+        ("\\\\", false),
+        ("\\code", false),
+        ("notepad C:\\notes.txt", false),
+    ];
+    for (query, expected) in rooted {
+        assert_eq!(is_rooted(query), *expected, "{}", query);
+    }
+}
+
+#[test]
+fn dirs_first_fuzzy_filter() {
+    let entries = entries();
+    assert_eq!(names(filter_entries(&entries, "", false)), vec!["src_backup", "switch", "SwitchHelper", "notes.md", "switchboard.txt"]);
+    assert_eq!(names(filter_entries(&entries, "sw", false)), vec!["switch", "SwitchHelper", "switchboard.txt"]);
+    assert_eq!(names(filter_entries(&entries, "sh", false)), vec!["SwitchHelper", "switch", "switchboard.txt"]);
+    assert_eq!(names(filter_entries(&entries, "cache", false)), Vec::<&str>::new());
+    assert_eq!(names(filter_entries(&entries, "cache", true)), vec![".cache"]);
+}
+
+#[test]
+fn tab_completion() {
+    let entries = entries();
+    assert_eq!(common_prefix(&["switch", "SwitchHelper", "switchboard.txt"]), "switch");
+    assert_eq!(common_prefix(&["abc"]), "abc");
+    assert_eq!(common_prefix(&["abc", "xyz"]), "");
+    assert_eq!(common_prefix(&[]), "");
+
+    // Extend to the common prefix, keeping what was typed before the filter.
+    assert_eq!(complete("C:\\code\\sw", &entries, false), Some("C:\\code\\switch".to_owned()));
+    // Nothing more in common.
+    assert_eq!(complete("C:\\code\\switch", &entries, false), None);
+    // A single match is completed, directories get a separator so the next tab descends.
+    assert_eq!(complete("C:\\code\\switchh", &entries, false), Some(format!("C:\\code\\SwitchHelper{}", SEP)));
+    assert_eq!(complete("C:\\code\\no", &entries, false), Some("C:\\code\\notes.md".to_owned()));
+    assert_eq!(complete("C:\\code\\.c", &entries, false), None);
+    assert_eq!(complete("C:\\code\\.c", &entries, true), Some(format!("C:\\code\\.cache{}", SEP)));
+    assert_eq!(complete("C:\\code\\zzz", &entries, false), None);
+}
+
+#[test]
+fn recent_directories() {
+    let mut recent = vec![];
+    push_recent(&mut recent, "C:\\code\\switch", 3);
+    push_recent(&mut recent, "C:\\code\\other\\", 3);
+    push_recent(&mut recent, "C:\\Users", 3);
+    assert_eq!(recent, vec![
+        format!("C:\\Users{}", SEP),
+        "C:\\code\\other\\".to_owned(),
+        format!("C:\\code\\switch{}", SEP),
+    ]);
+
+    // Visiting again moves it to the front, the oldest falls off.
+    push_recent(&mut recent, "C:\\code\\other\\", 3);
+    push_recent(&mut recent, "D:\\", 3);
+    assert_eq!(recent, vec!["D:\\".to_owned(), "C:\\code\\other\\".to_owned(), format!("C:\\Users{}", SEP)]);
+
+    let recent = vec!["C:\\code\\switch\\".to_owned(), "C:\\code\\other\\".to_owned(), "C:\\code\\switchboard\\".to_owned()];
+    assert_eq!(suggest_recent(&recent, "switch", 5), vec!["C:\\code\\switch\\", "C:\\code\\switchboard\\"]);
+    assert_eq!(suggest_recent(&recent, "code", 2), vec!["C:\\code\\switch\\", "C:\\code\\other\\"]);
+    assert_eq!(suggest_recent(&recent, "", 5).len(), 0);
+}
+
+#[test]
+fn read_and_cache() {
+    let root = std::env::temp_dir().join(format!("switch_directorylisting_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("sub")).unwrap();
+    std::fs::write(root.join("file.txt"), "").unwrap();
+
+    let mut cache = DirectoryCache::default();
+    let mut listed = cache.entries(&root).iter().map(|e| (e.name.clone(), e.is_dir)).collect::<Vec<(String, bool)>>();
+    listed.sort();
+    assert_eq!(listed, vec![("file.txt".to_owned(), false), ("sub".to_owned(), true)]);
+
+    // Cached until the directory changes.
+    std::fs::write(root.join("new.txt"), "").unwrap();
+    assert_eq!(cache.entries(&root).len(), 2);
+    assert_eq!(cache.entries(&root.join("sub")).len(), 0);
+    assert_eq!(cache.entries(&root).len(), 3);
+
+    assert_eq!(entry("sub", true, false).display_path(), format!("{}{}", std::path::Path::new("C:\\code").join("sub").display(), SEP));
+    std::fs::remove_dir_all(&root).unwrap();
+}
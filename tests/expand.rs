@@ -0,0 +1,108 @@
+use switch::expand::*;
+
+struct TestVariables {
+    vars: std::collections::HashMap<&'static str, &'static str>,
+}
+
+impl TestVariables {
+    fn new() -> Self {
+        return TestVariables {
+            vars: [
+                ("USERPROFILE", r"C:\Users\me"),
+                ("ProgramData", r"C:\ProgramData"),
+                ("LONG", "a very long value that would not fit in any of the fixed size buffers we used to have, repeated: \
+                    a very long value that would not fit in any of the fixed size buffers we used to have"),
+                ("UNICODE", r"C:\Users\José\文档"),
+                ("EMPTY", ""),
+            ].into_iter().collect(),
+        };
+    }
+}
+
+impl VariableSource for TestVariables {
+    fn var(&self, name: &str) -> Option<String> {
+        return self.vars.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.to_string());
+    }
+
+    fn home_dir(&self, user: Option<&str>) -> Option<String> {
+        return match user {
+            None => Some(r"C:\Users\me".into()),
+            Some("other") => Some(r"C:\Users\other".into()),
+            Some(_) => None,
+        };
+    }
+}
+
+// cargo test --test expand
+#[test]
+fn expand_variables() {
+    let vars = TestVariables::new();
+    let cases: &[(&str, &str)] = &[
+        ("", ""),
+        ("no variables", "no variables"),
+        (r"%USERPROFILE%\.cargo\bin", r"C:\Users\me\.cargo\bin"),
+        (r"%userprofile%\x", r"C:\Users\me\x"),
+        (r"%ProgramData%\%USERPROFILE%", r"C:\ProgramData\C:\Users\me"),
+        (r"$USERPROFILE/x", r"C:\Users\me/x"),
+        (r"${USERPROFILE}x", r"C:\Users\mex"),
+        ("$USERPROFILEx", "$USERPROFILEx"),
+        (r"%UNICODE%\x", r"C:\Users\José\文档\x"),
+        ("[%EMPTY%]", "[]"),
+        // Unknown or malformed variables are left alone.
+        ("%UNDEFINED%", "%UNDEFINED%"),
+        ("100% done", "100% done"),
+        ("%%", "%%"),
+        ("%USERPROFILE", "%USERPROFILE"),
+        (r"\\server\c$\dir", r"\\server\c$\dir"),
+        ("${UNDEFINED}", "${UNDEFINED}"),
+        ("${USERPROFILE", "${USERPROFILE"),
+        ("$", "$"),
+        ("cost $5", "cost $5"),
+        ("%UNDEFINED%USERPROFILE%", r"%UNDEFINEDC:\Users\me"),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(expand_with(input, &vars), *expected, "{}", input);
+    }
+
+    let long = expand_with("%LONG%%LONG%%LONG%%LONG%%LONG%", &vars);
+    assert_eq!(long, vars.var("LONG").unwrap().repeat(5));
+    assert!(long.len() > 512);
+}
+
+#[test]
+fn expand_home() {
+    let vars = TestVariables::new();
+    let cases: &[(&str, &str)] = &[
+        ("~", r"C:\Users\me"),
+        (r"~\Documents", r"C:\Users\me\Documents"),
+        ("~/src", r"C:\Users\me/src"),
+        (r"~other\Desktop", r"C:\Users\other\Desktop"),
+        ("~nobody", "~nobody"),
+        (r"code ~\src", r"code C:\Users\me\src"),
+        // Only at the start of a word.
+        ("a~b", "a~b"),
+        (r"C:\PROGRA~1\x", r"C:\PROGRA~1\x"),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(expand_with(input, &vars), *expected, "{}", input);
+    }
+}
+
+#[test]
+fn expand_tilde_only() {
+    let vars = TestVariables::new();
+    let cases: &[(&str, &str)] = &[
+        (r"~\%USERPROFILE%", r"C:\Users\me\%USERPROFILE%"),
+        (r"~other\x ~\y", r"C:\Users\other\x ~\y"),
+        ("%USERPROFILE%", "%USERPROFILE%"),
+        ("notepad", "notepad"),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(expand_tilde_with(input, &vars), *expected, "{}", input);
+    }
+}
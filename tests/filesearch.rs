@@ -0,0 +1,187 @@
+use switch::filesearchprovider::{search, FileEntry, FileSearchProvider, MAX_SCORED};
+use switch::listcontentprovider::ListContentProvider;
+use switch::ignore::IgnoreFile;
+
+fn file(path: &str) -> FileEntry {
+    return FileEntry { path: path.to_owned(), is_dir: false };
+}
+
+fn paths(files: &[FileEntry], query: &str) -> Vec<String> {
+    return search(files, query, 10).into_iter().map(|f| f.path).collect();
+}
+
+// cargo test --test filesearch
+#[test]
+fn ignore_patterns() {
+    let ignore = IgnoreFile::parse("
+# comment
+*.log
+!keep.log
+build/
+/root_only.txt
+docs/*.pdf
+**/generated/**
+[Tt]emp?
+\\#hash
+trailing
+");
+
+    // (path relative to the ignore file, is_dir, ignored)
+    let cases: &[(&str, bool, bool)] = &[
+        ("a.log", false, true),
+        ("sub/dir/a.log", false, true),
+        ("keep.log", false, false),
+        ("build", true, true),
+        ("sub/build", true, true),
+        ("build", false, false),
+        ("root_only.txt", false, true),
+        ("sub/root_only.txt", false, false),
+        ("docs/a.pdf", false, true),
+        ("docs/sub/a.pdf", false, false),
+        ("other/docs/a.pdf", false, false),
+        ("generated", true, false),
+        ("generated/a.rs", false, true),
+        ("src/generated/deep/a.rs", false, true),
+        ("Temp1", true, true),
+        ("temp12", true, false),
+        ("#hash", false, true),
+        ("comment", false, false),
+        ("trailing", false, true),
+        ("src\\x.log", false, true),
+        // Case only matters outside Windows.
+        ("A.LOG", false, cfg!(windows)),
+        ("BUILD", true, cfg!(windows)),
+        ("TEMP1", true, cfg!(windows)),
+        ("Docs/A.pdf", false, cfg!(windows)),
+    ];
+
+    for (path, is_dir, ignored) in cases {
+        assert_eq!(ignore.is_ignored(path, *is_dir), *ignored, "{}", path);
+    }
+
+    assert_eq!(ignore.matched("keep.log", false), Some(false));
+    assert_eq!(ignore.matched("main.rs", false), None);
+}
+
+#[test]
+fn walk_respects_ignore_files() {
+    let root = std::env::temp_dir().join(format!("switch_filesearch_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+
+    for dir in ["src/nested", "target/debug", ".git", "docs"] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    for file in ["src/main.rs", "src/nested/lib.rs", "src/nested/skip.tmp", "target/debug/app.exe", ".git/HEAD", "docs/notes.md", "docs/draft.md"] {
+        std::fs::write(root.join(file), "").unwrap();
+    }
+    std::fs::write(root.join(".gitignore"), "target/\n*.tmp\n").unwrap();
+    std::fs::write(root.join("docs/.ignore"), "draft.md\n").unwrap();
+
+    let mut found = vec![];
+    switch::index::walk_ignoring(&root, &[".git/".to_owned()], 10, &mut |path, is_dir| {
+        let relative = path.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/");
+        found.push(if is_dir { relative + "/" } else { relative });
+    }).unwrap();
+    found.sort();
+
+    assert_eq!(found, vec![
+        ".gitignore",
+        "docs/",
+        "docs/.ignore",
+        "docs/notes.md",
+        "src/",
+        "src/main.rs",
+        "src/nested/",
+        "src/nested/lib.rs",
+    ]);
+
+    // Depth 0 only lists the root's children, target is still ignored by .gitignore.
+    let mut count = 0;
+    switch::index::walk_ignoring(&root, &[], 0, &mut |_, _| count += 1).unwrap();
+    assert_eq!(count, 4);
+
+    assert!(switch::index::walk_ignoring(root.join("missing"), &[], 10, &mut |_, _| {}).is_err());
+
+    // The same walk without ignore files, only files are passed.
+    let mut files = vec![];
+    switch::index::visit_directories(&root, &mut |entry| {
+        files.push(entry.path().strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/"));
+    }, 10).unwrap();
+    files.sort();
+    assert_eq!(files, vec![
+        ".git/HEAD",
+        ".gitignore",
+        "docs/.ignore",
+        "docs/draft.md",
+        "docs/notes.md",
+        "src/main.rs",
+        "src/nested/lib.rs",
+        "src/nested/skip.tmp",
+        "target/debug/app.exe",
+    ]);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn fuzzy_scores() {
+    use switch::fuzzy::score;
+
+    assert_eq!(score("", "anything"), Some(0));
+    assert_eq!(score("abc", "ab"), None);
+    assert_eq!(score("cba", "abc"), None);
+    assert!(score("MAIN", "main.rs").is_some());
+
+    // Word starts and consecutive runs beat scattered matches.
+    assert!(score("fb", "foo_bar").unwrap() > score("fb", "xfxxbx").unwrap());
+    assert!(score("main", "main.rs").unwrap() > score("main", "m_a_i_n.rs").unwrap());
+    assert!(score("sp", "SearchProvider").unwrap() > score("sp", "aspen").unwrap());
+    // Separators match either slash.
+    assert!(score("src/main", "C:\\code\\src\\main.rs").is_some());
+}
+
+#[test]
+fn path_segment_ranking() {
+    let files = vec![
+        file("C:\\code\\switch\\src\\bin\\switch.rs"),
+        file("C:\\code\\switch\\src\\main.rs"),
+        file("C:\\code\\switch\\docs\\maintenance.md"),
+        file("C:\\code\\other\\src\\domain.rs"),
+        file("C:\\code\\switch\\README.md"),
+        FileEntry { path: "C:\\code\\switch".to_owned(), is_dir: true },
+    ];
+
+    // File names beat directories higher up, shorter paths win ties.
+    assert_eq!(paths(&files, "main")[0], "C:\\code\\switch\\src\\main.rs");
+    assert_eq!(paths(&files, "readme"), vec!["C:\\code\\switch\\README.md"]);
+
+    // A term with a separator lines up with path segments.
+    assert_eq!(paths(&files, "bin/sw"), vec!["C:\\code\\switch\\src\\bin\\switch.rs"]);
+
+    // Every term has to match.
+    assert_eq!(paths(&files, "other dom"), vec!["C:\\code\\other\\src\\domain.rs"]);
+    assert_eq!(paths(&files, "switch zzz").len(), 0);
+
+    assert_eq!(paths(&files, "switch")[0], "C:\\code\\switch");
+    assert_eq!(paths(&files, "  ").len(), 0);
+}
+
+#[test]
+fn typing_narrows_matches() {
+    let mut files = (0..MAX_SCORED + 10).map(|i| file(&format!("C:\\data\\x{}.txt", i))).collect::<Vec<FileEntry>>();
+    files.push(file("C:\\code\\main.rs"));
+    files.push(file("C:\\code\\mail.rs"));
+    let mut provider = FileSearchProvider::with_files(files.clone());
+
+    // Typing on, deleting and replacing the query all end up where a fresh search would.
+    for query in ["m", "ma", "mai", "main", "ma", "mail", "x12", "x1", "rs", "rs main", ""] {
+        provider.set_query(query.to_owned());
+        let expected = search(&files, query, 200).into_iter().map(|f| f.path).collect::<Vec<String>>();
+        assert_eq!(provider.query_for_names(), expected, "{}", query);
+    }
+
+    // Only MAX_SCORED of the matches are ranked when nearly everything matches.
+    provider.set_query("a".to_owned());
+    assert!(!provider.query_for_names().contains(&"C:\\code\\main.rs".to_owned()));
+    provider.set_query("main".to_owned());
+    assert_eq!(provider.query_for_names(), vec!["C:\\code\\main.rs"]);
+}
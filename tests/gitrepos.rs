@@ -0,0 +1,132 @@
+use switch::gitrepos::*;
+use switch::gitrepoprovider::GitRepoProvider;
+use switch::ListContentProvider;
+
+fn repo(path: &str, branch: Option<&str>, dirty: Option<bool>) -> GitRepo {
+    return GitRepo {
+        path: path.to_owned(),
+        branch: branch.map(|b| b.to_owned()),
+        dirty,
+        ..Default::default()
+    };
+}
+
+// cargo test --test gitrepos
+#[test]
+fn head() {
+    let cases: &[(&str, Option<&str>)] = &[
+        ("ref: refs/heads/main\n", Some("main")),
+        ("ref: refs/heads/feature/mouse\n", Some("feature/mouse")),
+        ("ref: refs/remotes/origin/main", Some("refs/remotes/origin/main")),
+        ("e4279d7c0b7b0b5f1c1d8f2a9e2f3b4c5d6e7f80\n", Some("e4279d7")),
+        ("garbage", None),
+        ("", None),
+    ];
+    for (head, branch) in cases {
+        assert_eq!(parse_head(head).as_deref(), *branch, "{}", head);
+    }
+
+    assert_eq!(repo("C:\\code\\switch", Some("main"), Some(true)).display_name(), "switch (main*)");
+    assert_eq!(repo("C:\\code\\switch", Some("main"), Some(false)).display_name(), "switch (main)");
+    assert_eq!(repo("C:\\code\\switch", None, None).display_name(), "switch");
+}
+
+#[test]
+fn discovery() {
+    let root = std::env::temp_dir().join(format!("switch_gitrepos_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let git = |dir: &std::path::Path, head: &str| {
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(dir.join(".git").join("HEAD"), head).unwrap();
+    };
+    git(&root.join("alpha"), "ref: refs/heads/main\n");
+    // Nested repositories aren't listed, discovery stops at the outer one.
+    git(&root.join("alpha").join("vendor").join("nested"), "ref: refs/heads/main\n");
+    git(&root.join("group").join("beta"), "ref: refs/heads/dev\n");
+    git(&root.join("node_modules").join("skipped"), "ref: refs/heads/main\n");
+    git(&root.join("a").join("b").join("c").join("too_deep"), "ref: refs/heads/main\n");
+    // A worktree has a .git file pointing at its git directory.
+    std::fs::create_dir_all(root.join("worktree")).unwrap();
+    std::fs::write(root.join("worktree").join(".git"), "gitdir: ../alpha/.git\n").unwrap();
+
+    let mut found = vec![];
+    discover(&root, 3, &mut |repo| found.push(repo.strip_prefix(&root).unwrap().to_path_buf()));
+    found.sort();
+    assert_eq!(found, vec![
+        std::path::PathBuf::from("alpha"),
+        std::path::Path::new("group").join("beta"),
+        std::path::PathBuf::from("worktree"),
+    ]);
+
+    assert_eq!(read_branch(&root.join("group").join("beta")).as_deref(), Some("dev"));
+    assert_eq!(read_branch(&root.join("worktree")).as_deref(), Some("main"));
+    assert_eq!(read_branch(&root.join("group")), None);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn incremental_updates() {
+    let mut repos = vec![repo("C:\\code\\zeta", Some("main"), Some(false))];
+    update_repo(&mut repos, repo("C:\\code\\alpha", Some("main"), None));
+    update_repo(&mut repos, repo("C:\\code\\zeta", Some("dev"), Some(true)));
+    assert_eq!(repos, vec![
+        repo("C:\\code\\alpha", Some("main"), None),
+        repo("C:\\code\\zeta", Some("dev"), Some(true)),
+    ]);
+
+    let mut provider = GitRepoProvider::with_repos(vec![
+        repo("C:\\code\\switch", Some("main"), Some(false)),
+        repo("C:\\work\\tools", Some("dev"), Some(true)),
+    ], "code".to_owned());
+    assert_eq!(provider.query_for_names(), vec!["switch (main)  C:\\code\\switch", "tools (dev*)  C:\\work\\tools"]);
+    provider.set_query("work/".to_owned());
+    assert_eq!(provider.query_for_names(), vec!["tools (dev*)  C:\\work\\tools"]);
+    assert_eq!(provider.copy_text(0), Some("C:\\work\\tools".to_owned()));
+
+    provider.set_query(String::new());
+    provider.apply_update(RepoUpdate::Repo(repo("C:\\code\\new", None, None)));
+    provider.set_query(String::new());
+    assert_eq!(provider.query_for_names().len(), 3);
+}
+
+#[test]
+fn refresh_changed_repos() {
+    let root = std::env::temp_dir().join(format!("switch_gitrepos_refresh_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for name in ["alpha", "beta", "gamma"] {
+        std::fs::create_dir_all(root.join(name).join(".git")).unwrap();
+        std::fs::write(root.join(name).join(".git").join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::write(root.join(name).join(".git").join("index"), "").unwrap();
+    }
+    let path = |name: &str| root.join(name).to_string_lossy().into_owned();
+    let offline = std::env::temp_dir().join(format!("switch_gitrepos_offline_{}", std::process::id()));
+
+    // alpha is as it was cached but its status is read again, beta's HEAD was written since,
+    // gamma is new and the drive with remote on it isn't there.
+    let (head_modified, index_modified) = modified_times(&root.join("alpha"));
+    assert!(head_modified.is_some() && index_modified.is_some());
+    let cached = vec![
+        GitRepo { head_modified, index_modified, ..repo(&path("alpha"), Some("cached"), Some(true)) },
+        GitRepo { head_modified: Some(0), index_modified, ..repo(&path("beta"), Some("cached"), Some(true)) },
+        repo(&offline.join("remote").to_string_lossy(), Some("main"), Some(false)),
+    ];
+    let roots = vec![root.to_string_lossy().into_owned(), offline.to_string_lossy().into_owned()];
+    let mut read = vec![];
+    let mut found = vec![];
+    refresh(&roots, 2, &cached, &mut |update| match update {
+        RepoUpdate::Repo(repo) => read.push((repo.path, repo.branch)),
+        RepoUpdate::Done(paths) => found = paths,
+    });
+    read.sort();
+    found.sort();
+    // These aren't real repositories, git status fails on alpha so whether it's dirty is unknown now.
+    assert_eq!(read, vec![
+        (path("alpha"), Some("cached".to_owned())),
+        (path("beta"), Some("main".to_owned())),
+        (path("gamma"), Some("main".to_owned())),
+    ]);
+    let mut expected = vec![path("alpha"), path("beta"), path("gamma"), offline.join("remote").to_string_lossy().into_owned()];
+    expected.sort();
+    assert_eq!(found, expected);
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,54 @@
+use switch::calculatorprovider::CalculatorProvider;
+use switch::gitrepoprovider::GitRepoProvider;
+use switch::help::*;
+use switch::keymap::Keymap;
+use switch::ListContentProvider;
+
+fn providers() -> Vec<Box<dyn ListContentProvider>> {
+    return vec![CalculatorProvider::new(), GitRepoProvider::with_repos(vec![], "code".into())];
+}
+
+// cargo test --test help
+#[test]
+fn lines() {
+    let mut keymap = Keymap::empty();
+    for (chord, command) in [("enter", "accept"), ("ctrl+tab", "next-provider"), ("f2", "next-provider"), ("alt+1", "provider-1"), ("?", "help")] {
+        keymap.bind(chord, command).unwrap();
+    }
+    assert_eq!(help_lines(&keymap, &providers()), vec![
+        "Keys",
+        "  enter         accept",
+        "  ctrl+tab, f2  next-provider",
+        "  alt+1         provider-1",
+        "  ?             help",
+        "",
+        "Lists",
+        "  alt+1  Calculator  =",
+        "  -      Git         #",
+        "",
+        "Queries",
+        "  =3.5 GiB in MB    convert units",
+        "  =0xff * 2 in bin  show in another base",
+        "  #work/            repositories in a folder",
+    ]);
+}
+
+#[test]
+fn scrolling() {
+    let mut help = Help::new(&Keymap::default(), &providers());
+    let len = help.lines.len();
+    let cases: &[(isize, usize)] = &[
+        (-1, 0),
+        (3, 3),
+        (1000, len - 10),
+        (-2, len - 12),
+    ];
+    for (lines, scroll) in cases {
+        help.scroll_by(*lines, 10);
+        assert_eq!(help.scroll, *scroll, "{}", lines);
+    }
+
+    // Everything fits.
+    help.scroll_by(5, 1000);
+    assert_eq!(help.scroll, 0);
+}
@@ -0,0 +1,289 @@
+use switch::inputline::{InputLine, KILL_RING_SIZE};
+
+fn input(text: &str, cursor: usize) -> InputLine {
+    let mut line = InputLine::default();
+    line.reset_buffer(text);
+    line.set_cursor_pos(cursor);
+    return line;
+}
+
+// The text with | at the cursor.
+fn show(line: &InputLine) -> String {
+    let text = String::from(line);
+    let mut shown = line_prefix(&text, line.cursor_pos());
+    shown.push('|');
+    shown += &text[shown.len() - 1..];
+    return shown;
+}
+
+fn line_prefix(text: &str, graphemes: usize) -> String {
+    let mut line = InputLine::default();
+    line.reset_buffer(text);
+    line.set_cursor_pos(graphemes);
+    line.forward_kill_line();
+    return String::from(&line);
+}
+
+// cargo test --test inputline
+#[test]
+fn graphemes() {
+    // e and a combining acute accent, a flag, a family emoji and a CJK word.
+    let text = "cafe\u{301} 🇯🇵 👨‍👩‍👧 日本";
+    let mut line = input(text, 0);
+    assert_eq!(line.len(), 11);
+
+    let mut positions = vec![];
+    for _ in 0..12 {
+        line.cursor_move(1);
+        positions.push(show(&line));
+    }
+    assert_eq!(positions[3], "cafe\u{301}| 🇯🇵 👨‍👩‍👧 日本");
+    assert_eq!(positions[5], "cafe\u{301} 🇯🇵| 👨‍👩‍👧 日本");
+    assert_eq!(positions[7], "cafe\u{301} 🇯🇵 👨‍👩‍👧| 日本");
+    // Stops at the end.
+    assert_eq!(positions[10], format!("{}|", text));
+    assert_eq!(positions[11], format!("{}|", text));
+
+    let cases: &[(usize, &str)] = &[
+        (4, "caf| 🇯🇵 👨‍👩‍👧 日本"),
+        (6, "cafe\u{301} | 👨‍👩‍👧 日本"),
+        (8, "cafe\u{301} 🇯🇵 | 日本"),
+        (11, "cafe\u{301} 🇯🇵 👨‍👩‍👧 日|"),
+    ];
+    for (cursor, expected) in cases {
+        let mut line = input(text, *cursor);
+        line.backward_remove_cursor();
+        assert_eq!(show(&line), *expected, "{}", cursor);
+    }
+
+    let mut line = input(text, 3);
+    line.forward_remove_cursor();
+    assert_eq!(show(&line), "caf| 🇯🇵 👨‍👩‍👧 日本");
+}
+
+#[test]
+fn inserting() {
+    let mut line = input("日本", 1);
+    line.insert('語');
+    assert_eq!(show(&line), "日語|本");
+
+    // Pasted text moves the cursor past all of it, not by its length in bytes.
+    line.insert_string("→ü");
+    assert_eq!(show(&line), "日語→ü|本");
+    line.insert('x');
+    assert_eq!(show(&line), "日語→üx|本");
+
+    // A combining mark joins the letter before the cursor.
+    let mut line = input("e", 1);
+    line.insert('\u{301}');
+    assert_eq!(line.len(), 1);
+    assert_eq!(show(&line), "e\u{301}|");
+
+    // A letter typed in front of a mark takes it, the cursor goes after both.
+    let mut line = input("a\u{301}", 0);
+    line.cursor_move(1);
+    line.insert_string("b");
+    assert_eq!(show(&line), "a\u{301}b|");
+    let mut line = input("\u{301}x", 0);
+    line.insert('o');
+    assert_eq!(show(&line), "o\u{301}|x");
+}
+
+#[test]
+fn words() {
+    let mut line = input("größe/日本語 test", 14);
+    line.backward_word();
+    assert_eq!(show(&line), "größe/日本語 |test");
+    line.backward_word();
+    assert_eq!(show(&line), "größe/|日本語 test");
+    line.backward_kill_word();
+    assert_eq!(show(&line), "|日本語 test");
+    line.forward_word();
+    assert_eq!(show(&line), "日本語| test");
+    line.forward_kill_word();
+    assert_eq!(show(&line), "日本語|");
+}
+
+#[test]
+fn view() {
+    let mut line = input("", 0);
+    assert_eq!(line.view(5), ("".to_string(), 0));
+    assert_eq!(line.view(0), ("".to_string(), 0));
+
+    // CJK characters are two columns wide.
+    let mut line = input("日本語", 3);
+    assert_eq!(line.cursor_column(), 6);
+    assert_eq!(line.view(10), ("日本語".to_string(), 6));
+    // The end scrolls into view with a column left for the cursor, the character cut in half
+    // on the left becomes a space.
+    assert_eq!(line.view(4), (" 語".to_string(), 3));
+    line.cursor_begin();
+    assert_eq!(line.view(4), ("日本".to_string(), 0));
+    line.cursor_move(2);
+    assert_eq!(line.view(4), (" 本".to_string(), 3));
+
+    // Scrolls as little as needed.
+    let mut line = input("abcdefghij", 10);
+    assert_eq!(line.view(5), ("ghij".to_string(), 4));
+    line.cursor_move(-3);
+    assert_eq!(line.view(5), ("ghij".to_string(), 1));
+    line.cursor_move(-3);
+    assert_eq!(line.view(5), ("efghi".to_string(), 0));
+    // Deleting pulls the text back in.
+    line.cursor_end();
+    for _ in 0..6 {
+        line.backward_remove_cursor();
+    }
+    assert_eq!(line.view(5), ("abcd".to_string(), 4));
+
+    let mut line = input("cafe\u{301}", 4);
+    assert_eq!(line.view(10), ("cafe\u{301}".to_string(), 4));
+}
+
+#[test]
+fn kill_ring() {
+    let mut line = input("open the pod bay doors", 12);
+    line.backward_kill_word();
+    assert_eq!(show(&line), "open the | bay doors");
+    // Consecutive kills join, backward kills in front.
+    line.backward_kill_word();
+    assert_eq!(show(&line), "open | bay doors");
+    line.forward_kill_line();
+    assert_eq!(show(&line), "open |");
+    assert_eq!(line.kill_ring(), &["the pod bay doors"]);
+
+    // Moving starts a new kill.
+    line.cursor_begin();
+    line.forward_kill_word();
+    assert_eq!(show(&line), "| ");
+    assert_eq!(line.kill_ring(), &["the pod bay doors", "open"]);
+
+    line.cursor_end();
+    line.yank();
+    assert_eq!(show(&line), " open|");
+    assert!(line.yank_pop());
+    assert_eq!(show(&line), " the pod bay doors|");
+    // Round the ring.
+    assert!(line.yank_pop());
+    assert_eq!(show(&line), " open|");
+
+    // Only right after a yank.
+    line.insert('!');
+    assert!(!line.yank_pop());
+    assert_eq!(show(&line), " open!|");
+
+    let mut line = input("", 0);
+    line.yank();
+    assert!(!line.yank_pop());
+    assert_eq!(show(&line), "|");
+
+    // Kills in the middle of a word and of a wide text.
+    let mut line = input("日本語 text", 2);
+    line.backward_kill_line();
+    line.cursor_end();
+    line.yank();
+    assert_eq!(show(&line), "語 text日本|");
+
+    // The oldest kills drop off.
+    let mut line = input("", 0);
+    for i in 0..KILL_RING_SIZE + 2 {
+        line.reset_buffer(i.to_string());
+        line.backward_kill_line();
+        line.cursor_end();
+    }
+    assert_eq!(line.kill_ring().len(), KILL_RING_SIZE);
+    assert_eq!(line.kill_ring()[0], "2");
+}
+
+#[test]
+fn undo_redo() {
+    let mut line = input("", 0);
+    for c in "hello".chars() {
+        line.insert(c);
+    }
+    line.insert(' ');
+    line.cursor_move(-1);
+    // Typing after moving is a new group.
+    line.insert(',');
+    line.cursor_end();
+    for c in "world".chars() {
+        line.insert(c);
+    }
+    line.backward_kill_word();
+    assert_eq!(show(&line), "hello, |");
+
+    let steps = [
+        "hello, world|",
+        "hello, |",
+        "hello| ",
+        "|",
+    ];
+    for step in steps {
+        assert!(line.undo());
+        assert_eq!(show(&line), step);
+    }
+    assert!(!line.undo());
+
+    for step in ["hello| ", "hello, |", "hello, world|", "hello, |"] {
+        assert!(line.redo());
+        assert_eq!(show(&line), step);
+    }
+    assert!(!line.redo());
+
+    // A new edit drops what was undone.
+    line.undo();
+    line.insert('!');
+    assert!(!line.redo());
+    assert_eq!(show(&line), "hello, world!|");
+
+    // Replacing the line, like tab completion, can be undone too.
+    line.reset_buffer("C:\\Users\\");
+    line.undo();
+    assert_eq!(show(&line), "hello, world!|");
+}
+
+#[test]
+fn transpose() {
+    let cases: &[(&str, usize, &str)] = &[
+        ("abc", 1, "ba|c"),
+        ("abc", 2, "acb|"),
+        // At the end, the last two.
+        ("abc", 3, "acb|"),
+        ("abc", 0, "|abc"),
+        ("a", 1, "a|"),
+        ("", 0, "|"),
+        ("e\u{301}x", 1, "xe\u{301}|"),
+        ("日本", 2, "本日|"),
+    ];
+    for (text, cursor, expected) in cases {
+        let mut line = input(text, *cursor);
+        line.transpose_chars();
+        assert_eq!(show(&line), *expected, "{} {}", text, cursor);
+    }
+
+    let mut line = input("ab", 1);
+    line.transpose_chars();
+    line.undo();
+    assert_eq!(show(&line), "a|b");
+}
+
+#[test]
+fn case_words() {
+    let cases: &[(&str, usize, fn(&mut InputLine), &str)] = &[
+        ("hello world", 0, InputLine::upcase_word, "HELLO| world"),
+        ("hello world", 5, InputLine::upcase_word, "hello WORLD|"),
+        ("HELLO World", 0, InputLine::downcase_word, "hello| World"),
+        ("hELLO world", 0, InputLine::capitalize_word, "Hello| world"),
+        // From the cursor on.
+        ("hello world", 2, InputLine::capitalize_word, "heLlo| world"),
+        ("c:\\users\\me", 2, InputLine::capitalize_word, "c:\\Users|\\me"),
+        ("größe", 0, InputLine::upcase_word, "GRÖSSE|"),
+        ("élan vital", 0, InputLine::capitalize_word, "Élan| vital"),
+        ("", 0, InputLine::upcase_word, "|"),
+    ];
+    for (text, cursor, change, expected) in cases {
+        let mut line = input(text, *cursor);
+        change(&mut line);
+        assert_eq!(show(&line), *expected, "{} {}", text, cursor);
+    }
+}
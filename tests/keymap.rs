@@ -0,0 +1,133 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use switch::config::Config;
+use switch::keymap::*;
+
+fn lookup(keymap: &Keymap, modifiers: KeyModifiers, code: KeyCode) -> Option<Command> {
+    return keymap.lookup(&KeyEvent::new(code, modifiers));
+}
+
+// cargo test --test keymap
+#[test]
+fn chords() {
+    let cases: &[(&str, KeyModifiers, KeyCode, &str)] = &[
+        ("ctrl+n", KeyModifiers::CONTROL, KeyCode::Char('n'), "ctrl+n"),
+        ("Ctrl+Shift+Delete", KeyModifiers::CONTROL | KeyModifiers::SHIFT, KeyCode::Delete, "ctrl+shift+delete"),
+        ("alt+O", KeyModifiers::ALT, KeyCode::Char('o'), "alt+o"),
+        ("control+enter", KeyModifiers::CONTROL, KeyCode::Enter, "ctrl+enter"),
+        ("escape", KeyModifiers::NONE, KeyCode::Esc, "esc"),
+        ("PgDn", KeyModifiers::NONE, KeyCode::PageDown, "pagedown"),
+        ("f1", KeyModifiers::NONE, KeyCode::F(1), "f1"),
+        ("?", KeyModifiers::NONE, KeyCode::Char('?'), "?"),
+        ("+", KeyModifiers::NONE, KeyCode::Char('+'), "+"),
+        ("ctrl++", KeyModifiers::CONTROL, KeyCode::Char('+'), "ctrl++"),
+        ("alt+space", KeyModifiers::ALT, KeyCode::Char(' '), "alt+space"),
+    ];
+    for (text, modifiers, code, display) in cases {
+        let chord = KeyChord::parse(text).unwrap();
+        assert_eq!(chord, KeyChord::new(*modifiers, *code), "{}", text);
+        assert_eq!(chord.to_string(), *display, "{}", text);
+        assert_eq!(KeyChord::parse(display).unwrap(), chord, "{}", text);
+    }
+
+    for text in ["", "ctrl+", "hyper+x", "f25", "ctrl+nn"] {
+        assert!(KeyChord::parse(text).is_err(), "{}", text);
+    }
+}
+
+#[test]
+fn default_keymap() {
+    let keymap = Keymap::default();
+    let cases: &[(KeyModifiers, KeyCode, Option<Command>)] = &[
+        (KeyModifiers::NONE, KeyCode::Enter, Some(Command::Accept)),
+        (KeyModifiers::CONTROL, KeyCode::Enter, Some(Command::AcceptElevated)),
+        (KeyModifiers::CONTROL, KeyCode::Char('c'), Some(Command::Exit)),
+        (KeyModifiers::CONTROL, KeyCode::Char('k'), Some(Command::KillLine)),
+        (KeyModifiers::SHIFT, KeyCode::Delete, Some(Command::KillTree)),
+        (KeyModifiers::NONE, KeyCode::Delete, Some(Command::Remove)),
+        (KeyModifiers::ALT, KeyCode::Up, Some(Command::RaisePriority)),
+        (KeyModifiers::NONE, KeyCode::F(1), Some(Command::Help)),
+        (KeyModifiers::SHIFT, KeyCode::Char('?'), Some(Command::Help)),
+        (KeyModifiers::CONTROL, KeyCode::Tab, Some(Command::NextProvider)),
+        (KeyModifiers::ALT, KeyCode::Char('1'), Some(Command::SelectProvider(0))),
+        (KeyModifiers::ALT, KeyCode::Char('9'), Some(Command::SelectProvider(8))),
+        // Shift falls back to the key without it, letters match either case.
+        (KeyModifiers::SHIFT, KeyCode::Left, Some(Command::BackwardChar)),
+        (KeyModifiers::CONTROL | KeyModifiers::SHIFT, KeyCode::Char('U'), Some(Command::BackwardKillLine)),
+        // Typed.
+        (KeyModifiers::NONE, KeyCode::Char('k'), None),
+        (KeyModifiers::SHIFT, KeyCode::Char('K'), None),
+        (KeyModifiers::CONTROL, KeyCode::Char('n'), None),
+    ];
+    for (modifiers, code, command) in cases {
+        assert_eq!(lookup(&keymap, *modifiers, *code), *command, "{:?} {:?}", modifiers, code);
+    }
+
+    assert_eq!(keymap.chords(Command::Exit), vec![
+        KeyChord::new(KeyModifiers::NONE, KeyCode::Esc),
+        KeyChord::new(KeyModifiers::CONTROL, KeyCode::Char('c')),
+    ]);
+    // Every command has a name that parses back to it.
+    for (name, command) in COMMANDS {
+        assert_eq!(Command::parse(name), Some(*command));
+        assert_eq!(command.name(), *name);
+    }
+}
+
+#[test]
+fn presets() {
+    let cases: &[(&str, KeyModifiers, KeyCode, Option<Command>)] = &[
+        ("emacs", KeyModifiers::CONTROL, KeyCode::Char('n'), Some(Command::ListNext)),
+        ("emacs", KeyModifiers::CONTROL, KeyCode::Char('p'), Some(Command::ListPrevious)),
+        ("emacs", KeyModifiers::ALT, KeyCode::Char('d'), Some(Command::KillWord)),
+        ("emacs", KeyModifiers::CONTROL, KeyCode::Char('k'), Some(Command::KillLine)),
+        ("emacs", KeyModifiers::NONE, KeyCode::Down, Some(Command::ListNext)),
+        ("vim", KeyModifiers::CONTROL, KeyCode::Char('j'), Some(Command::ListNext)),
+        ("vim", KeyModifiers::CONTROL, KeyCode::Char('k'), Some(Command::ListPrevious)),
+        ("vim", KeyModifiers::CONTROL, KeyCode::Char('u'), Some(Command::BackwardKillLine)),
+        ("vim", KeyModifiers::CONTROL, KeyCode::Char('n'), None),
+    ];
+    for (preset, modifiers, code, command) in cases {
+        let keymap = Keymap::preset(preset).unwrap();
+        assert_eq!(lookup(&keymap, *modifiers, *code), *command, "{} {:?} {:?}", preset, modifiers, code);
+    }
+    assert!(Keymap::preset("nano").is_err());
+}
+
+#[test]
+fn config_keys() {
+    let config = Config::parse(r#"{
+        "keymap": "vim",
+        "keys": {
+            "ctrl+n": "list-next",
+            "ctrl+j": "none",
+            "f2": "next-provider",
+            "ctrl+q": "no-such-command",
+            "hyper+q": "exit"
+        }
+    }"#).unwrap();
+    let keymap = Keymap::from_config(&config);
+
+    let cases: &[(KeyModifiers, KeyCode, Option<Command>)] = &[
+        (KeyModifiers::CONTROL, KeyCode::Char('n'), Some(Command::ListNext)),
+        (KeyModifiers::CONTROL, KeyCode::Char('j'), None),
+        (KeyModifiers::CONTROL, KeyCode::Char('k'), Some(Command::ListPrevious)),
+        (KeyModifiers::NONE, KeyCode::F(2), Some(Command::NextProvider)),
+        (KeyModifiers::NONE, KeyCode::F(1), Some(Command::Help)),
+        // Bad entries are skipped.
+        (KeyModifiers::CONTROL, KeyCode::Char('q'), None),
+    ];
+    for (modifiers, code, command) in cases {
+        assert_eq!(lookup(&keymap, *modifiers, *code), *command, "{:?} {:?}", modifiers, code);
+    }
+
+    let mut keymap = Keymap::default();
+    assert!(keymap.bind("ctrl+x", "accept").is_ok());
+    assert!(keymap.bind("ctrl+x", "nope").is_err());
+    assert!(keymap.bind("nope+x", "accept").is_err());
+    assert_eq!(lookup(&keymap, KeyModifiers::CONTROL, KeyCode::Char('x')), Some(Command::Accept));
+
+    // An unknown preset falls back to the default keys.
+    let keymap = Keymap::from_config(&Config::parse(r#"{"keymap": "nano"}"#).unwrap());
+    assert_eq!(lookup(&keymap, KeyModifiers::CONTROL, KeyCode::Char('k')), Some(Command::KillLine));
+}
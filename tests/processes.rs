@@ -0,0 +1,283 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use switch::processes::*;
+use switch::processprovider::ProcessProvider;
+use switch::listcontentprovider::Action;
+use switch::ListContentProvider;
+
+fn process(pid: u32, parent: u32, name: &str) -> ProcessInfo {
+    return ProcessInfo {
+        pid,
+        parent,
+        name: name.to_owned(),
+        priority: Some(Priority::Normal),
+        ..Default::default()
+    };
+}
+
+// Processes in memory, recording what was done to them.
+struct FakeSource {
+    processes: Rc<RefCell<Vec<ProcessInfo>>>,
+    log: Rc<RefCell<Vec<String>>>,
+}
+
+impl ProcessSource for FakeSource {
+    fn processes(&self) -> anyhow::Result<Vec<ProcessInfo>> {
+        return Ok(self.processes.borrow().clone());
+    }
+
+    fn kill(&self, pid: u32) -> anyhow::Result<()> {
+        self.log.borrow_mut().push(format!("kill {}", pid));
+        let mut processes = self.processes.borrow_mut();
+        let before = processes.len();
+        processes.retain(|p| p.pid != pid);
+        if processes.len() == before {
+            anyhow::bail!("No process {}", pid);
+        }
+        return Ok(());
+    }
+
+    fn set_priority(&self, pid: u32, priority: Priority) -> anyhow::Result<()> {
+        self.log.borrow_mut().push(format!("priority {} {:?}", pid, priority));
+        for process in self.processes.borrow_mut().iter_mut().filter(|p| p.pid == pid) {
+            process.priority = Some(priority);
+        }
+        return Ok(());
+    }
+}
+
+fn data(dir: &str) -> std::path::PathBuf {
+    return std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join(dir);
+}
+
+// cargo test --test processes
+#[test]
+fn proc_fs() {
+    let processes = ProcFs { root: data("proc") }.processes().unwrap();
+    let summary = processes.iter()
+        .map(|p| (p.pid, p.parent, p.name.as_str(), p.memory, p.cpu_time, p.priority))
+        .collect::<Vec<_>>();
+    assert_eq!(summary, vec![
+        (1, 0, "systemd", 12840 * 1024, Duration::from_millis(3800), Some(Priority::Normal)),
+        // Kernel threads have no memory of their own.
+        (2, 0, "kthreadd", 0, Duration::from_millis(30), Some(Priority::Normal)),
+        (812, 1, "tmux: server", 4400 * 1024, Duration::from_millis(90120), Some(Priority::Normal)),
+        // Names can have parentheses, nice 10 is below normal.
+        (813, 812, "my (odd) cmd", 2800 * 1024, Duration::from_millis(2000), Some(Priority::BelowNormal)),
+    ]);
+    let started = processes.iter().map(|p| p.creation_time).collect::<Vec<Option<u64>>>();
+    assert_eq!(started, vec![Some(9), Some(9), Some(5000), Some(6000)]);
+
+    assert_eq!(ProcFs::parse_stat(5, "5 (sh"), None);
+    assert_eq!(ProcFs::parse_stat(5, "5 (sh) S x"), None);
+    assert_eq!(ProcFs::parse_rss("Name:\tsh\nVmRSS:\t 12 kB\n"), Some(12 * 1024));
+    assert_eq!(ProcFs::parse_rss("Name:\tsh\n"), None);
+    assert!(ProcFs { root: data("missing") }.processes().is_err());
+}
+
+#[test]
+fn process_tree() {
+    let processes = vec![
+        process(812, 1, "tmux"),
+        process(1, 0, "init"),
+        process(900, 812, "vim"),
+        process(813, 812, "bash"),
+        process(950, 813, "cargo"),
+        // Parent exited.
+        process(40, 7777, "orphan"),
+        // System idle process is its own parent on Windows.
+        process(0, 0, "idle"),
+        // Parents loop from reused pids.
+        process(60, 61, "loop a"),
+        process(61, 60, "loop b"),
+    ];
+
+    let tree = tree(&processes).into_iter()
+        .map(|(depth, p)| (depth, p.pid))
+        .collect::<Vec<(usize, u32)>>();
+    assert_eq!(tree, vec![
+        (0, 0),
+        (0, 1),
+        (1, 812),
+        (2, 813),
+        (3, 950),
+        (2, 900),
+        (0, 40),
+        (0, 60),
+        (1, 61),
+    ]);
+
+    let cases: &[(u32, &[u32])] = &[
+        (812, &[812, 813, 900, 950]),
+        (950, &[950]),
+        (60, &[60, 61]),
+        (0, &[0]),
+        (4444, &[4444]),
+    ];
+    for (pid, expected) in cases {
+        assert_eq!(&descendants(&processes, *pid), expected, "{}", pid);
+    }
+
+    // 700 exited and its pid went to a later process, its old children aren't under it.
+    let started = |pid: u32, parent: u32, name: &str, creation_time: u64| ProcessInfo {
+        creation_time: Some(creation_time),
+        ..process(pid, parent, name)
+    };
+    let processes = vec![
+        started(700, 1, "new", 500),
+        started(701, 700, "old child", 200),
+        started(702, 700, "new child", 500),
+        started(703, 701, "grandchild", 300),
+        process(704, 700, "unknown start"),
+    ];
+    assert_eq!(descendants(&processes, 700), vec![700, 702, 704]);
+    // And in the tree the old child is a root of its own.
+    let tree = switch::processes::tree(&processes).into_iter()
+        .map(|(depth, p)| (depth, p.pid))
+        .collect::<Vec<(usize, u32)>>();
+    assert_eq!(tree, vec![(0, 700), (1, 702), (1, 704), (0, 701), (1, 703)]);
+    assert_eq!(descendants(&processes, 701), vec![701, 703]);
+
+    let log = Rc::new(RefCell::new(vec![]));
+    let source = FakeSource { processes: Rc::new(RefCell::new(processes)), log: log.clone() };
+    kill_tree(&source, 700).unwrap();
+    assert_eq!(*log.borrow(), vec!["kill 700", "kill 702", "kill 704"]);
+}
+
+#[test]
+fn formatting() {
+    let memory: &[(u64, &str)] = &[
+        (0, "0 B"),
+        (1023, "1023 B"),
+        (1536, "1.5 KB"),
+        (12840 * 1024, "12.5 MB"),
+        (3 * 1024 * 1024 * 1024, "3.0 GB"),
+        (5000 * 1024 * 1024 * 1024, "5000.0 GB"),
+    ];
+    for (bytes, text) in memory {
+        assert_eq!(format_memory(*bytes), *text);
+    }
+
+    let cpu: &[(u64, &str)] = &[
+        (0, "0.00s"),
+        (3450, "3.45s"),
+        (123_000, "2:03"),
+        (3_723_000, "1:02:03"),
+    ];
+    for (millis, text) in cpu {
+        assert_eq!(format_cpu_time(Duration::from_millis(*millis)), *text);
+    }
+
+    assert_eq!(Priority::Normal.step(true), Some(Priority::AboveNormal));
+    assert_eq!(Priority::Normal.step(false), Some(Priority::BelowNormal));
+    assert_eq!(Priority::Realtime.step(true), None);
+    assert_eq!(Priority::Idle.step(false), None);
+    for nice in -20..=19 {
+        let priority = Priority::from_nice(nice);
+        assert_eq!(Priority::from_nice(priority.nice()), priority, "{}", nice);
+    }
+}
+
+#[test]
+fn provider() {
+    let processes = Rc::new(RefCell::new(vec![
+        process(1, 0, "init"),
+        process(812, 1, "tmux"),
+        process(813, 812, "bash"),
+        process(950, 813, "cargo"),
+        process(1200, 1, "sshd"),
+    ]));
+    let log = Rc::new(RefCell::new(vec![]));
+    let mut provider = ProcessProvider::with_source(Box::new(FakeSource { processes: processes.clone(), log: log.clone() }));
+
+    assert_eq!(provider.query_for_names(), vec![
+        "init  1  0  0 B  0.00s  Normal",
+        "  tmux  812  1  0 B  0.00s  Normal",
+        "    bash  813  812  0 B  0.00s  Normal",
+        "      cargo  950  813  0 B  0.00s  Normal",
+        "  sshd  1200  1  0 B  0.00s  Normal",
+    ]);
+
+    // Searching is flat, by name or the start of the pid.
+    let cases: &[(&str, &[&str])] = &[
+        ("bash", &["813"]),
+        ("81", &["812", "813"]),
+        ("ssh", &["1200"]),
+        ("zzz", &[]),
+    ];
+    for (query, pids) in cases {
+        provider.set_query(query.to_string());
+        let copied = (0..pids.len()).map(|i| provider.copy_text(i).unwrap()).collect::<Vec<String>>();
+        assert_eq!(copied, *pids, "{}", query);
+        assert_eq!(provider.query_for_names().len(), pids.len(), "{}", query);
+    }
+
+    provider.set_query("tmux".into());
+    assert!(provider.run_action(0, Action::RaisePriority, false));
+    assert_eq!(processes.borrow()[1].priority, Some(Priority::AboveNormal));
+    assert!(provider.query_for_names()[0].ends_with("AboveNormal"));
+    assert!(provider.run_action(0, Action::LowerPriority, false));
+    assert!(provider.run_action(0, Action::LowerPriority, false));
+    assert_eq!(processes.borrow()[1].priority, Some(Priority::BelowNormal));
+
+    // Parents first, and the list is read again after.
+    assert!(provider.run_action(0, Action::KillTree, false));
+    assert_eq!(provider.query_for_names().len(), 0);
+    provider.set_query(String::new());
+    assert_eq!(provider.query_for_names().len(), 2);
+
+    provider.set_query("sshd".into());
+    provider.remove(0);
+    assert_eq!(provider.query_for_names().len(), 0);
+    provider.remove(0);
+
+    assert_eq!(*log.borrow(), vec![
+        "priority 812 AboveNormal",
+        "priority 812 Normal",
+        "priority 812 BelowNormal",
+        "kill 812",
+        "kill 813",
+        "kill 950",
+        "kill 1200",
+    ]);
+
+    // No executable path to show.
+    assert!(!provider.run_action(0, Action::OpenFolder, false));
+    assert_eq!(provider.prefix(), Some("&"));
+}
+
+#[test]
+fn marked_actions() {
+    let processes = Rc::new(RefCell::new(vec![
+        process(1, 0, "init"),
+        process(812, 1, "tmux"),
+        process(813, 812, "bash"),
+        process(950, 813, "cargo"),
+        process(1200, 1, "sshd"),
+        process(1300, 1, "cron"),
+        process(1400, 1, "nginx"),
+    ]));
+    let log = Rc::new(RefCell::new(vec![]));
+    let mut provider = ProcessProvider::with_source(Box::new(FakeSource { processes: processes.clone(), log: log.clone() }));
+
+    // Killing tmux takes its children out of the list, sshd is still what was marked.
+    assert!(provider.run_action_all(&[1, 4], Action::KillTree));
+    assert_eq!(provider.query_for_names(), vec![
+        "init  1  0  0 B  0.00s  Normal",
+        "  cron  1300  1  0 B  0.00s  Normal",
+        "  nginx  1400  1  0 B  0.00s  Normal",
+    ]);
+
+    assert!(provider.run_action_all(&[1, 2], Action::RaisePriority));
+    assert!(!provider.run_action_all(&[7], Action::LowerPriority));
+    assert_eq!(*log.borrow(), vec![
+        "kill 812",
+        "kill 813",
+        "kill 950",
+        "kill 1200",
+        "priority 1300 AboveNormal",
+        "priority 1400 AboveNormal",
+    ]);
+}
@@ -0,0 +1,66 @@
+use switch::queryhistory::*;
+
+// cargo test --test queryhistory
+#[test]
+fn record() {
+    let mut history = QueryHistory::new(3);
+    let cases: &[(&str, &str, bool, &[&str])] = &[
+        ("Apps", "code", true, &["code"]),
+        ("Apps", "wt", true, &["wt", "code"]),
+        // Duplicates move to the front.
+        ("Apps", "code", true, &["code", "wt"]),
+        ("Apps", "  ", false, &["code", "wt"]),
+        ("Apps", "notepad", true, &["notepad", "code", "wt"]),
+        // Capped per provider.
+        ("Apps", "calc", true, &["calc", "notepad", "code"]),
+        ("Files", "readme", true, &["calc", "notepad", "code"]),
+    ];
+    for (provider, query, recorded, entries) in cases {
+        assert_eq!(history.record(provider, query), *recorded, "{}", query);
+        assert_eq!(history.entries("Apps"), *entries, "{}", query);
+    }
+    assert_eq!(history.entries("Files"), &["readme"]);
+    assert_eq!(history.entries("Git").len(), 0);
+
+    assert!(!QueryHistory::new(0).record("Apps", "code"));
+}
+
+#[test]
+fn search() {
+    let mut history = QueryHistory::new(10);
+    for query in ["apple", "Grape", "apricot", "fig"] {
+        history.record("Fruit", query);
+    }
+    // fig apricot Grape apple
+    let cases: &[(&str, usize, Option<usize>)] = &[
+        ("ap", 0, Some(1)),
+        ("ap", 2, Some(2)),
+        ("AP", 3, Some(3)),
+        ("ap", 4, None),
+        ("", 0, Some(0)),
+        ("kiwi", 0, None),
+    ];
+    for (text, start, index) in cases {
+        assert_eq!(history.search("Fruit", text, *start), *index, "{} {}", text, start);
+    }
+    assert_eq!(history.search("Tools", "", 0), None);
+}
+
+#[test]
+fn persistence() {
+    let path = std::env::temp_dir().join(format!("switch-query-history-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(QueryHistory::load_from(&path, 5).unwrap(), QueryHistory::new(5));
+
+    let mut history = QueryHistory::new(5);
+    history.record("Apps", "code");
+    history.record("Web", "g rust tui");
+    history.save_to(&path).unwrap();
+    assert_eq!(QueryHistory::load_from(&path, 5).unwrap(), history);
+    std::fs::remove_file(&path).unwrap();
+
+    // Cleaned up when read, in case the file was edited.
+    let history = QueryHistory::parse(r#"{"Apps": ["a", "b", "a", "", "c"]}"#, 2).unwrap();
+    assert_eq!(history.entries("Apps"), &["a", "b"]);
+    assert!(QueryHistory::parse("[]", 2).is_err());
+}
@@ -0,0 +1,810 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use unicode_width::UnicodeWidthStr;
+use tui::{backend::TestBackend, style::{Color, Modifier}, Terminal};
+
+use switch::clipboard::MemoryClipboard;
+use switch::keymap::Command;
+use switch::searchablelistapp::*;
+use switch::theme::Theme;
+use switch::{ListContentProvider, ListItem};
+
+struct FakeItem {
+    name: String,
+}
+
+impl ListItem for FakeItem {
+    fn as_any(&self) -> &dyn std::any::Any {
+        return self;
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        return self;
+    }
+
+    fn as_string(&self) -> String {
+        return self.name.clone();
+    }
+
+    fn as_matchable_string(&self) -> String {
+        return self.name.clone();
+    }
+}
+
+// Lists the names containing the query, recording what was started.
+struct FakeProvider {
+    name: &'static str,
+    names: Vec<String>,
+    prefix: Option<&'static str>,
+    // What tab completes to instead of the selected name.
+    completion: Rc<RefCell<Option<String>>>,
+    filtered: Vec<FakeItem>,
+    started: Rc<RefCell<Vec<String>>>,
+}
+
+impl FakeProvider {
+    fn new(name: &'static str, names: &[&str], prefix: Option<&'static str>, completion: Rc<RefCell<Option<String>>>, started: Rc<RefCell<Vec<String>>>) -> Box<Self> {
+        let mut provider = Box::new(FakeProvider {
+            name,
+            names: names.iter().map(|name| name.to_string()).collect(),
+            prefix,
+            completion,
+            filtered: vec![],
+            started,
+        });
+        provider.set_query(String::new());
+        return provider;
+    }
+}
+
+impl ListContentProvider for FakeProvider {
+    fn query_for_items(&mut self) -> Vec<&mut dyn ListItem> {
+        return self.filtered.iter_mut().map(|e| e as &mut dyn ListItem).collect();
+    }
+
+    fn query_for_names(&mut self) -> Vec<String> {
+        return self.filtered.iter().map(|e| e.as_string()).collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.filtered = self.names.iter()
+            .filter(|name| name.contains(&query))
+            .map(|name| FakeItem { name: name.clone() })
+            .collect();
+    }
+
+    fn start(&mut self, filtered_index: usize, elevated: bool) {
+        let name = self.filtered.get(filtered_index).map(|e| e.name.clone()).unwrap_or_default();
+        self.started.borrow_mut().push(format!("{} {}", name, elevated));
+    }
+
+    fn remove(&mut self, filtered_index: usize) {
+        if filtered_index < self.filtered.len() {
+            let item = self.filtered.remove(filtered_index);
+            self.names.retain(|name| *name != item.name);
+        }
+    }
+
+    fn name(&self) -> &str {
+        return self.name;
+    }
+
+    fn copy_text(&mut self, filtered_index: usize) -> Option<String> {
+        return self.filtered.get(filtered_index).map(|e| e.name.to_uppercase());
+    }
+
+    fn complete(&mut self, _filtered_index: usize) -> Option<String> {
+        return self.completion.borrow().clone();
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        return self.prefix;
+    }
+
+    // Tools can be removed too, for more than one action.
+    fn actions(&mut self, _filtered_index: usize) -> Vec<(Command, &'static str)> {
+        if self.prefix.is_some() {
+            return vec![(Command::Accept, "start"), (Command::Remove, "remove")];
+        }
+        return vec![(Command::Accept, "start")];
+    }
+}
+
+fn key(code: KeyCode) -> Event {
+    return Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+}
+
+fn with(modifiers: KeyModifiers, code: KeyCode) -> Event {
+    return Event::Key(KeyEvent::new(code, modifiers));
+}
+
+fn typed(text: &str) -> Vec<Event> {
+    return text.chars().map(|c| key(KeyCode::Char(c))).collect();
+}
+
+struct Harness {
+    terminal: Terminal<TestBackend>,
+    app: SearchableListApp,
+    started: Rc<RefCell<Vec<String>>>,
+    completion: Rc<RefCell<Option<String>>>,
+}
+
+impl Harness {
+    // A list of fruit with no prefix and a list of tools with #.
+    fn new(width: u16, height: u16) -> Harness {
+        let started = Rc::new(RefCell::new(vec![]));
+        let completion = Rc::new(RefCell::new(None));
+        let providers: Vec<Box<dyn ListContentProvider>> = vec![
+            FakeProvider::new("Fruit", &["apple", "apricot", "banana", "blueberry", "cherry", "date", "fig", "grape", "kiwi", "lemon"], None, completion.clone(), started.clone()),
+            FakeProvider::new("Tools", &["hammer", "saw", "wrench"], Some("#"), completion.clone(), started.clone()),
+        ];
+        let mut app = SearchableListApp::new(providers, width, height);
+        app.clipboard = MemoryClipboard::new();
+        // Most tests are about the list, status_bar turns it back on.
+        app.theme.status_bar = false;
+
+        let mut harness = Harness {
+            terminal: Terminal::new(TestBackend::new(width, height)).unwrap(),
+            app,
+            started,
+            completion,
+        };
+        harness.terminal.draw(|f| ui(f, &mut harness.app)).unwrap();
+        return harness;
+    }
+
+    // Sends events one at a time like run_app, returns true if one of them exits.
+    fn send(&mut self, events: Vec<Event>) -> bool {
+        for event in events {
+            if let Event::Resize(width, height) = event {
+                self.terminal.backend_mut().resize(width, height);
+            }
+            if self.app.handle_event(&mut self.terminal, event).unwrap() {
+                return true;
+            }
+            self.terminal.draw(|f| ui(f, &mut self.app)).unwrap();
+        }
+        return false;
+    }
+
+    // The screen with trailing spaces trimmed, the selected row marked with * and the cursor
+    // in the input line with |.
+    fn screen(&mut self) -> Vec<String> {
+        // The cursor is hidden under the help.
+        let (cursor_x, cursor_y) = if self.app.help.is_some() { (u16::MAX, u16::MAX) } else { self.terminal.get_cursor().unwrap() };
+        let highlight = self.app.theme.highlight.bg.map(|color| color.0);
+        let buffer = self.terminal.backend().buffer();
+        let mut rows = vec![];
+        for y in 0..buffer.area.height {
+            let mut row = String::new();
+            let mut covered = false;
+            for x in 0..buffer.area.width {
+                if (x, y) == (cursor_x, cursor_y) {
+                    row.push('|');
+                }
+                // The cell after a wide character is under it on a real console.
+                if covered {
+                    covered = false;
+                    continue;
+                }
+                let symbol = &buffer.get(x, y).symbol;
+                covered = symbol.width() > 1;
+                row += symbol;
+            }
+            let selected = (0..buffer.area.width).any(|x| Some(buffer.get(x, y).bg) == highlight);
+            rows.push(format!("{}{}", if selected { "*" } else { " " }, row).trim_end().to_owned());
+        }
+        return rows;
+    }
+}
+
+// cargo test --test searchablelistapp
+#[test]
+fn typing() {
+    let mut harness = Harness::new(20, 5);
+    assert_eq!(harness.screen(), vec![" > |", " apple", " apricot", " banana", " blueberry"]);
+
+    let cases: &[(&str, &[&str])] = &[
+        ("a", &[" > a|", "*apple", " apricot", " banana", " date"]),
+        ("p", &[" > ap|", "*apple", " apricot", " grape", ""]),
+        ("r", &[" > apr|", "*apricot", "", "", ""]),
+        ("x", &[" > aprx|", "", "", "", ""]),
+    ];
+    for (text, screen) in cases {
+        harness.send(typed(text));
+        assert_eq!(harness.screen(), *screen, "{}", text);
+    }
+
+    harness.send(vec![key(KeyCode::Backspace), key(KeyCode::Backspace)]);
+    assert_eq!(harness.screen(), vec![" > ap|", "*apple", " apricot", " grape", ""]);
+    harness.send(vec![key(KeyCode::Down)]);
+    assert_eq!(harness.screen(), vec![" > ap|", " apple", "*apricot", " grape", ""]);
+    // Wraps around.
+    harness.send(vec![key(KeyCode::Down), key(KeyCode::Down)]);
+    assert_eq!(harness.screen(), vec![" > ap|", "*apple", " apricot", " grape", ""]);
+    harness.send(vec![key(KeyCode::Up)]);
+    assert_eq!(harness.screen(), vec![" > ap|", " apple", " apricot", "*grape", ""]);
+}
+
+#[test]
+fn cursor_movement() {
+    let mut harness = Harness::new(30, 2);
+    harness.send(typed("an apple a day"));
+    assert_eq!(harness.screen()[0], " > an apple a day|");
+
+    let cases: &[(Vec<Event>, &str)] = &[
+        (vec![key(KeyCode::Left)], " > an apple a da|y"),
+        (vec![with(KeyModifiers::CONTROL, KeyCode::Left)], " > an apple a |day"),
+        (vec![with(KeyModifiers::CONTROL, KeyCode::Left)], " > an apple |a day"),
+        (vec![key(KeyCode::Home)], " > |an apple a day"),
+        // Can't go past the start.
+        (vec![key(KeyCode::Left)], " > |an apple a day"),
+        (vec![with(KeyModifiers::CONTROL, KeyCode::Right)], " > an| apple a day"),
+        (vec![key(KeyCode::Right)], " > an |apple a day"),
+        (typed("old "), " > an old |apple a day"),
+        (vec![with(KeyModifiers::CONTROL, KeyCode::Backspace)], " > an |apple a day"),
+        (vec![with(KeyModifiers::CONTROL, KeyCode::Char('k'))], " > an |"),
+        (vec![key(KeyCode::End), key(KeyCode::Right)], " > an |"),
+        (typed("eye"), " > an eye|"),
+        (vec![key(KeyCode::Left), key(KeyCode::Left), with(KeyModifiers::CONTROL, KeyCode::Char('u'))], " > |ye"),
+    ];
+    for (events, input) in cases {
+        harness.send(events.clone());
+        assert_eq!(harness.screen()[0], *input);
+    }
+}
+
+#[test]
+fn paging() {
+    let mut harness = Harness::new(20, 5);
+    // Pages are the height of the list, the screen less the input line.
+    let cases: &[(KeyCode, &[&str])] = &[
+        (KeyCode::Down, &[" > |", "*apple", " apricot", " banana", " blueberry"]),
+        (KeyCode::PageDown, &[" > |", " apricot", " banana", " blueberry", "*cherry"]),
+        (KeyCode::PageDown, &[" > |", " date", " fig", " grape", "*kiwi"]),
+        // Wraps to the top past the end.
+        (KeyCode::PageDown, &[" > |", "*apple", " apricot", " banana", " blueberry"]),
+        (KeyCode::PageUp, &[" > |", " fig", " grape", " kiwi", "*lemon"]),
+        (KeyCode::PageUp, &[" > |", "*date", " fig", " grape", " kiwi"]),
+        (KeyCode::Up, &[" > |", "*cherry", " date", " fig", " grape"]),
+    ];
+    for (code, screen) in cases {
+        harness.send(vec![key(*code)]);
+        assert_eq!(harness.screen(), *screen, "{:?}", code);
+    }
+}
+
+#[test]
+fn tab_completion() {
+    let mut harness = Harness::new(20, 3);
+    // Nothing selected, nothing to complete.
+    harness.send(vec![key(KeyCode::Tab)]);
+    assert_eq!(harness.screen(), vec![" > |", " apple", " apricot"]);
+
+    harness.send(typed("rr"));
+    harness.send(vec![key(KeyCode::Down), key(KeyCode::Tab)]);
+    assert_eq!(harness.screen(), vec![" > cherry|", "*cherry", ""]);
+
+    // The provider's own completion wins.
+    harness.send(vec![key(KeyCode::F(2))]);
+    *harness.completion.borrow_mut() = Some("wr".into());
+    harness.send(vec![key(KeyCode::Down), key(KeyCode::Tab)]);
+    assert_eq!(harness.screen(), vec![" > wr|", "*wrench", ""]);
+}
+
+#[test]
+fn provider_switching() {
+    let mut harness = Harness::new(20, 4);
+    harness.send(typed("ap"));
+    harness.send(vec![key(KeyCode::F(2))]);
+    // F2 clears the input.
+    assert_eq!(harness.screen(), vec![" > |", " hammer", " saw", " wrench"]);
+    harness.send(vec![key(KeyCode::F(2))]);
+    assert_eq!(harness.screen(), vec![" > |", " apple", " apricot", " banana"]);
+
+    // A prefix jumps to its provider and isn't part of the query.
+    harness.send(typed("#w"));
+    assert_eq!(harness.screen(), vec![" > w|", "*saw", " wrench", ""]);
+    harness.send(typed("r"));
+    assert_eq!(harness.screen(), vec![" > wr|", "*wrench", "", ""]);
+
+    // Backspace on empty input goes back.
+    harness.send(vec![key(KeyCode::Backspace), key(KeyCode::Backspace)]);
+    assert_eq!(harness.screen(), vec![" > |", "*hammer", " saw", " wrench"]);
+    harness.send(vec![key(KeyCode::Backspace)]);
+    assert_eq!(harness.screen(), vec![" > |", "*apple", " apricot", " banana"]);
+    // And only once.
+    harness.send(vec![key(KeyCode::Backspace)]);
+    assert_eq!(harness.screen(), vec![" > |", "*apple", " apricot", " banana"]);
+}
+
+#[test]
+fn expression_without_prefix() {
+    let mut harness = Harness::new(20, 3);
+    harness.app.providers.push(switch::calculatorprovider::CalculatorProvider::new());
+
+    // A plain number is still searched for.
+    harness.send(typed("2"));
+    assert_eq!(harness.app.selected_provider, 0);
+    harness.send(typed("*3"));
+    assert_eq!(harness.app.selected_provider, 2);
+    assert_eq!(harness.screen(), vec![" > 2*3|", "*= 6", " = 0x6"]);
+
+    // Back to the fruit once it isn't an expression.
+    harness.send(typed("a"));
+    assert_eq!(harness.app.selected_provider, 0);
+    assert_eq!(harness.screen(), vec![" > 2*3a|", "", ""]);
+    harness.send(vec![key(KeyCode::Backspace)]);
+    assert_eq!(harness.app.selected_provider, 2);
+
+    // And from another list.
+    harness.send(vec![key(KeyCode::F(2)), key(KeyCode::F(2))]);
+    assert_eq!(harness.app.selected_provider, 1);
+    harness.send(typed("1+1"));
+    assert_eq!(harness.app.selected_provider, 2);
+    harness.send(vec![key(KeyCode::Backspace), key(KeyCode::Backspace)]);
+    assert_eq!(harness.app.selected_provider, 1);
+}
+
+#[test]
+fn resize() {
+    let mut harness = Harness::new(20, 5);
+    harness.send(typed("an"));
+    assert_eq!(harness.screen(), vec![" > an|", "*banana", "", "", ""]);
+
+    harness.send(vec![Event::Resize(8, 2)]);
+    assert_eq!(harness.app.screen_width, 8);
+    assert_eq!(harness.screen(), vec![" > an|", "*banana"]);
+
+    // Paging follows the new height.
+    harness.send(vec![key(KeyCode::Backspace), key(KeyCode::Backspace), key(KeyCode::PageDown)]);
+    assert_eq!(harness.screen(), vec![" > |", "*apricot"]);
+
+    // The selection stays in view.
+    harness.send(vec![Event::Resize(12, 3)]);
+    assert_eq!(harness.screen(), vec![" > |", "*apricot", " banana"]);
+}
+
+#[test]
+fn exit_and_clipboard() {
+    let mut harness = Harness::new(20, 3);
+    harness.send(typed("fig"));
+    assert!(harness.send(vec![with(KeyModifiers::ALT, KeyCode::Char('w'))]));
+    assert_eq!(harness.app.clipboard.get_text().unwrap(), "FIG");
+
+    // Right click pastes at the cursor.
+    harness.app.clipboard.set_text("ra").unwrap();
+    harness.send(vec![key(KeyCode::Home)]);
+    harness.send(vec![Event::Mouse(MouseEvent { kind: MouseEventKind::Down(MouseButton::Right), column: 0, row: 0, modifiers: KeyModifiers::NONE })]);
+    assert_eq!(harness.screen()[0], " > ra|fig");
+
+    assert!(harness.send(vec![key(KeyCode::Esc)]));
+    assert!(harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('c'))]));
+    assert_eq!(harness.started.borrow().len(), 0);
+}
+
+#[test]
+fn scripted_run() {
+    let cases: &[(Vec<Event>, &[&str], bool)] = &[
+        ([typed("berry"), vec![key(KeyCode::Enter)]].concat(), &["blueberry false"], true),
+        ([typed("che"), vec![with(KeyModifiers::CONTROL, KeyCode::Enter)]].concat(), &["cherry true"], true),
+        (vec![key(KeyCode::Down), key(KeyCode::Down), key(KeyCode::Enter)], &["apricot false"], true),
+        // Runs out of events without exiting.
+        (typed("kiwi"), &[], false),
+    ];
+    for (events, started, exits) in cases {
+        let mut harness = Harness::new(20, 4);
+        let mut script = ScriptedEvents::new(events.clone());
+        let result = run_app(&mut harness.terminal, &mut harness.app, &mut script, Duration::from_millis(1000));
+        assert_eq!(result.is_ok(), *exits, "{:?}", events);
+        assert_eq!(*harness.started.borrow(), *started, "{:?}", events);
+    }
+}
+
+#[test]
+fn keymap() {
+    let mut harness = Harness::new(20, 4);
+    harness.app.keymap = switch::keymap::Keymap::preset("vim").unwrap();
+    harness.send(typed("a"));
+    assert_eq!(harness.screen(), vec![" > a|", "*apple", " apricot", " banana"]);
+
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('j')), with(KeyModifiers::CONTROL, KeyCode::Char('j'))]);
+    assert_eq!(harness.screen(), vec![" > a|", " apple", " apricot", "*banana"]);
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('k'))]);
+    assert_eq!(harness.screen(), vec![" > a|", " apple", "*apricot", " banana"]);
+
+    // Unbound keys with modifiers still type, like AltGr characters.
+    harness.send(vec![with(KeyModifiers::CONTROL | KeyModifiers::ALT, KeyCode::Char('p'))]);
+    assert_eq!(harness.screen(), vec![" > ap|", "*apple", " apricot", " grape"]);
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('w'))]);
+    assert_eq!(harness.screen(), vec![" > |", "*apple", " apricot", " banana"]);
+
+    harness.app.keymap.bind("ctrl+j", "none").unwrap();
+    harness.app.keymap.bind("enter", "accept-elevated").unwrap();
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('j'))]);
+    assert_eq!(harness.screen(), vec![" > j|", "", "", ""]);
+    harness.send(vec![key(KeyCode::Backspace)]);
+    assert!(harness.send(vec![key(KeyCode::Enter)]));
+    assert_eq!(*harness.started.borrow(), vec!["apple true"]);
+}
+
+#[test]
+fn themes() {
+    let mut harness = Harness::new(24, 5);
+    harness.app.theme = Theme { status_bar: false, ..Theme::named("fzf").unwrap() };
+    harness.send(typed("an"));
+    // Input at the bottom, the list right above it.
+    assert_eq!(harness.screen(), vec!["*banana", "", "", "", " > an|"]);
+
+    // Matched characters are styled on their own.
+    let buffer = harness.terminal.backend().buffer();
+    let colors = (0..6).map(|x| buffer.get(x, 0).fg).collect::<Vec<Color>>();
+    assert_eq!(colors, vec![Color::Reset, Color::LightGreen, Color::LightGreen, Color::Reset, Color::Reset, Color::Reset]);
+
+    harness.app.theme = Theme::from_config(&serde_json::from_str(r##"{
+        "name": "high-contrast",
+        "prompt": "$ ",
+        "status_bar": false,
+        "highlight": {"fg": "black", "bg": "#ffd700"}
+    }"##).unwrap());
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('u'))]);
+    assert_eq!(harness.screen(), vec![
+        " ┌──────────────────────┐",
+        " │$ |                    │",
+        "*│apple                 │",
+        " │apricot               │",
+        " └──────────────────────┘",
+    ]);
+    let buffer = harness.terminal.backend().buffer();
+    assert_eq!(buffer.get(1, 2).bg, Color::Rgb(0xff, 0xd7, 0x00));
+    assert_eq!(buffer.get(1, 3).bg, Color::Black);
+    assert!(!buffer.get(1, 3).modifier.contains(Modifier::BOLD));
+
+    // A page is what fits inside the borders.
+    harness.send(vec![key(KeyCode::PageDown)]);
+    assert_eq!(harness.screen(), vec![
+        " ┌──────────────────────┐",
+        " │$ |                    │",
+        " │apricot               │",
+        "*│banana                │",
+        " └──────────────────────┘",
+    ]);
+}
+
+#[test]
+fn actions_menu() {
+    let mut harness = Harness::new(30, 5);
+    // Nothing selected, nothing to act on.
+    harness.send(vec![with(KeyModifiers::SHIFT, KeyCode::F(10))]);
+    assert!(harness.app.actions.is_none());
+
+    harness.send(typed("#"));
+    harness.send(vec![key(KeyCode::Down), with(KeyModifiers::SHIFT, KeyCode::F(10))]);
+    assert_eq!(harness.screen(), vec![
+        " > |",
+        " ┌ Actions ─────┐",
+        "*│start   enter │",
+        " │remove  delete│",
+        " └──────────────┘",
+    ]);
+    let buffer = harness.terminal.backend().buffer();
+    assert_eq!(buffer.get(2, 2).bg, Color::LightGreen);
+    assert_eq!(buffer.get(2, 3).bg, Color::Reset);
+
+    // Down and enter runs the action on the row the menu was opened for.
+    harness.send(vec![key(KeyCode::Down)]);
+    assert_eq!(harness.app.actions.as_ref().unwrap().selected, 1);
+    harness.send(vec![key(KeyCode::Enter)]);
+    assert!(harness.app.actions.is_none());
+    assert_eq!(harness.screen(), vec![" > |", " hammer", "*wrench", "", ""]);
+
+    // Esc closes it without exiting, other keys close it too.
+    harness.send(vec![with(KeyModifiers::SHIFT, KeyCode::F(10))]);
+    assert!(!harness.send(vec![key(KeyCode::Esc)]));
+    assert!(harness.app.actions.is_none());
+    harness.send(vec![with(KeyModifiers::SHIFT, KeyCode::F(10)), key(KeyCode::Left)]);
+    assert!(harness.app.actions.is_none());
+    assert_eq!(harness.screen()[2], "*wrench");
+
+    // Clicking an action runs it, clicking elsewhere closes the menu.
+    harness.send(vec![with(KeyModifiers::SHIFT, KeyCode::F(10))]);
+    harness.send(vec![mouse(MouseEventKind::Down(MouseButton::Left), 4)]);
+    assert!(harness.app.actions.is_none());
+    assert_eq!(harness.screen()[2], "*wrench");
+    harness.send(vec![with(KeyModifiers::SHIFT, KeyCode::F(10))]);
+    assert!(harness.send(vec![mouse(MouseEventKind::Down(MouseButton::Left), 2)]));
+    assert_eq!(*harness.started.borrow(), vec!["wrench false"]);
+
+    // Middle click opens it for the row under the pointer.
+    harness.send(vec![mouse(MouseEventKind::Down(MouseButton::Middle), 1)]);
+    assert_eq!(harness.screen()[1], "*┌ Actions ─────┐");
+
+    // An action's own key runs it too, enter runs the selected one.
+    harness.send(vec![key(KeyCode::Delete)]);
+    assert_eq!(harness.screen(), vec![" > |", "*wrench", "", "", ""]);
+    harness.send(vec![with(KeyModifiers::SHIFT, KeyCode::F(10))]);
+    assert!(harness.send(vec![key(KeyCode::Enter)]));
+    assert_eq!(*harness.started.borrow(), vec!["wrench false", "wrench false"]);
+}
+
+#[test]
+fn wide_input() {
+    let mut harness = Harness::new(10, 2);
+    harness.send(typed("日本語の"));
+    // The line scrolls, the prompt stays.
+    assert_eq!(harness.screen(), vec![" >  本語の|", ""]);
+    harness.send(vec![key(KeyCode::Home)]);
+    assert_eq!(harness.screen(), vec![" > |日本語の", ""]);
+    harness.send(vec![key(KeyCode::Right), key(KeyCode::Right), key(KeyCode::Backspace)]);
+    assert_eq!(harness.screen(), vec![" > 日|語の", ""]);
+
+    // Pasting places the cursor after what was pasted.
+    harness.app.clipboard.set_text("é").unwrap();
+    harness.send(vec![Event::Mouse(MouseEvent { kind: MouseEventKind::Down(MouseButton::Right), column: 0, row: 0, modifiers: KeyModifiers::NONE })]);
+    assert_eq!(harness.screen(), vec![" > 日é|語の", ""]);
+}
+
+#[test]
+fn editing() {
+    let mut harness = Harness::new(20, 2);
+    harness.send(typed("app ban"));
+    assert_eq!(harness.screen(), vec![" > app ban|", ""]);
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Backspace)]);
+    assert_eq!(harness.screen(), vec![" > app |", ""]);
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('u')), with(KeyModifiers::CONTROL, KeyCode::Char('y'))]);
+    assert_eq!(harness.screen(), vec![" > app ban|", ""]);
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('z'))]);
+    assert_eq!(harness.screen(), vec![" > |", "*apple"]);
+    harness.send(vec![with(KeyModifiers::CONTROL | KeyModifiers::SHIFT, KeyCode::Char('Z'))]);
+    assert_eq!(harness.screen(), vec![" > app ban|", ""]);
+    harness.send(vec![key(KeyCode::Home), with(KeyModifiers::ALT, KeyCode::Char('u'))]);
+    assert_eq!(harness.screen(), vec![" > APP| ban", ""]);
+}
+
+#[test]
+fn query_history() {
+    let mut harness = Harness::new(20, 3);
+    for query in ["fig", "ban", "app"] {
+        harness.send(typed(query));
+        assert!(harness.send(vec![key(KeyCode::Enter)]));
+        harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('u'))]);
+    }
+    harness.send(typed("#saw"));
+    assert!(harness.send(vec![key(KeyCode::Enter)]));
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('u')), key(KeyCode::Backspace)]);
+    assert_eq!(harness.app.history.entries("Fruit"), &["app", "ban", "fig"]);
+    assert_eq!(harness.app.history.entries("Tools"), &["saw"]);
+
+    // Up at the top of the list with nothing typed.
+    harness.send(vec![key(KeyCode::Up)]);
+    assert_eq!(harness.screen(), vec![" > app|", "*apple", ""]);
+    harness.send(vec![key(KeyCode::Up), key(KeyCode::Up)]);
+    assert_eq!(harness.screen(), vec![" > fig|", "*fig", ""]);
+    // Stays at the oldest.
+    harness.send(vec![key(KeyCode::Up)]);
+    assert_eq!(harness.screen(), vec![" > fig|", "*fig", ""]);
+    harness.send(vec![key(KeyCode::Down)]);
+    assert_eq!(harness.screen(), vec![" > ban|", "*banana", ""]);
+    harness.send(vec![key(KeyCode::Down), key(KeyCode::Down)]);
+    assert_eq!(harness.screen(), vec![" > |", "*apple", " apricot"]);
+
+    // Typing stops going through the history, up moves in the list again.
+    harness.send(vec![key(KeyCode::Up)]);
+    harness.send(typed("a"));
+    harness.send(vec![key(KeyCode::Up)]);
+    assert_eq!(harness.screen(), vec![" > appa|", "", ""]);
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('u')), key(KeyCode::Down), key(KeyCode::Up)]);
+    assert_eq!(harness.screen(), vec![" > |", "*apple", " apricot"]);
+
+    // Entering a query again moves it to the front.
+    harness.send(typed("ban"));
+    assert!(harness.send(vec![key(KeyCode::Enter)]));
+    assert_eq!(harness.app.history.entries("Fruit"), &["ban", "app", "fig"]);
+}
+
+#[test]
+fn history_search() {
+    let mut harness = Harness::new(30, 3);
+    for query in ["grape", "apricot", "apple"] {
+        harness.app.history.record("Fruit", query);
+    }
+    harness.send(typed("ki"));
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('r'))]);
+    assert_eq!(harness.screen(), vec![" (history '') ki|", "*kiwi", ""]);
+    harness.send(typed("ap"));
+    assert_eq!(harness.screen(), vec![" (history 'ap') apple|", "*apple", ""]);
+    // Older matches.
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('r'))]);
+    assert_eq!(harness.screen(), vec![" (history 'ap') apricot|", "*apricot", ""]);
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('r'))]);
+    assert_eq!(harness.screen(), vec![" (history 'ap') grape|", "*grape", ""]);
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('r'))]);
+    assert_eq!(harness.screen(), vec![" (failed history 'ap') grape|", "*grape", ""]);
+    harness.send(typed("x"));
+    assert_eq!(harness.screen(), vec![" (failed history 'apx') grape|", "*grape", ""]);
+    harness.send(vec![key(KeyCode::Backspace)]);
+    assert_eq!(harness.screen(), vec![" (history 'ap') apple|", "*apple", ""]);
+
+    // Escape puts back what was typed.
+    harness.send(vec![key(KeyCode::Esc)]);
+    assert_eq!(harness.screen(), vec![" > ki|", "*kiwi", ""]);
+
+    // Anything else takes the match and runs.
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('r'))]);
+    harness.send(typed("cot"));
+    harness.send(vec![key(KeyCode::Left)]);
+    assert_eq!(harness.screen(), vec![" > aprico|t", "*apricot", ""]);
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('r')), key(KeyCode::Char('g'))]);
+    assert!(harness.send(vec![key(KeyCode::Enter)]));
+    assert_eq!(*harness.started.borrow(), vec!["grape false"]);
+}
+
+#[test]
+fn history_search_bound_keys() {
+    let mut harness = Harness::new(30, 3);
+    harness.app.history.record("Fruit", "fig?");
+    harness.app.history.record("Fruit", "red apple");
+    // Space marks and ? opens help outside the search.
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('r'))]);
+    harness.send(typed("d a"));
+    assert_eq!(harness.screen(), vec![" (history 'd a') red apple|", "", ""]);
+    harness.send(vec![key(KeyCode::Backspace), key(KeyCode::Backspace), key(KeyCode::Backspace)]);
+    harness.send(typed("g?"));
+    assert_eq!(harness.screen(), vec![" (history 'g?') fig?|", "", ""]);
+    harness.send(vec![key(KeyCode::Esc)]);
+    assert_eq!(harness.screen(), vec![" > |", "*apple", " apricot"]);
+
+    // AltGr characters arrive with ctrl and alt.
+    harness.app.history.record("Fruit", "a@b");
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('r')), with(KeyModifiers::CONTROL | KeyModifiers::ALT, KeyCode::Char('@'))]);
+    assert_eq!(harness.screen()[0], " (history '@') a@b|");
+}
+
+fn mouse(kind: MouseEventKind, row: u16) -> Event {
+    return Event::Mouse(MouseEvent { kind, column: 2, row, modifiers: KeyModifiers::NONE });
+}
+
+#[test]
+fn mouse_clicks_and_wheel() {
+    let mut harness = Harness::new(20, 5);
+    let cases: &[(Event, &[&str])] = &[
+        (mouse(MouseEventKind::Down(MouseButton::Left), 3), &[" > |", " apple", " apricot", "*banana", " blueberry"]),
+        // The selection stays on screen as the list scrolls under it.
+        (mouse(MouseEventKind::ScrollDown, 3), &[" > |", "*blueberry", " cherry", " date", " fig"]),
+        (mouse(MouseEventKind::ScrollDown, 3), &[" > |", "*fig", " grape", " kiwi", " lemon"]),
+        (mouse(MouseEventKind::ScrollDown, 3), &[" > |", "*fig", " grape", " kiwi", " lemon"]),
+        (mouse(MouseEventKind::ScrollUp, 3), &[" > |", " blueberry", " cherry", " date", "*fig"]),
+        (mouse(MouseEventKind::Drag(MouseButton::Left), 2), &[" > |", " blueberry", "*cherry", " date", " fig"]),
+        // Off the list.
+        (mouse(MouseEventKind::Down(MouseButton::Left), 0), &[" > |", " blueberry", "*cherry", " date", " fig"]),
+        // Keys scroll from where the wheel left the list.
+        (key(KeyCode::Up), &[" > |", "*blueberry", " cherry", " date", " fig"]),
+        (key(KeyCode::Up), &[" > |", "*banana", " blueberry", " cherry", " date"]),
+    ];
+    for (event, screen) in cases {
+        harness.send(vec![*event]);
+        assert_eq!(harness.screen(), *screen, "{:?}", event);
+    }
+
+    // Past the end of a short list.
+    harness.send(typed("kiw"));
+    harness.send(vec![mouse(MouseEventKind::Down(MouseButton::Left), 3)]);
+    assert_eq!(harness.screen(), vec![" > kiw|", "*kiwi", "", "", ""]);
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('u'))]);
+
+    // Two clicks on different rows aren't a double click.
+    assert!(!harness.send(vec![mouse(MouseEventKind::Down(MouseButton::Left), 1), mouse(MouseEventKind::Down(MouseButton::Left), 2)]));
+    assert!(harness.send(vec![mouse(MouseEventKind::Down(MouseButton::Left), 2)]));
+    assert_eq!(*harness.started.borrow(), vec!["apricot false"]);
+}
+
+#[test]
+fn marking() {
+    let mut harness = Harness::new(24, 5);
+    let input = |line: &str, marked: &str| format!(" > {}|{:>w$}", line, marked, w = 24 - 2 - line.len()).trim_end().to_owned();
+    // Space types until the list is moved through.
+    harness.send(typed("ap "));
+    assert_eq!(harness.screen(), vec![input("ap ", ""), "".into(), "".into(), "".into(), "".into()]);
+    harness.send(vec![key(KeyCode::Backspace), key(KeyCode::Down), key(KeyCode::Char(' '))]);
+    assert_eq!(harness.screen(), vec![input("ap", " 1 marked"), "   apple".into(), " + apricot".into(), "*  grape".into(), "".into()]);
+    // Marks go with the query.
+    harness.send(vec![key(KeyCode::Backspace)]);
+    assert_eq!(harness.screen(), vec![input("a", ""), "*apple".into(), " apricot".into(), " banana".into(), " date".into()]);
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('u'))]);
+
+    let ctrl_click = |row| Event::Mouse(MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), column: 4, row, modifiers: KeyModifiers::CONTROL });
+    harness.send(vec![ctrl_click(2), ctrl_click(4), ctrl_click(1), ctrl_click(1)]);
+    assert_eq!(harness.screen(), vec![input("", " 2 marked"), "*  apple".into(), " + apricot".into(), "   banana".into(), " + blueberry".into()]);
+    assert!(harness.send(vec![with(KeyModifiers::ALT, KeyCode::Char('w'))]));
+    assert_eq!(harness.app.clipboard.get_text().unwrap(), "APRICOT\nBLUEBERRY");
+
+    harness.send(vec![key(KeyCode::Delete)]);
+    assert_eq!(harness.screen(), vec![input("", ""), "*apple".into(), " banana".into(), " cherry".into(), " date".into()]);
+
+    // Space marks and moves down, enter starts everything marked.
+    harness.send(vec![key(KeyCode::Down), key(KeyCode::Char(' ')), key(KeyCode::Char(' '))]);
+    assert_eq!(harness.screen(), vec![input("", " 2 marked"), "   apple".into(), " + banana".into(), " + cherry".into(), "*  date".into()]);
+    assert!(harness.send(vec![key(KeyCode::Enter)]));
+    assert_eq!(*harness.started.borrow(), vec!["banana false", "cherry false"]);
+}
+
+#[test]
+fn status_bar() {
+    let mut harness = Harness::new(40, 5);
+    harness.app.theme.status_bar = true;
+    harness.send(typed("an"));
+    // The current provider's tab is highlighted, so its row is marked too.
+    assert_eq!(harness.screen(), vec![
+        " > an|",
+        "* 1 Fruit  2 Tools   1  enter start",
+        "*banana",
+        "",
+        "",
+    ]);
+    let buffer = harness.terminal.backend().buffer();
+    assert_eq!(buffer.get(1, 1).bg, Color::LightGreen);
+    assert_eq!(buffer.get(10, 1).bg, Color::Reset);
+
+    // Alt+2 jumps to the second tab, hints show whatever keys the commands are bound to.
+    harness.app.keymap.bind("enter", "none").unwrap();
+    harness.app.keymap.bind("ctrl+o", "accept").unwrap();
+    harness.send(vec![with(KeyModifiers::ALT, KeyCode::Char('2')), key(KeyCode::Down)]);
+    assert_eq!(harness.screen(), vec![
+        " > |",
+        "* 1 Fruit  2 Tools   3  ctrl+o start",
+        "*hammer",
+        " saw",
+        " wrench",
+    ]);
+    let buffer = harness.terminal.backend().buffer();
+    assert_eq!(buffer.get(1, 1).bg, Color::Reset);
+    assert_eq!(buffer.get(10, 1).bg, Color::LightGreen);
+
+    // Nothing to hint at without a selection, and no tab for a provider that isn't there.
+    harness.send(typed("x"));
+    harness.send(vec![with(KeyModifiers::ALT, KeyCode::Char('3'))]);
+    assert_eq!(harness.screen()[1], "* 1 Fruit  2 Tools   0");
+    assert_eq!(harness.app.selected_provider, 1);
+}
+
+#[test]
+fn help() {
+    let mut harness = Harness::new(30, 6);
+    // ? types unless the input is empty.
+    harness.send(typed("a?"));
+    assert_eq!(harness.screen()[0], " > a?|");
+    harness.send(vec![with(KeyModifiers::CONTROL, KeyCode::Char('u')), key(KeyCode::Char('?'))]);
+    assert_eq!(harness.screen(), vec![
+        " ┌ Help, esc closes ──────────┐",
+        " │Keys                        │",
+        " │  enter           accept    │",
+        " │  ctrl+enter      accept-ele│",
+        " │  esc, ctrl+c     exit      │",
+        " └────────────────────────────┘",
+    ]);
+    harness.send(vec![key(KeyCode::Down), key(KeyCode::PageDown)]);
+    assert_eq!(harness.screen()[1], " │  up              list-previ│");
+
+    // The lists with their prefixes at the end.
+    harness.send(vec![key(KeyCode::End)]);
+    assert_eq!(harness.screen(), vec![
+        " ┌ Help, esc closes ──────────┐",
+        " │                            │",
+        " │Lists                       │",
+        " │  alt+1  Fruit              │",
+        " │  alt+2  Tools  #           │",
+        " └────────────────────────────┘",
+    ]);
+
+    // Esc closes it without exiting, F1 opens it too.
+    assert!(!harness.send(vec![key(KeyCode::Esc)]));
+    assert_eq!(harness.screen()[0], " > |");
+    harness.send(vec![key(KeyCode::F(1))]);
+    assert!(harness.app.help.is_some());
+    harness.send(vec![key(KeyCode::Char('q'))]);
+    assert!(harness.app.help.is_none());
+    assert_eq!(harness.screen()[0], " > |");
+}
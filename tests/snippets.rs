@@ -0,0 +1,155 @@
+use switch::clipboard::{Clipboard, MemoryClipboard};
+use switch::snippetprovider::SnippetProvider;
+use switch::snippets::*;
+use switch::ListContentProvider;
+
+fn snippet(name: &str, tags: &[&str], text: &str) -> Snippet {
+    return Snippet {
+        name: name.to_owned(),
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        text: text.to_owned(),
+    };
+}
+
+fn snippets() -> Vec<Snippet> {
+    return vec![
+        snippet("lgtm", &["review"], "Looks good to me, thanks {input:author}!"),
+        snippet("signature", &["mail"], "Best regards\r\nAlex"),
+        snippet("nit", &["review"], "Nit: {input}"),
+        snippet("ticket", &[], "https://jira.example.com/browse/{input:id} {input:id}"),
+        snippet("today", &[], "Updated {date} {time} from {clipboard}"),
+    ];
+}
+
+fn names(found: Vec<&Snippet>) -> Vec<&str> {
+    return found.into_iter().map(|s| s.name.as_str()).collect();
+}
+
+// cargo test --test snippets
+#[test]
+fn search_names_and_tags() {
+    let snippets = snippets();
+    assert_eq!(names(search(&snippets, "")), vec!["lgtm", "nit", "signature", "ticket", "today"]);
+    assert_eq!(names(search(&snippets, "sig")), vec!["signature"]);
+    // Name matches beat tag matches.
+    assert_eq!(names(search(&snippets, "review")), vec!["lgtm", "nit"]);
+    assert_eq!(names(search(&snippets, "review ni")), vec!["nit"]);
+    assert_eq!(names(search(&snippets, "mail sig")), vec!["signature"]);
+    assert_eq!(names(search(&snippets, "xyz")), Vec::<&str>::new());
+}
+
+#[test]
+fn placeholders() {
+    let now = chrono::NaiveDate::from_ymd_opt(2022, 7, 4).unwrap().and_hms_opt(9, 5, 0).unwrap();
+    let clipboard_reads = std::cell::Cell::new(0);
+    let mut clipboard = || {
+        clipboard_reads.set(clipboard_reads.get() + 1);
+        return "copied".to_owned();
+    };
+
+    let inputs = vec![("id".to_owned(), "SW-12".to_owned())];
+    assert_eq!(expand("{input:id}: {input:id} {input:other} {{x}}", &inputs, &now, &mut clipboard), "SW-12: SW-12 {input:other} {x}");
+    assert_eq!(clipboard_reads.get(), 0);
+    assert_eq!(expand("Updated {date} {time} from {clipboard}", &[], &now, &mut clipboard), "Updated 2022-07-04 09:05 from copied");
+    assert_eq!(clipboard_reads.get(), 1);
+
+    let snippets = snippets();
+    assert_eq!(snippets[0].inputs(), vec!["author"]);
+    assert_eq!(snippets[2].inputs(), vec!["input"]);
+    assert_eq!(snippets[3].inputs(), vec!["id"]);
+    assert_eq!(snippets[4].inputs(), Vec::<String>::new());
+}
+
+#[test]
+fn load_toml_and_directory() {
+    let toml = r#"
+[[snippet]]
+name = "lgtm"
+tags = ["review"]
+text = "Looks good to me!"
+
+[[snippet]]
+name = "sig"
+text = """
+Best regards
+Alex"""
+"#;
+    assert_eq!(parse_toml(toml).unwrap(), vec![
+        snippet("lgtm", &["review"], "Looks good to me!"),
+        snippet("sig", &[], "Best regards\nAlex"),
+    ]);
+    assert!(parse_toml("[[snippet]]\ntags = []").is_err());
+
+    let root = std::env::temp_dir().join(format!("switch_snippets_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("review").join("code")).unwrap();
+    std::fs::write(root.join("signature.txt"), "Best regards\r\n").unwrap();
+    std::fs::write(root.join("review").join("code").join("nit.md"), "Nit: {input}\n\n").unwrap();
+
+    assert_eq!(load(&root).unwrap(), vec![
+        snippet("nit", &["review", "code"], "Nit: {input}\n"),
+        snippet("signature", &[], "Best regards"),
+    ]);
+    assert_eq!(load(root.join("missing")).unwrap(), vec![]);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn provider_prompts_for_inputs() {
+    let mut clipboard = MemoryClipboard::new();
+    clipboard.set_text("copied").unwrap();
+    let mut provider = SnippetProvider::with_snippets(snippets(), clipboard);
+
+    provider.set_query("sig".to_owned());
+    assert_eq!(provider.query_for_names(), vec!["signature [mail]  Best regards⏎Alex"]);
+    assert_eq!(provider.navigate(0), None);
+    provider.start(0, false);
+    assert_eq!(provider.clipboard().get_text().unwrap(), "Best regards\r\nAlex");
+
+    // Enter asks for each input in the input line, the last one starts the snippet.
+    provider.set_query("ticket".to_owned());
+    assert_eq!(provider.navigate(0), Some(String::new()));
+    assert_eq!(provider.prompting(), Some("id"));
+    provider.set_query("SW-12".to_owned());
+    assert_eq!(provider.query_for_names(), vec!["ticket id: https://jira.example.com/browse/SW-12 SW-12"]);
+    assert_eq!(provider.navigate(0), None);
+    provider.start(0, false);
+    assert_eq!(provider.clipboard().get_text().unwrap(), "https://jira.example.com/browse/SW-12 SW-12");
+}
+
+#[test]
+fn provider_abandons_prompt() {
+    let mut provider = SnippetProvider::with_snippets(snippets(), MemoryClipboard::new());
+    let all = provider.query_for_names().len();
+
+    // Backspace in the empty input goes back to the snippets, and only then.
+    provider.set_query("ticket".to_owned());
+    assert_eq!(provider.navigate(0), Some(String::new()));
+    provider.set_query("SW".to_owned());
+    assert_eq!(provider.backspace_query(), None);
+    provider.set_query(String::new());
+    assert_eq!(provider.backspace_query(), Some(String::new()));
+    assert_eq!(provider.prompting(), None);
+    provider.set_query(String::new());
+    assert_eq!(provider.query_for_names().len(), all);
+    assert_eq!(provider.backspace_query(), None);
+
+    // Going to another provider.
+    provider.set_query("ticket".to_owned());
+    assert_eq!(provider.navigate(0), Some(String::new()));
+    provider.leave();
+    assert_eq!(provider.prompting(), None);
+    provider.set_query("ticket".to_owned());
+    assert_eq!(provider.query_for_names().len(), 1);
+    assert!(provider.query_for_names()[0].starts_with("ticket  "));
+
+    // Starting copies with the values and forgets them.
+    assert_eq!(provider.navigate(0), Some(String::new()));
+    provider.set_query("SW-1".to_owned());
+    assert_eq!(provider.navigate(0), None);
+    provider.start(0, false);
+    assert_eq!(provider.clipboard().get_text().unwrap(), "https://jira.example.com/browse/SW-1 SW-1");
+    assert_eq!(provider.prompting(), None);
+    provider.set_query("SW-1".to_owned());
+    assert_eq!(provider.query_for_names().len(), 0);
+}
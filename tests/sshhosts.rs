@@ -0,0 +1,165 @@
+use switch::sshhosts::*;
+use switch::sshhostprovider::SshHostProvider;
+use switch::ListContentProvider;
+
+const CONFIG: &str = "
+# Personal servers
+Host build build.lan
+    HostName 10.0.0.5
+    User ci
+
+Host *.corp !bastion.corp
+    User me
+
+Host=pi
+  Hostname = raspberrypi.local
+  Port 2222
+  User pi
+Include conf.d/*
+
+Match host build
+    User other
+
+Host \"quoted host\" *
+    IdentityFile ~/.ssh/id_ed25519
+";
+
+const INCLUDED: &str = "
+Host work
+    HostName work.example.com
+Host build
+    HostName ignored.example.com
+";
+
+const KNOWN_HOSTS: &str = "
+github.com,140.82.121.3 ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl
+10.0.0.5 ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl
+[gitlab.local]:2200 ecdsa-sha2-nistp256 AAAAE2VjZHNhLXNoYTItbmlzdHAyNTYAAAAIbmlzdHAyNTY=
+[gitlab.local]:22 ecdsa-sha2-nistp256 AAAAE2VjZHNhLXNoYTItbmlzdHAyNTYAAAAIbmlzdHAyNTY=
+*.corp,!bastion.corp ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQ==
+@cert-authority *.example.com ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQ==
+@revoked revoked.example.com ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQ==
+|1|AAECAwQFBgcICQoLDA0ODxAREhM=|W4noGNn85u2RYnTzk9lkbF+uhHI= ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl
+|1|AAECAwQFBgcICQoLDA0ODxAREhM=|ZepK/qQaJF7Tgv6coCH9Rf/Cbdo= ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl
+";
+
+fn host(name: &str, hostname: Option<&str>, user: Option<&str>, port: Option<u16>) -> SshHost {
+    return SshHost {
+        name: name.to_owned(),
+        hostname: hostname.map(|h| h.to_owned()),
+        user: user.map(|u| u.to_owned()),
+        port,
+        configured: true,
+    };
+}
+
+fn known_host(name: &str, port: Option<u16>) -> SshHost {
+    return SshHost {
+        port,
+        ..SshHost::new(name)
+    };
+}
+
+fn parse_sample_config() -> Vec<SshHost> {
+    let mut hosts = vec![];
+    let mut included = vec![];
+    parse_config(CONFIG, &mut hosts, &mut |pattern| {
+        included.push(pattern.to_owned());
+        return vec![INCLUDED.to_owned()];
+    });
+    assert_eq!(included, vec!["conf.d/*"]);
+    return hosts;
+}
+
+// cargo test --test sshhosts
+#[test]
+fn config_hosts() {
+    assert_eq!(parse_sample_config(), vec![
+        host("build", Some("10.0.0.5"), Some("ci"), None),
+        host("build.lan", Some("10.0.0.5"), Some("ci"), None),
+        host("pi", Some("raspberrypi.local"), Some("pi"), Some(2222)),
+        host("work", Some("work.example.com"), None, None),
+        host("quoted host", None, None, None),
+    ]);
+}
+
+#[test]
+fn config_includes() {
+    let root = std::env::temp_dir().join(format!("switch_sshhosts_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("conf.d")).unwrap();
+    std::fs::write(root.join("config"), "Include conf.d/*.conf\nHost main\n").unwrap();
+    std::fs::write(root.join("conf.d").join("b.conf"), "Host b\n").unwrap();
+    std::fs::write(root.join("conf.d").join("a.conf"), "Host a\nInclude loop\n").unwrap();
+    std::fs::write(root.join("conf.d").join("skipped.txt"), "Host skipped\n").unwrap();
+    // Includes itself, loading has to give up eventually.
+    std::fs::write(root.join("loop"), "Host loop\nInclude loop\n").unwrap();
+
+    let names = load_config(root.join("config")).into_iter().map(|h| h.name).collect::<Vec<String>>();
+    assert_eq!(names, vec!["a", "loop", "b", "main"]);
+    assert_eq!(load_config(root.join("missing")).len(), 0);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn known_hosts() {
+    let known = parse_known_hosts(KNOWN_HOSTS);
+    assert_eq!(known.hosts, vec![
+        known_host("github.com", None),
+        known_host("140.82.121.3", None),
+        known_host("10.0.0.5", None),
+        known_host("gitlab.local", Some(2200)),
+        known_host("gitlab.local", None),
+    ]);
+
+    assert_eq!(known.hashed_len(), 2);
+    assert!(known.contains_hashed("secret.example.com", None));
+    assert!(known.contains_hashed("secret.example.com", Some(22)));
+    assert!(known.contains_hashed("secret.example.com", Some(2222)));
+    assert!(!known.contains_hashed("secret.example.com", Some(2200)));
+    assert!(!known.contains_hashed("other.example.com", None));
+
+    assert_eq!(known.hosts[3].ssh_args(), "-p 2200 gitlab.local");
+    assert_eq!(host("quoted host", None, None, None).ssh_args(), "\"quoted host\"");
+    // ssh reads the port of configured hosts from the config.
+    assert_eq!(host("pi", None, None, Some(2222)).ssh_args(), "pi");
+
+    // 10.0.0.5 is the HostName of build.
+    let merged = merge_hosts(parse_sample_config(), &known.hosts);
+    let names = merged.iter().map(|h| h.name.as_str()).collect::<Vec<&str>>();
+    assert_eq!(names, vec!["build", "build.lan", "pi", "work", "quoted host", "github.com", "140.82.121.3", "gitlab.local", "gitlab.local"]);
+}
+
+#[test]
+fn hmac() {
+    let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    assert_eq!(hex(&sha1(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    assert_eq!(hex(&sha1(&[b'a'; 1000])), "291e9a6c66994949b57ba5e650361e98fc36b1ba");
+    assert_eq!(hex(&hmac_sha1(b"key", b"The quick brown fox jumps over the lazy dog")), "de7c9b85b8b78aa6bc8a7a36f70a90701c9db4d9");
+}
+
+#[test]
+fn provider_ranks_by_use() {
+    let known = parse_known_hosts(KNOWN_HOSTS);
+    let hosts = merge_hosts(parse_sample_config(), &known.hosts);
+    let mut provider = SshHostProvider::with_hosts(hosts, known, "wt.exe".to_owned());
+
+    provider.set_query("pi".to_owned());
+    assert_eq!(provider.query_for_names()[0], "pi (pi@raspberrypi.local:2222)");
+    assert_eq!(provider.copy_text(0), Some("ssh pi".to_owned()));
+
+    let mut use_counts = SshUseCounts::parse(r#"{"github.com": 3}"#).unwrap();
+    use_counts.record(&SshHost::new("github.com"));
+    assert_eq!(use_counts.get(&SshHost::new("github.com")), 4);
+    assert_eq!(SshUseCounts::parse(&use_counts.to_json().unwrap()).unwrap(), use_counts);
+    provider.set_use_counts(&use_counts);
+    provider.set_query(String::new());
+    assert_eq!(provider.query_for_names()[..3], ["github.com", "build (ci@10.0.0.5)", "build.lan (ci@10.0.0.5)"]);
+
+    // Hashed hosts show up when typed in full.
+    provider.set_query("secret.example.com".to_owned());
+    assert_eq!(provider.query_for_names(), vec!["secret.example.com"]);
+    provider.set_query("secret.example".to_owned());
+    assert_eq!(provider.query_for_names().len(), 0);
+}
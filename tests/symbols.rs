@@ -0,0 +1,50 @@
+use switch::symbols::*;
+
+fn texts(symbols: Vec<&Symbol>) -> Vec<&str> {
+    return symbols.into_iter().map(|s| s.text.as_str()).collect();
+}
+
+// cargo test --test symbols
+#[test]
+fn parse() {
+    let data = "# comment\n\n2713\tcheck mark\tcheck tick yes done\n1F1FA 1F1F8\tflag united states\n00B0\tdegree sign\nzzzz\tbroken\n";
+    assert_eq!(parse_symbols(data), vec![
+        Symbol { text: "✓".to_owned(), name: "check mark".to_owned(), keywords: "check tick yes done".to_owned() },
+        Symbol { text: "🇺🇸".to_owned(), name: "flag united states".to_owned(), keywords: "".to_owned() },
+        Symbol { text: "°".to_owned(), name: "degree sign".to_owned(), keywords: "".to_owned() },
+    ]);
+    assert_eq!(parse_symbols(data)[1].codepoints(), "U+1F1FA U+1F1F8");
+    assert_eq!(parse_symbols(data)[2].codepoints(), "U+00B0");
+
+    // Every line of the embedded table parses.
+    let lines = SYMBOLS_DATA.lines().filter(|l| l.len() > 0 && !l.starts_with('#')).count();
+    assert_eq!(symbols().len(), lines);
+}
+
+#[test]
+fn search_by_name() {
+    let symbols = symbols();
+    let cases: &[(&str, &str)] = &[
+        ("check", "✓"),
+        ("check mark", "✓"),
+        ("tick", "✓"),
+        ("degree", "°"),
+        ("arrow right", "→"),
+        ("right arrow", "→"),
+        ("grinning", "😀"),
+        ("GRINNING FACE", "😀"),
+        ("U+2713", "✓"),
+        ("u+b0", "°"),
+        ("→", "→"),
+    ];
+    for (query, first) in cases {
+        assert_eq!(search(&symbols, query, 10).first().map(|s| s.text.as_str()), Some(*first), "{}", query);
+    }
+
+    // Every word has to match.
+    assert!(texts(search(&symbols, "degree", 10)).contains(&"℃"));
+    assert!(!texts(search(&symbols, "degree fahrenheit", 10)).contains(&"℃"));
+    assert_eq!(search(&symbols, "xyzzy", 10).len(), 0);
+    assert_eq!(search(&symbols, "", 10).len(), 0);
+    assert_eq!(search(&symbols, "arrow", 3).len(), 3);
+}
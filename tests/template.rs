@@ -0,0 +1,101 @@
+use switch::template::*;
+
+fn template(keyword: &str, command: &str) -> LaunchTemplate {
+    return LaunchTemplate {
+        keyword: keyword.into(),
+        command: command.into(),
+        name: String::new(),
+    };
+}
+
+// cargo test --test template
+#[test]
+fn match_query() {
+    let gh = template("gh", "https://github.com/search?q={query}");
+    let cases: &[(&str, Option<&str>)] = &[
+        ("gh", Some("")),
+        ("gh ", Some("")),
+        ("gh rust tui", Some("rust tui")),
+        ("  GH   rust", Some("rust")),
+        ("ghost", None),
+        ("g", None),
+        ("", None),
+        ("rust gh", None),
+    ];
+
+    for (query, expected) in cases {
+        assert_eq!(gh.match_query(query), *expected, "{}", query);
+    }
+}
+
+#[test]
+fn expand_placeholders_in_text() {
+    let mut lookup = |name: &str| match name {
+        "query" => Some("q".to_owned()),
+        "empty" => Some(String::new()),
+        _ => None,
+    };
+
+    let cases: &[(&str, &str)] = &[
+        ("", ""),
+        ("{query}", "q"),
+        ("a {query} b {query}", "a q b q"),
+        ("{empty}x", "x"),
+        ("{unknown}", "{unknown}"),
+        ("{{query}}", "{query}"),
+        ("{{{query}}}", "{q}"),
+        ("{query", "{query"),
+        ("query}", "query}"),
+        ("{ {query}", "{ q"),
+    ];
+
+    for (text, expected) in cases {
+        assert_eq!(expand_placeholders(text, &mut lookup), *expected, "{}", text);
+    }
+}
+
+#[test]
+fn expand_templates() {
+    let mut lookup = |name: &str| match name {
+        "query" => Some("rust tui & more".to_owned()),
+        "clipboard" => Some(r"C:\some dir\file.txt".to_owned()),
+        "selected_dir" => Some(r"C:\src".to_owned()),
+        _ => None,
+    };
+
+    assert_eq!(
+        template("gh", "https://github.com/search?q={query}").expand(&mut lookup),
+        "https://github.com/search?q=rust%20tui%20%26%20more");
+    // Quoting depends on the platform's command line syntax.
+    assert_eq!(
+        template("code", "code {clipboard}").expand(&mut lookup),
+        format!("code {}", switch::cmdline::quote(r"C:\some dir\file.txt")));
+    assert_eq!(
+        template("wt", "wt -d {selected_dir}").expand(&mut lookup),
+        format!("wt -d {}", switch::cmdline::quote(r"C:\src")));
+    assert_eq!(template("echo", "echo {query}").expand(&mut lookup), "echo rust tui & more");
+}
+
+#[test]
+fn url_encode_text() {
+    assert_eq!(url_encode("abc-_.~XYZ09"), "abc-_.~XYZ09");
+    assert_eq!(url_encode("a b/c?d=e&f#g+h"), "a%20b%2Fc%3Fd%3De%26f%23g%2Bh");
+    assert_eq!(url_encode("José"), "Jos%C3%A9");
+}
+
+#[test]
+fn parse_config_templates() {
+    let config = switch::config::Config::parse(r#"{
+        "templates": [
+            { "keyword": "gh", "command": "https://github.com/search?q={query}", "name": "GitHub" },
+            { "keyword": "code", "command": "code {clipboard}" }
+        ]
+    }"#).unwrap();
+
+    assert_eq!(config.templates.len(), 2);
+    assert_eq!(config.templates[0].display_name(), "GitHub");
+    assert_eq!(config.templates[1].display_name(), "code");
+
+    let empty = switch::config::Config::parse("{}").unwrap();
+    assert_eq!(empty.templates.len(), 0);
+}
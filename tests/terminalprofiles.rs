@@ -0,0 +1,88 @@
+use switch::terminalprofiles::*;
+use switch::terminalprofileprovider::TerminalProfileProvider;
+use switch::ListContentProvider;
+
+const SETTINGS: &str = include_str!("data/terminal_settings.json");
+const FRAGMENT: &str = include_str!("data/terminal_fragment.json");
+
+fn names(profiles: Vec<&TerminalProfile>) -> Vec<&str> {
+    return profiles.into_iter().map(|p| p.name.as_str()).collect();
+}
+
+// cargo test --test terminalprofiles
+#[test]
+fn jsonc() {
+    let cases: &[(&str, &str)] = &[
+        ("{\"a\": 1, // comment\n}", "{\"a\": 1  \n}"),
+        ("[1, 2,]", "[1, 2 ]"),
+        ("[1 /* one */, 2]", "[1  , 2]"),
+        ("{\"url\": \"https://example.com\", \"s\": \"a,]\\\"//\"}", "{\"url\": \"https://example.com\", \"s\": \"a,]\\\"//\"}"),
+        ("[[1,],/* x */]", "[[1 ]  ]"),
+    ];
+    for (jsonc, json) in cases {
+        assert_eq!(strip_jsonc(jsonc), *json, "{}", jsonc);
+    }
+}
+
+#[test]
+fn parse_sample_settings() {
+    let profiles = parse_settings(SETTINGS).unwrap();
+    assert_eq!(profiles.len(), 5);
+    assert_eq!(profiles[0], TerminalProfile {
+        name: "Windows PowerShell".to_owned(),
+        guid: Some("{61c54bbd-c2c6-5271-96e7-009a87ff44bf}".to_owned()),
+        source: None,
+        commandline: Some("powershell.exe".to_owned()),
+        hidden: false,
+        default: true,
+    });
+    assert_eq!(profiles[2].source.as_deref(), Some("Windows.Terminal.Wsl"));
+    assert_eq!(profiles[4].commandline.as_deref(), Some("cmd.exe /k \"C:\\code\\vcvars.bat\" // not a comment"));
+
+    // Hidden, or from a disabled dynamic profile source.
+    assert!(profiles[1].hidden);
+    assert!(profiles[3].hidden);
+    assert_eq!(names(visible_profiles(&profiles)), vec!["Windows PowerShell", "Ubuntu", "Build; x64 \"release\""]);
+
+    // The old format with profiles as an array and the default by name.
+    let old = parse_settings("{ \"defaultProfile\": \"cmd\", \"profiles\": [{ \"name\": \"cmd\" }, { \"guid\": \"{x}\" }] }").unwrap();
+    assert_eq!(old.len(), 1);
+    assert!(old[0].default);
+
+    assert!(parse_settings("{ \"profiles\": ").is_err());
+    assert_eq!(parse_settings("{}").unwrap().len(), 0);
+}
+
+#[test]
+fn fragments() {
+    let mut profiles = parse_settings(SETTINGS).unwrap();
+    let fragment = parse_fragment(FRAGMENT, "Git").unwrap();
+    assert_eq!(names(fragment.iter().collect()), vec!["Ubuntu", "Git Bash"]);
+    assert_eq!(fragment[1].source.as_deref(), Some("Git"));
+
+    // Ubuntu is already in settings.json.
+    merge_fragments(&mut profiles, fragment);
+    assert_eq!(names(visible_profiles(&profiles)), vec!["Windows PowerShell", "Ubuntu", "Build; x64 \"release\"", "Git Bash"]);
+    assert_eq!(profiles[2].commandline, None);
+}
+
+#[test]
+fn wt_command_line() {
+    let profiles = parse_settings(SETTINGS).unwrap();
+    assert_eq!(profiles[0].wt_args(false), "-p \"Windows PowerShell\"");
+    assert_eq!(profiles[2].wt_args(false), "-p Ubuntu");
+    assert_eq!(profiles[2].wt_args(true), "-w _quake nt -p Ubuntu");
+    assert_eq!(profiles[4].wt_args(false), r#"-p "Build\; x64 \"release\"""#);
+    assert_eq!(switch::cmdline::split_windows(&profiles[0].wt_args(true)), vec!["-w", "_quake", "nt", "-p", "Windows PowerShell"]);
+
+    let mut provider = TerminalProfileProvider::with_profiles(profiles);
+    assert_eq!(provider.query_for_names(), vec![
+        "Windows PowerShell (default)  powershell.exe",
+        "Ubuntu  Windows.Terminal.Wsl",
+        "Build; x64 \"release\"  cmd.exe /k \"C:\\code\\vcvars.bat\" // not a comment",
+    ]);
+    provider.set_query("ubu".to_owned());
+    assert_eq!(provider.query_for_names(), vec!["Ubuntu  Windows.Terminal.Wsl"]);
+    provider.set_query("wsl".to_owned());
+    assert_eq!(provider.copy_text(0), Some("wt -p Ubuntu".to_owned()));
+}
@@ -0,0 +1,114 @@
+use tui::layout::Rect;
+use tui::style::Color;
+
+use switch::config::Config;
+use switch::theme::*;
+
+// cargo test --test theme
+#[test]
+fn colors() {
+    let cases: &[(&str, Option<Color>)] = &[
+        ("lightgreen", Some(Color::LightGreen)),
+        ("Light Green", Some(Color::LightGreen)),
+        ("dark_gray", Some(Color::DarkGray)),
+        ("#ffd700", Some(Color::Rgb(0xff, 0xd7, 0x00))),
+        ("208", Some(Color::Indexed(208))),
+        ("#ffd70", None),
+        ("256", None),
+        ("chartreuse", None),
+    ];
+    for (text, color) in cases {
+        let parsed = ThemeColor::try_from(text.to_string()).ok().map(|c| c.0);
+        assert_eq!(parsed, *color, "{}", text);
+    }
+    assert_eq!(String::from(ThemeColor(Color::Rgb(1, 2, 255))), "#0102ff");
+}
+
+#[test]
+fn config() {
+    assert_eq!(Theme::from_config(&Config::default().theme), Theme::default());
+
+    let config = Config::parse(r##"{
+        "theme": {
+            "name": "fzf",
+            "prompt": "λ ",
+            "borders": true,
+            "matched": {"fg": "yellow", "underline": true}
+        }
+    }"##).unwrap();
+    let theme = Theme::from_config(&config.theme);
+    assert_eq!(theme.prompt, "λ ");
+    assert!(theme.borders);
+    assert_eq!(theme.matched, ThemeStyle { fg: Some(ThemeColor(Color::Yellow)), underline: true, ..Default::default() });
+    // The rest is from fzf.
+    assert_eq!(theme.input_position, InputPosition::Bottom);
+    assert_eq!(theme.highlight, Theme::named("fzf").unwrap().highlight);
+
+    // A bad color is a broken config, an unknown theme name falls back to the default.
+    assert!(Config::parse(r#"{"theme": {"normal": {"fg": "nope"}}}"#).is_err());
+    assert!(Theme::named("solarized").is_err());
+    let theme = Theme::from_config(&Config::parse(r#"{"theme": {"name": "solarized"}}"#).unwrap().theme);
+    assert_eq!(theme, Theme::default());
+}
+
+#[test]
+fn layout() {
+    let area = Rect::new(0, 0, 20, 10);
+    let cases: &[(&str, bool, bool, Rect, Rect, Rect)] = &[
+        ("default", false, false, Rect::new(0, 0, 20, 1), Rect::new(0, 1, 20, 0), Rect::new(0, 1, 20, 9)),
+        ("default", true, false, Rect::new(1, 1, 18, 1), Rect::new(1, 2, 18, 0), Rect::new(1, 2, 18, 7)),
+        ("fzf", false, false, Rect::new(0, 9, 20, 1), Rect::new(0, 9, 20, 0), Rect::new(0, 0, 20, 9)),
+        ("fzf", true, false, Rect::new(1, 8, 18, 1), Rect::new(1, 8, 18, 0), Rect::new(1, 1, 18, 7)),
+        // The status bar goes between the input line and the list.
+        ("default", false, true, Rect::new(0, 0, 20, 1), Rect::new(0, 1, 20, 1), Rect::new(0, 2, 20, 8)),
+        ("default", true, true, Rect::new(1, 1, 18, 1), Rect::new(1, 2, 18, 1), Rect::new(1, 3, 18, 6)),
+        ("fzf", false, true, Rect::new(0, 9, 20, 1), Rect::new(0, 8, 20, 1), Rect::new(0, 0, 20, 8)),
+        ("fzf", true, true, Rect::new(1, 8, 18, 1), Rect::new(1, 7, 18, 1), Rect::new(1, 1, 18, 6)),
+    ];
+    for (name, borders, status_bar, input, status, list) in cases {
+        let theme = Theme { borders: *borders, status_bar: *status_bar, ..Theme::named(name).unwrap() };
+        assert_eq!(theme.layout(area), (*input, *status, *list), "{} {} {}", name, borders, status_bar);
+    }
+
+    // Too small for anything but the borders.
+    let theme = Theme { borders: true, ..Theme::default() };
+    assert_eq!(theme.layout(Rect::new(0, 0, 2, 2)).2.height, 0);
+    // The list before the status bar.
+    assert_eq!(Theme::default().layout(Rect::new(0, 0, 20, 2)), (Rect::new(0, 0, 20, 1), Rect::new(0, 1, 20, 0), Rect::new(0, 1, 20, 1)));
+}
+
+#[test]
+fn columns() {
+    let rows = [
+        "init  1  12.5 MB".to_string(),
+        "  tmux: server  812  4.3 MB".to_string(),
+        "    bash  813".to_string(),
+        "no columns".to_string(),
+    ];
+    assert_eq!(align_columns(&rows), vec![
+        "init            1    12.5 MB",
+        "  tmux: server  812  4.3 MB",
+        "    bash        813",
+        "no columns",
+    ]);
+    assert_eq!(align_columns(&[]), Vec::<String>::new());
+}
+
+#[test]
+fn match_positions() {
+    let cases: &[(&str, &str, &[usize])] = &[
+        ("", "banana", &[]),
+        // Where the query appears as is over the first fuzzy match.
+        ("an", "banana", &[1, 2]),
+        ("bna", "banana", &[0, 2, 3]),
+        ("FIRE", "Mozilla Firefox", &[8, 9, 10, 11]),
+        ("moz fox", "Mozilla Firefox", &[0, 1, 2, 12, 13, 14]),
+        // Terms that don't match are left out.
+        ("moz xyz", "Mozilla Firefox", &[0, 1, 2]),
+        ("c/w", "C:\\Windows", &[0, 2, 3]),
+        ("é", "café crème", &[3]),
+    ];
+    for (query, text, positions) in cases {
+        assert_eq!(switch::fuzzy::match_positions(query, text), *positions, "{} {}", query, text);
+    }
+}
@@ -0,0 +1,56 @@
+use switch::startappsprovider::AppEntryKind;
+use switch::websearchprovider::*;
+
+fn urls(query: &str) -> Vec<(String, String)> {
+    return search_entries(&default_search_engines(), query).into_iter().map(|e| {
+        match e.kind {
+            AppEntryKind::Command { command } => (e.name, command),
+            _ => panic!("Search entries should be commands"),
+        }
+    }).collect();
+}
+
+// cargo test --test websearch
+#[test]
+fn keyword_selects_engine() {
+    assert_eq!(urls("g rust tui"), vec![
+        ("Google: rust tui".to_owned(), "https://www.google.com/search?q=rust%20tui".to_owned()),
+    ]);
+    assert_eq!(urls("docs tokio"), vec![
+        ("docs.rs: tokio".to_owned(), "https://docs.rs/releases/search?query=tokio".to_owned()),
+    ]);
+    assert_eq!(urls("G   c++ & c#"), vec![
+        ("Google: c++ & c#".to_owned(), "https://www.google.com/search?q=c%2B%2B%20%26%20c%23".to_owned()),
+    ]);
+}
+
+#[test]
+fn unknown_keyword_lists_all_engines() {
+    let results = urls("rust tui");
+    assert_eq!(results.len(), default_search_engines().len());
+    assert_eq!(results[0], ("Google: rust tui".to_owned(), "https://www.google.com/search?q=rust%20tui".to_owned()));
+
+    let results = urls("");
+    assert_eq!(results.len(), default_search_engines().len());
+}
+
+#[test]
+fn configured_engines() {
+    let config = switch::config::Config::parse(r#"{
+        "search_engines": [
+            { "keyword": "jira", "name": "Jira", "command": "https://jira.example.com/browse/{query}" }
+        ]
+    }"#).unwrap();
+
+    let results = search_entries(&config.search_engines, "jira PROJ-123");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].name, "Jira: PROJ-123");
+    match &results[0].kind {
+        AppEntryKind::Command { command } => assert_eq!(command, "https://jira.example.com/browse/PROJ-123"),
+        _ => panic!("Search entries should be commands"),
+    }
+
+    // Without search_engines in config the defaults are used.
+    let config = switch::config::Config::parse("{}").unwrap();
+    assert_eq!(config.search_engines.len(), default_search_engines().len());
+}